@@ -1,17 +1,26 @@
 extern crate std;
 
 use std::io::{self, Read, Write, Seek, SeekFrom};
-use std::fs::File;
-use std::path::Path;
+use std::fs::{self, File, OpenOptions};
+use std::path::{Path, PathBuf};
+use std::vec;
 
 use id3v1;
 use id3v2;
+use id3v2::Version;
+use id3v2::simple::Simple;
+use util;
+use id3v2::frame::{Frame, Field, Encoding};
 
 static DEFAULT_FILE_DISCARD: [&'static [u8]; 11] = [
     b"AENC", b"ETCO", b"EQUA", b"MLLT", b"POSS",
     b"SYLT", b"SYTC", b"RVAD", b"TENC", b"TLEN", b"TSIZ"
 ];
 static PADDING_BYTES: u32 = 2048;
+/// The length of an APEv2 tag's footer, and of its optional header.
+const APE_FOOTER_LEN: u64 = 32;
+/// Extensions of the files which `FileTags::for_each_in_dir` considers to be audio.
+static AUDIO_EXTENSIONS: [&'static str; 3] = ["mp3", "mp2", "mp1"];
 
 //enum Chunk()
 
@@ -32,7 +41,22 @@ where R: Read+Seek
 {
     /// Reads a TaggedFile from a seekable reader.
     pub fn from_seekable(reader: &'a mut R) -> Result<Self, io::Error> {
-        let v2 = try!(id3v2::read_tag(reader));
+        TaggedFile::read_seekable(reader, false)
+    }
+
+    /// Reads a TaggedFile from a seekable reader, reading the ID3v2 tag with
+    /// `id3v2::read_tag_preserving` so that unmodified frames and the tag's
+    /// padding are written back unchanged.
+    pub fn from_seekable_preserving(reader: &'a mut R) -> Result<Self, io::Error> {
+        TaggedFile::read_seekable(reader, true)
+    }
+
+    fn read_seekable(reader: &'a mut R, preserve: bool) -> Result<Self, io::Error> {
+        let v2 = if preserve {
+            try!(id3v2::read_tag_preserving(reader))
+        } else {
+            try!(id3v2::read_tag(reader))
+        };
         let audio_start = match v2
         {
             Some(ref _tag) => try!(reader.seek(SeekFrom::Current(0))),
@@ -119,6 +143,428 @@ where R: Read+Seek
 }
 // }}}
 
+/// The ID3v1 and ID3v2 tags belonging to an audio file.
+pub struct FileTags {
+    /// The ID3v1 tag (combined with ID3v1.1 and Extended ID3v1 data), if any.
+    pub v1: Option<id3v1::Tag>,
+    /// The ID3v2 tag stored at the file's start, if any.
+    pub v2: Option<id3v2::Tag>,
+    path: Option<PathBuf>,
+}
+
+impl FileTags {
+    /// Creates a `FileTags` from the given tags.
+    pub fn from_tags(v1: Option<id3v1::Tag>, v2: Option<id3v2::Tag>) -> FileTags {
+        FileTags { v1: v1, v2: v2, path: None }
+    }
+
+    /// Reads any ID3v2 tag at the start and ID3v1 tag at the end of the file
+    /// at the given path, which is recorded and returned by `path`.
+    pub fn from_path(path: &Path) -> Result<FileTags, io::Error> {
+        let mut file = try!(File::open(path));
+        let mut tags = try!(FileTags::from_reader(&mut file));
+        tags.path = Some(path.to_path_buf());
+        Ok(tags)
+    }
+
+    /// Returns the path of the file the tags were read from, if they were read
+    /// with `from_path`.
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_ref().map(|path| &**path)
+    }
+
+    /// Reads any ID3v2 tag at the start and ID3v1 tag at the end of a seekable
+    /// reader, such as a file or a `Cursor` over data in memory.
+    pub fn from_reader<R: Read + Seek>(reader: &mut R) -> Result<FileTags, io::Error> {
+        let tagged = try!(TaggedFile::from_seekable(reader));
+        Ok(FileTags { v1: tagged.v1, v2: tagged.v2, path: None })
+    }
+
+    /// Reads any ID3v2 tag at the start and ID3v1 tag at the end of a seekable
+    /// reader, remembering the original bytes of each ID3v2 frame and the
+    /// tag's padding. When the tags are stored, frames which were not modified
+    /// are written back byte-for-byte and only mutated frames are reserialized,
+    /// so that the file changes as little as possible.
+    pub fn read_preserving<R: Read + Seek>(reader: &mut R) -> Result<FileTags, io::Error> {
+        let tagged = try!(TaggedFile::from_seekable_preserving(reader));
+        Ok(FileTags { v1: tagged.v1, v2: tagged.v2, path: None })
+    }
+
+    /// Reads the audio data from a seekable reader, excluding any ID3v2 tag
+    /// at its start and any ID3v1 tag, along with its extended data, at its
+    /// end. An APEv2 tag preceding the ID3v1 tag is excluded as well, though
+    /// it is otherwise left alone and kept when the tags are stored.
+    pub fn audio_data<R: Read + Seek>(reader: &mut R) -> Result<Vec<u8>, io::Error> {
+        let mut bounds = try!(TaggedFile::from_seekable(reader)).data_bounds;
+        match try!(ape_tag_start(reader, bounds.end)) {
+            Some(ape_start) if ape_start >= bounds.start => bounds.end = ape_start,
+            _ => (),
+        }
+        read_range(reader, bounds)
+    }
+
+    /// Writes the tags around the given audio data: the ID3v2 tag first,
+    /// followed by the audio, followed by the ID3v1 tag. An empty ID3v2 tag is
+    /// not written, as its header alone would carry no information.
+    fn write_around<W: Write>(&self, writer: &mut W, audio: &[u8]) -> Result<(), io::Error> {
+        if let Some(ref v2) = self.v2 {
+            if v2.is_empty() {
+                debug!("omitting empty ID3v2 tag");
+            } else {
+                try!(v2.write_to(writer, v2.flags().get(id3v2::TagFlag::Unsynchronization)));
+            }
+        }
+        try!(writer.write_all(audio));
+        self.write_v1(writer)
+    }
+
+    /// Writes the ID3v1 tag, preceded by its extended data if it has any.
+    fn write_v1<W: Write>(&self, writer: &mut W) -> Result<(), io::Error> {
+        if let Some(ref v1) = self.v1 {
+            if v1.has_extended_data() {
+                try!(writer.write_all(b"TAG+"));
+                try!(v1.write_extended(writer));
+            }
+            try!(v1.write(writer, v1.track != 0));
+        }
+        Ok(())
+    }
+
+    /// Stores the tags in the file at the given path, replacing any ID3 tags
+    /// already present while preserving the audio data between them. An ID3v2
+    /// tag with no frames is omitted, so storing one removes the file's
+    /// existing ID3v2 tag.
+    ///
+    /// If the new ID3v2 tag fits in the space taken by the file's existing tag
+    /// and its padding, the tags are overwritten in place and the audio data is
+    /// left untouched. Otherwise, the new file is written alongside the
+    /// original and then renamed over it, so the original is left intact if
+    /// writing fails. If there is no file at the path, one containing only the
+    /// tags is created.
+    pub fn store_at_path(&self, path: &Path) -> Result<(), io::Error> {
+        match self.store_in_place(path) {
+            Ok(true) => return Ok(()),
+            Ok(false) => (),
+            Err(ref err) if err.kind() == io::ErrorKind::NotFound => return self.store_new(path),
+            Err(err) => return Err(err),
+        }
+
+        // the data between the tags is kept whole, including any APEv2 tag
+        let audio = {
+            let mut file = try!(File::open(path));
+            let bounds = try!(TaggedFile::from_seekable(&mut file)).data_bounds;
+            try!(read_range(&mut file, bounds))
+        };
+
+        let mut tmp_path = path.as_os_str().to_owned();
+        tmp_path.push(".id3tmp");
+        let written = File::create(&tmp_path).and_then(|mut tmp| {
+            try!(self.write_around(&mut tmp, &*audio));
+            tmp.sync_all()
+        });
+        match written.and_then(|_| fs::rename(&tmp_path, path)) {
+            Ok(()) => Ok(()),
+            Err(err) => {
+                drop(fs::remove_file(&tmp_path));
+                Err(err)
+            },
+        }
+    }
+
+    /// Overwrites the file's existing ID3v2 tag with this one, padded to the
+    /// same size, and replaces its ID3v1 tag. Returns FALSE without modifying
+    /// the file if the file has no ID3v2 tag, if the new tag is empty, or if
+    /// it does not fit.
+    fn store_in_place(&self, path: &Path) -> Result<bool, io::Error> {
+        let v2 = match self.v2 {
+            Some(ref v2) if !v2.is_empty() => v2,
+            _ => return Ok(false),
+        };
+
+        let mut file = try!(OpenOptions::new().read(true).write(true).open(path));
+        let bounds = try!(TaggedFile::from_seekable(&mut file)).data_bounds;
+        if bounds.start == 0 || bounds.start > u32::max_value() as u64 {
+            return Ok(false);
+        }
+
+        let mut tag = vec![];
+        if !try!(v2.write_to_fit(&mut tag, v2.flags().get(id3v2::TagFlag::Unsynchronization), bounds.start as u32)) {
+            debug!("new ID3v2 tag does not fit in {} bytes; rewriting file", bounds.start);
+            return Ok(false);
+        }
+        try!(file.seek(SeekFrom::Start(0)));
+        try!(file.write_all(&*tag));
+
+        let mut v1 = vec![];
+        try!(self.write_v1(&mut v1));
+        try!(file.seek(SeekFrom::Start(bounds.end)));
+        try!(file.write_all(&*v1));
+        try!(file.set_len(bounds.end + v1.len() as u64));
+        try!(file.sync_all());
+        Ok(true)
+    }
+
+    /// Writes the tags to a new file at the given path.
+    fn store_new(&self, path: &Path) -> Result<(), io::Error> {
+        let mut file = try!(File::create(path));
+        try!(self.write_around(&mut file, &[]));
+        file.sync_all()
+    }
+
+    /// Returns the artist, from the ID3v2 tag if it has one and from the
+    /// ID3v1 tag otherwise.
+    pub fn artist(&self) -> Option<String> {
+        self.v2_text(|v2| v2.version().artist_id()).or_else(|| self.v1.as_ref().and_then(|v1| id3v1::decode_text(&*v1.artist)))
+    }
+
+    /// Returns the album, from the ID3v2 tag if it has one and from the ID3v1
+    /// tag otherwise.
+    pub fn album(&self) -> Option<String> {
+        self.v2_text(|v2| v2.version().album_id()).or_else(|| self.v1.as_ref().and_then(|v1| id3v1::decode_text(&*v1.album)))
+    }
+
+    /// Returns the title, from the ID3v2 tag if it has one and from the ID3v1
+    /// tag otherwise.
+    pub fn title(&self) -> Option<String> {
+        self.v2_text(|v2| v2.version().title_id()).or_else(|| self.v1.as_ref().and_then(|v1| id3v1::decode_text(&*v1.title)))
+    }
+
+    /// Returns the track number, from the ID3v2 tag if it has one and from
+    /// the ID3v1.1 track number otherwise.
+    pub fn track(&self) -> Option<u32> {
+        self.v2.as_ref().and_then(|v2| v2.track_pair()).map(|(track, _)| track)
+            .or_else(|| self.v1.as_ref().and_then(|v1| if v1.track != 0 { Some(v1.track as u32) } else { None }))
+    }
+
+    /// Returns the year, from the ID3v2 tag if it has one and from the ID3v1
+    /// tag otherwise.
+    pub fn year(&self) -> Option<usize> {
+        self.v2.as_ref().and_then(|v2| v2.year())
+            .or_else(|| self.v1.as_ref().and_then(|v1| if v1.year.value() != 0 { Some(v1.year.value() as usize) } else { None }))
+    }
+
+    /// Returns the genre, from the ID3v2 tag if it has one and from the ID3v1
+    /// tag otherwise. References to ID3v1 genre numbers in the ID3v2 genre
+    /// frame are resolved to genre names, as given by `genre_names`, and
+    /// several genres are separated by slashes. The ID3v1 extended genre
+    /// string is preferred over the name of the ID3v1 genre number.
+    pub fn genre(&self) -> Option<String> {
+        let v2_genre = self.v2.as_ref().map(|v2| v2.genre_names()).and_then(|names| {
+            if names.is_empty() { None } else { Some(names.join("/")) }
+        });
+        v2_genre.or_else(|| self.v1.as_ref().and_then(|v1| {
+            id3v1::decode_text(&*v1.genre_str).or_else(|| v1.genre_name().map(|name| name.to_owned()))
+        }))
+    }
+
+    /// Returns the text of the first comment, from the ID3v2 tag if it has
+    /// one and from the ID3v1 tag otherwise.
+    pub fn comment(&self) -> Option<String> {
+        self.v2.as_ref().and_then(|v2| v2.comments().into_iter().next()).map(|(_, text)| text)
+            .or_else(|| self.v1.as_ref().and_then(|v1| id3v1::decode_text(&*v1.comment)))
+    }
+
+    /// Returns the frame ID and textual rendering, as given by
+    /// `Frame::display_text`, of every ID3v2 frame which has one, in the order
+    /// of the frames. If there is no ID3v2 tag, the fields of the ID3v1 tag are
+    /// returned as the ID3v2.4 frames they would be converted to.
+    pub fn all_metadata(&self) -> Vec<(String, String)> {
+        let converted;
+        let v2 = match (self.v2.as_ref(), self.v1.as_ref()) {
+            (Some(v2), _) => v2,
+            (None, Some(v1)) => {
+                converted = id3v2::Tag::from_id3v1(v1, Version::V4);
+                &converted
+            },
+            (None, None) => return vec![],
+        };
+        v2.frames.iter().filter_map(|frame| {
+            frame.display_text().map(|text| (String::from_utf8_lossy(frame.id.name()).into_owned(), text))
+        }).collect()
+    }
+
+    /// Returns the text of the ID3v2 text frame with the ID chosen for the
+    /// tag's version, if there is an ID3v2 tag with such a frame.
+    fn v2_text<F: Fn(&id3v2::Tag) -> id3v2::frame::Id>(&self, id: F) -> Option<String> {
+        self.v2.as_ref().and_then(|v2| v2.text_frame_str(id(v2))).map(|text| text.into_owned())
+    }
+
+    /// Copies every picture frame (APIC/PIC) from `other`'s ID3v2 tag into this
+    /// one's, converting the frames to this tag's version as needed. A picture
+    /// with the same picture type and description as a copied picture is
+    /// replaced. If there is no ID3v2 tag, one with the same version as
+    /// `other`'s is created.
+    pub fn copy_pictures_from(&mut self, other: &FileTags) {
+        let source = match other.v2 {
+            Some(ref v2) => v2,
+            None => return,
+        };
+        if self.v2.is_none() {
+            self.v2 = Some(id3v2::Tag::with_version(source.version()));
+        }
+        let target = self.v2.as_mut().unwrap();
+
+        let picture_id = source.version().picture_id();
+        for frame in source.frames.iter().filter(|frame| frame.id == picture_id) {
+            let copy = match convert_picture(frame, target.version()) {
+                Some(copy) => copy,
+                None => {
+                    debug!("skipping malformed picture frame {:?}", frame.id);
+                    continue
+                },
+            };
+            let key = picture_key(&copy);
+            target.frames.retain(|existing| existing.id != copy.id || picture_key(existing) != key);
+            target.frames.push(copy);
+        }
+    }
+
+    /// Reads the tags of each audio file in a directory (and, if `recursive`
+    /// is true, its subdirectories) and passes them to a closure. If the
+    /// closure returns `Ok(true)`, the modified tags are stored back into the
+    /// file with `store_at_path`.
+    ///
+    /// A failure to process one file does not stop the others from being
+    /// processed; instead, the paths which could not be processed are returned
+    /// along with the errors encountered. An error is only returned if `dir`
+    /// itself could not be read.
+    pub fn for_each_in_dir<F>(dir: &Path, recursive: bool, mut f: F) -> Result<Vec<(PathBuf, io::Error)>, io::Error>
+    where F: FnMut(&Path, &mut FileTags) -> Result<bool, io::Error>
+    {
+        let mut failures = vec![];
+        try!(FileTags::visit_dir(dir, recursive, &mut f, &mut failures));
+        Ok(failures)
+    }
+
+    fn visit_dir<F>(dir: &Path, recursive: bool, f: &mut F, failures: &mut Vec<(PathBuf, io::Error)>) -> Result<(), io::Error>
+    where F: FnMut(&Path, &mut FileTags) -> Result<bool, io::Error>
+    {
+        for entry in try!(fs::read_dir(dir)) {
+            let path = match entry {
+                Ok(entry) => entry.path(),
+                Err(err) => { failures.push((dir.to_path_buf(), err)); continue },
+            };
+            let result = if path.is_dir() {
+                if !recursive {
+                    continue
+                }
+                FileTags::visit_dir(&path, recursive, f, failures)
+            } else if FileTags::is_audio_path(&path) {
+                FileTags::process_file(&path, f)
+            } else {
+                continue
+            };
+            if let Err(err) = result {
+                failures.push((path, err));
+            }
+        }
+        Ok(())
+    }
+
+    fn process_file<F>(path: &Path, f: &mut F) -> Result<(), io::Error>
+    where F: FnMut(&Path, &mut FileTags) -> Result<bool, io::Error>
+    {
+        let mut tags = {
+            let mut file = try!(File::open(path));
+            try!(FileTags::from_reader(&mut file))
+        };
+        if try!(f(path, &mut tags)) {
+            try!(tags.store_at_path(path));
+        }
+        Ok(())
+    }
+
+    fn is_audio_path(path: &Path) -> bool {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) => AUDIO_EXTENSIONS.contains(&&*ext.to_lowercase()),
+            None => false,
+        }
+    }
+}
+
+/// Reads the bytes in the given range of a seekable reader.
+fn read_range<R: Read + Seek>(reader: &mut R, range: std::ops::Range<u64>) -> Result<Vec<u8>, io::Error> {
+    try!(reader.seek(SeekFrom::Start(range.start)));
+    let mut data = vec![];
+    try!(reader.by_ref().take(range.end - range.start).read_to_end(&mut data));
+    Ok(data)
+}
+
+/// Returns the offset at which an APEv2 tag ending at `end` starts, if the
+/// tag's footer immediately precedes `end`. The footer records the length of
+/// the tag excluding its optional header, and whether the header is present.
+fn ape_tag_start<R: Read + Seek>(reader: &mut R, end: u64) -> Result<Option<u64>, io::Error> {
+    if end < APE_FOOTER_LEN {
+        return Ok(None);
+    }
+    try!(reader.seek(SeekFrom::Start(end - APE_FOOTER_LEN)));
+    let mut footer = [0u8; APE_FOOTER_LEN as usize];
+    if try!(util::read_fully(reader, &mut footer)) < footer.len() || &footer[..8] != b"APETAGEX" {
+        return Ok(None);
+    }
+
+    // the footer's fields are little-endian
+    let size = footer[12..16].iter().rev().fold(0, |n, &b| n << 8 | b as u64);
+    let has_header = footer[23] & 0x80 != 0;
+    let len = size + if has_header { APE_FOOTER_LEN } else { 0 };
+    if len < APE_FOOTER_LEN || len > end {
+        debug!("ignoring APEv2 footer declaring an impossible length of {}", len);
+        return Ok(None);
+    }
+    Ok(Some(end - len))
+}
+
+/// Returns the picture type and description which identify a picture frame.
+fn picture_key(frame: &Frame) -> (Option<u8>, Option<String>) {
+    let picture_type = match frame.fields.get(2) {
+        Some(&Field::Int8(picture_type)) => Some(picture_type),
+        _ => None,
+    };
+    (picture_type, frame.description_text())
+}
+
+/// Copies a picture frame, converting it to the layout of the given version:
+/// ID3v2.2 stores a three-character image format where later versions store
+/// a MIME type. Returns `None` if the frame does not have a picture layout.
+fn convert_picture(frame: &Frame, version: Version) -> Option<Frame> {
+    if frame.fields.len() != 5 {
+        return None
+    }
+    let mime_type = match frame.fields[1] {
+        Field::Latin1(ref mime_type) => mime_type.clone(),
+        Field::Int24(a, b, c) => util::format_mime_type(&[a, b, c]).into_bytes(),
+        _ => return None,
+    };
+
+    let mut copy = Frame::new(version.picture_id());
+    copy.fields = frame.fields.clone();
+    copy.fields[1] = match version {
+        Version::V2 => {
+            let format = util::mime_type_format(&*String::from_utf8_lossy(&*mime_type));
+            Field::Int24(format[0], format[1], format[2])
+        },
+        Version::V3 | Version::V4 => Field::Latin1(mime_type),
+    };
+    match copy.encoding() {
+        Some(encoding) if !version.encoding_compatible(encoding) => {
+            copy.set_encoding(Encoding::UTF16);
+        },
+        _ => (),
+    }
+    Some(copy)
+}
+
+/// Iterates over the frame IDs and textual renderings returned by
+/// `all_metadata`.
+impl<'a> IntoIterator for &'a FileTags {
+    type Item = (String, String);
+    type IntoIter = vec::IntoIter<(String, String)>;
+
+    fn into_iter(self) -> vec::IntoIter<(String, String)> {
+        self.all_metadata().into_iter()
+    }
+}
+
 // Tests {{{
 #[cfg(test)]
 mod tests {
@@ -136,5 +582,378 @@ mod tests {
         flags.set(Footer, true);
         assert_eq!(flags.to_byte(), 0xF0);
     }
+
+    #[test]
+    fn test_copy_pictures_from() {
+        use id3v2;
+        use id3v2::frame::{Frame, Field, Encoding};
+        use super::FileTags;
+
+        let picture = |id, description: &[u8], data: u8| {
+            let mut frame = Frame::new(id);
+            frame.fields = vec![Field::TextEncoding(Encoding::Latin1), Field::Latin1(b"image/png".to_vec()),
+                Field::Int8(3), Field::String(description.to_vec()), Field::BinaryData(vec![data])];
+            frame
+        };
+
+        let mut source = id3v2::Tag::new();
+        source.add_frame(picture(V4.picture_id(), b"front", 1));
+        source.add_frame(picture(V4.picture_id(), b"back", 2));
+        let source = FileTags::from_tags(None, Some(source));
+
+        let mut target = id3v2::Tag::with_version(V2);
+        let mut old = picture(V4.picture_id(), b"front", 0);
+        assert!(old.convert_version(V2));
+        old.fields[1] = Field::Int24(b'P', b'N', b'G');
+        target.add_frame(old);
+        let mut target = FileTags::from_tags(None, Some(target));
+
+        target.copy_pictures_from(&source);
+        let v2 = target.v2.unwrap();
+        assert_eq!(v2.frames.len(), 2);
+        assert!(v2.frames.iter().all(|frame| frame.id == V2.picture_id()));
+        assert!(v2.frames.iter().all(|frame| frame.fields[1] == Field::Int24(b'P', b'N', b'G')));
+        assert!(v2.frames.iter().all(|frame| frame.fields[4] != Field::BinaryData(vec![0])));
+
+        let mut padded = picture(V4.picture_id(), b"cover", 3);
+        assert!(padded.convert_version(V2));
+        padded.fields[1] = Field::Int24(b'P', b'N', b' ');
+        let mut source = id3v2::Tag::with_version(V2);
+        source.add_frame(padded);
+        let mut target = FileTags::from_tags(None, Some(id3v2::Tag::new()));
+        target.copy_pictures_from(&FileTags::from_tags(None, Some(source)));
+        assert_eq!(target.v2.unwrap().frames[0].fields[1], Field::Latin1(b"image/pn".to_vec()));
+    }
+
+    #[test]
+    fn test_store_empty_v2() {
+        use std::env;
+        use std::fs::{self, File};
+        use std::io::{Read, Write};
+        use id3v2;
+        use id3v2::frame::{Frame, Id, Encoding};
+        use super::FileTags;
+
+        let path = env::temp_dir().join("id3-test-store-empty-v2.mp3");
+        let audio = vec![0xFFu8; 512];
+        let mut tag = id3v2::Tag::new();
+        tag.add_frame(Frame::new_text_frame(Id::V4(*b"TIT2"), "title", Encoding::Latin1).unwrap());
+        {
+            let mut file = File::create(&path).unwrap();
+            tag.write_to(&mut file, false).unwrap();
+            file.write_all(&*audio).unwrap();
+        }
+
+        FileTags::from_tags(None, Some(id3v2::Tag::new())).store_at_path(&path).unwrap();
+        let mut stored = vec![];
+        File::open(&path).unwrap().read_to_end(&mut stored).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(stored, audio);
+    }
+
+    #[test]
+    fn test_v1_fallback() {
+        use std::env;
+        use std::fs::{self, File};
+        use std::io::Write;
+        use id3v1;
+        use id3v2;
+        use id3v2::simple::Simple;
+        use super::FileTags;
+
+        let path = env::temp_dir().join("id3-test-v1-fallback.mp3");
+        let mut v1 = id3v1::Tag::new();
+        v1.artist = b"artist".to_vec();
+        v1.title = b"title  ".to_vec();
+        v1.track = 3;
+        v1.genre = 9;
+        {
+            let mut file = File::create(&path).unwrap();
+            file.write_all(&[0xFF; 512]).unwrap();
+            v1.write(&mut file, true).unwrap();
+        }
+
+        let mut tags = FileTags::from_path(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert!(tags.v2.is_none());
+        assert_eq!(tags.artist(), Some("artist".to_owned()));
+        assert_eq!(tags.title(), Some("title".to_owned()));
+        assert_eq!(tags.album(), None);
+        assert_eq!(tags.track(), Some(3));
+        assert_eq!(tags.year(), None);
+        assert_eq!(tags.genre(), Some("Metal".to_owned()));
+        assert_eq!(tags.comment(), None);
+
+        let mut v2 = id3v2::Tag::new();
+        v2.set_title_enc("new title", ::id3v2::frame::Encoding::Latin1);
+        tags.v2 = Some(v2);
+        assert_eq!(tags.title(), Some("new title".to_owned()));
+        assert_eq!(tags.artist(), Some("artist".to_owned()));
+        assert_eq!(tags.genre(), Some("Metal".to_owned()));
+
+        let mut v2 = id3v2::Tag::new();
+        let id = v2.version().genre_id();
+        v2.add_text_frame(id, "(17)");
+        tags.v2 = Some(v2);
+        assert_eq!(tags.genre(), Some("Rock".to_owned()));
+    }
+
+    #[test]
+    fn test_v2_text_from_file() {
+        use std::env;
+        use std::fs::{self, File};
+        use std::io::Write;
+        use id3v1;
+        use id3v2;
+        use id3v2::frame::Encoding;
+        use super::FileTags;
+
+        for &version in &[V3, V4] {
+            let path = env::temp_dir().join(format!("id3-test-v2-text-from-file-{:?}.mp3", version));
+            let mut v2 = id3v2::Tag::with_version(version);
+            v2.add_text_frame_enc(version.title_id(), "v2 title", Encoding::Latin1);
+            v2.add_text_frame_enc(version.artist_id(), "v2 artist", Encoding::Latin1);
+            v2.add_text_frame_enc(version.album_id(), "v2 album", Encoding::Latin1);
+            v2.add_text_frame_enc(version.year_id(), "2001", Encoding::Latin1);
+            let mut v1 = id3v1::Tag::new();
+            v1.title = b"v1 title".to_vec();
+            v1.artist = b"v1 artist".to_vec();
+            v1.album = b"v1 album".to_vec();
+            v1.year = id3v1::Year::new(1999).unwrap();
+            {
+                let mut file = File::create(&path).unwrap();
+                v2.write_to(&mut file, false).unwrap();
+                file.write_all(&[0xFF; 512]).unwrap();
+                v1.write(&mut file, true).unwrap();
+            }
+
+            let tags = FileTags::from_path(&path).unwrap();
+            fs::remove_file(&path).unwrap();
+            assert_eq!(tags.title(), Some("v2 title".to_owned()));
+            assert_eq!(tags.artist(), Some("v2 artist".to_owned()));
+            assert_eq!(tags.album(), Some("v2 album".to_owned()));
+            assert_eq!(tags.year(), Some(2001));
+        }
+    }
+
+    #[test]
+    fn test_from_reader() {
+        use std::io::Cursor;
+        use id3v1;
+        use id3v2;
+        use id3v2::frame::{Frame, Id, Encoding};
+        use super::FileTags;
+
+        let mut v2 = id3v2::Tag::with_version(V3);
+        v2.add_frame(Frame::new_text_frame(Id::V3(*b"TIT2"), "title", Encoding::Latin1).unwrap());
+        let mut v1 = id3v1::Tag::new();
+        v1.artist = b"artist".to_vec();
+
+        let mut data = vec![];
+        v2.write_to(&mut data, false).unwrap();
+        data.extend(&[0xFF; 512][..]);
+        v1.write(&mut data, false).unwrap();
+
+        let tags = FileTags::from_reader(&mut Cursor::new(&*data)).unwrap();
+        assert_eq!(tags.v2.unwrap().frames.len(), 1);
+        assert_eq!(&tags.v1.unwrap().artist[..6], b"artist");
+        assert!(tags.path.is_none());
+
+        let tags = FileTags::from_reader(&mut Cursor::new(&[0xFF; 512][..])).unwrap();
+        assert!(tags.v1.is_none() && tags.v2.is_none());
+    }
+
+    #[test]
+    fn test_all_metadata() {
+        use id3v1;
+        use id3v2;
+        use id3v2::frame::{Frame, Field, Id, Encoding};
+        use super::FileTags;
+
+        let mut v2 = id3v2::Tag::with_version(V4);
+        v2.add_text_frame(Id::V4(*b"TIT2"), "title");
+        v2.add_frame(Frame::new_url_frame(Id::V4(*b"WOAR"), b"http://example.com/").unwrap());
+        let mut txxx = Frame::new(Id::V4(*b"TXXX"));
+        txxx.fields = vec![Field::TextEncoding(Encoding::UTF8), Field::String(b"mood".to_vec()),
+            Field::String(b"calm".to_vec())];
+        v2.add_frame(txxx);
+        let mut count = Frame::new(Id::V4(*b"PCNT"));
+        count.fields = vec![Field::Int32(0, 0, 0, 1)];
+        v2.add_frame(count);
+        let v1 = || {
+            let mut v1 = id3v1::Tag::new();
+            v1.artist = b"artist".to_vec();
+            v1
+        };
+
+        let tags = FileTags::from_tags(Some(v1()), Some(v2));
+        assert_eq!(tags.all_metadata(), vec![
+            ("TIT2".to_owned(), "title".to_owned()),
+            ("WOAR".to_owned(), "http://example.com/".to_owned()),
+            ("TXXX".to_owned(), "mood: calm".to_owned()),
+        ]);
+
+        let tags = FileTags::from_tags(Some(v1()), None);
+        assert!(tags.all_metadata().contains(&("TPE1".to_owned(), "artist".to_owned())));
+        assert!(FileTags::from_tags(None, None).all_metadata().is_empty());
+    }
+
+    #[test]
+    fn test_into_iter() {
+        use id3v1;
+        use id3v2;
+        use id3v2::frame::Id;
+        use super::FileTags;
+
+        let mut v2 = id3v2::Tag::with_version(V3);
+        v2.add_text_frame(Id::V3(*b"TALB"), "album");
+        let tags = FileTags::from_tags(None, Some(v2));
+        let mut pairs = vec![];
+        for (id, text) in &tags {
+            pairs.push((id, text));
+        }
+        assert_eq!(pairs, vec![("TALB".to_owned(), "album".to_owned())]);
+
+        let mut v1 = id3v1::Tag::new();
+        v1.title = b"title".to_vec();
+        let tags = FileTags::from_tags(Some(v1), None);
+        let pairs: Vec<_> = (&tags).into_iter().collect();
+        assert!(pairs.contains(&("TIT2".to_owned(), "title".to_owned())));
+    }
+
+    #[test]
+    fn test_audio_data() {
+        use std::io::Cursor;
+        use id3v1;
+        use id3v2;
+        use id3v2::frame::{Frame, Id, Encoding};
+        use super::FileTags;
+
+        let audio = vec![0xFFu8; 512];
+        let mut tag = id3v2::Tag::new();
+        tag.add_frame(Frame::new_text_frame(Id::V4(*b"TIT2"), "title", Encoding::Latin1).unwrap());
+        let mut v1 = id3v1::Tag::new();
+        v1.title = b"title".to_vec();
+
+        let mut file = vec![];
+        tag.write_to(&mut file, false).unwrap();
+        file.extend(&audio);
+        v1.write(&mut file, false).unwrap();
+        assert_eq!(FileTags::audio_data(&mut Cursor::new(&*file)).unwrap(), audio);
+
+        let mut file = vec![];
+        tag.write_to(&mut file, false).unwrap();
+        file.extend(&audio);
+        file.extend(b"TAG+");
+        file.extend(&[0; (id3v1::TAGPLUS_OFFSET - id3v1::TAG_OFFSET) as usize - 4][..]);
+        v1.write(&mut file, false).unwrap();
+        assert_eq!(FileTags::audio_data(&mut Cursor::new(&*file)).unwrap(), audio);
+
+        assert_eq!(FileTags::audio_data(&mut Cursor::new(&*audio)).unwrap(), audio);
+    }
+
+    #[test]
+    fn test_audio_data_before_ape_tag() {
+        use std::io::Cursor;
+        use id3v1;
+        use super::FileTags;
+
+        let audio = vec![0xFFu8; 512];
+        let mut v1 = id3v1::Tag::new();
+        v1.title = b"title".to_vec();
+
+        for &has_header in &[false, true] {
+            // a single item, "Title" => "title"
+            let mut items = vec![5, 0, 0, 0, 0, 0, 0, 0];
+            items.extend(b"Title\0title");
+            let size = items.len() as u8 + 32;
+            let flags = if has_header { 0x80 } else { 0 };
+            let mut footer = b"APETAGEX".to_vec();
+            footer.extend(&[0xD0, 0x07, 0, 0, size, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, flags]);
+            footer.extend(&[0; 8]);
+
+            let mut file = audio.clone();
+            if has_header {
+                let mut header = footer.clone();
+                header[23] |= 0x20;
+                file.extend(header);
+            }
+            file.extend(items);
+            file.extend(footer);
+            v1.write(&mut file, false).unwrap();
+            assert_eq!(FileTags::audio_data(&mut Cursor::new(&*file)).unwrap(), audio);
+        }
+
+        // a footer declaring more data than precedes it is ignored
+        let mut file = vec![0xFFu8; 16];
+        file.extend(b"APETAGEX");
+        file.extend(&[0xD0, 0x07, 0, 0, 0xFF, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        file.extend(&[0; 8]);
+        v1.write(&mut file, false).unwrap();
+        assert_eq!(FileTags::audio_data(&mut Cursor::new(&*file)).unwrap().len(), 16 + 32);
+    }
+
+    #[test]
+    fn test_for_each_in_dir() {
+        use std::env;
+        use std::fs::{self, File};
+        use std::io::{self, Read, Write};
+        use std::path::PathBuf;
+        use id3v1;
+        use super::FileTags;
+
+        let dir = env::temp_dir().join("id3-test-for-each-in-dir");
+        drop(fs::remove_dir_all(&dir));
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        for name in &["a.mp3", "notes.txt", "sub/b.mp3", "sub/c.mp2"] {
+            File::create(dir.join(name)).unwrap().write_all(&[0xFF; 512]).unwrap();
+        }
+
+        let mut visited = vec![];
+        let failures = FileTags::for_each_in_dir(&dir, false, |path, _| {
+            visited.push(path.to_path_buf());
+            Ok(false)
+        }).unwrap();
+        assert_eq!(visited, vec![dir.join("a.mp3")]);
+        assert!(failures.is_empty());
+
+        let mut visited = vec![];
+        let failures = FileTags::for_each_in_dir(&dir, true, |path, tags| {
+            visited.push(path.to_path_buf());
+            match path.file_name().unwrap().to_str().unwrap() {
+                "a.mp3" => {
+                    let mut v1 = id3v1::Tag::new();
+                    v1.title = b"title".to_vec();
+                    tags.v1 = Some(v1);
+                    Ok(true)
+                },
+                "c.mp2" => Err(io::Error::new(io::ErrorKind::Other, "rejected")),
+                _ => Ok(false),
+            }
+        }).unwrap();
+        visited.sort();
+        assert_eq!(visited, vec![dir.join("a.mp3"), dir.join("sub/b.mp3"), dir.join("sub/c.mp2")]);
+        let failures: Vec<PathBuf> = failures.into_iter().map(|(path, _)| path).collect();
+        assert_eq!(failures, vec![dir.join("sub/c.mp2")]);
+
+        let mut stored = vec![];
+        File::open(dir.join("a.mp3")).unwrap().read_to_end(&mut stored).unwrap();
+        let mut unchanged = vec![];
+        File::open(dir.join("sub/b.mp3")).unwrap().read_to_end(&mut unchanged).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(stored.len(), 512 + 128);
+        assert_eq!(&stored[512..517], b"TAGti");
+        assert_eq!(unchanged, vec![0xFF; 512]);
+    }
+
+    #[test]
+    fn test_is_audio_path() {
+        use std::path::Path;
+        use super::FileTags;
+        assert!(FileTags::is_audio_path(Path::new("music/song.mp3")));
+        assert!(FileTags::is_audio_path(Path::new("music/SONG.MP2")));
+        assert!(!FileTags::is_audio_path(Path::new("music/cover.jpg")));
+        assert!(!FileTags::is_audio_path(Path::new("music/mp3")));
+    }
 }
 // }}}
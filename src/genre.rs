@@ -0,0 +1,287 @@
+use std::fmt;
+
+macro_rules! genres {
+    ($($variant:ident = $name:expr,)*) => {
+        /// The genres identified by ID3v1 genre numbers, which ID3v2 genre
+        /// frames may refer to: the 80 standard genres, followed by the Winamp
+        /// extensions.
+        #[derive(Debug, PartialEq, Eq, Copy, Clone)]
+        #[allow(missing_docs)]
+        pub enum Genre {
+            $($variant,)*
+        }
+
+        static GENRES: &'static [Genre] = &[$(Genre::$variant,)*];
+        static GENRE_NAMES: &'static [&'static str] = &[$($name,)*];
+    };
+}
+
+genres! {
+    Blues = "Blues",
+    ClassicRock = "Classic Rock",
+    Country = "Country",
+    Dance = "Dance",
+    Disco = "Disco",
+    Funk = "Funk",
+    Grunge = "Grunge",
+    HipHop = "Hip-Hop",
+    Jazz = "Jazz",
+    Metal = "Metal",
+    NewAge = "New Age",
+    Oldies = "Oldies",
+    Other = "Other",
+    Pop = "Pop",
+    RAndB = "R&B",
+    Rap = "Rap",
+    Reggae = "Reggae",
+    Rock = "Rock",
+    Techno = "Techno",
+    Industrial = "Industrial",
+    Alternative = "Alternative",
+    Ska = "Ska",
+    DeathMetal = "Death Metal",
+    Pranks = "Pranks",
+    Soundtrack = "Soundtrack",
+    EuroTechno = "Euro-Techno",
+    Ambient = "Ambient",
+    TripHop = "Trip-Hop",
+    Vocal = "Vocal",
+    JazzFunk = "Jazz+Funk",
+    Fusion = "Fusion",
+    Trance = "Trance",
+    Classical = "Classical",
+    Instrumental = "Instrumental",
+    Acid = "Acid",
+    House = "House",
+    Game = "Game",
+    SoundClip = "Sound Clip",
+    Gospel = "Gospel",
+    Noise = "Noise",
+    AlternRock = "AlternRock",
+    Bass = "Bass",
+    Soul = "Soul",
+    Punk = "Punk",
+    Space = "Space",
+    Meditative = "Meditative",
+    InstrumentalPop = "Instrumental Pop",
+    InstrumentalRock = "Instrumental Rock",
+    Ethnic = "Ethnic",
+    Gothic = "Gothic",
+    Darkwave = "Darkwave",
+    TechnoIndustrial = "Techno-Industrial",
+    Electronic = "Electronic",
+    PopFolk = "Pop-Folk",
+    Eurodance = "Eurodance",
+    Dream = "Dream",
+    SouthernRock = "Southern Rock",
+    Comedy = "Comedy",
+    Cult = "Cult",
+    Gangsta = "Gangsta",
+    Top40 = "Top 40",
+    ChristianRap = "Christian Rap",
+    PopFunk = "Pop/Funk",
+    Jungle = "Jungle",
+    NativeAmerican = "Native American",
+    Cabaret = "Cabaret",
+    NewWave = "New Wave",
+    Psychadelic = "Psychadelic",
+    Rave = "Rave",
+    Showtunes = "Showtunes",
+    Trailer = "Trailer",
+    LoFi = "Lo-Fi",
+    Tribal = "Tribal",
+    AcidPunk = "Acid Punk",
+    AcidJazz = "Acid Jazz",
+    Polka = "Polka",
+    Retro = "Retro",
+    Musical = "Musical",
+    RockAndRoll = "Rock & Roll",
+    HardRock = "Hard Rock",
+    Folk = "Folk",
+    FolkRock = "Folk-Rock",
+    NationalFolk = "National Folk",
+    Swing = "Swing",
+    FastFusion = "Fast Fusion",
+    Bebob = "Bebob",
+    Latin = "Latin",
+    Revival = "Revival",
+    Celtic = "Celtic",
+    Bluegrass = "Bluegrass",
+    Avantgarde = "Avantgarde",
+    GothicRock = "Gothic Rock",
+    ProgressiveRock = "Progressive Rock",
+    PsychedelicRock = "Psychedelic Rock",
+    SymphonicRock = "Symphonic Rock",
+    SlowRock = "Slow Rock",
+    BigBand = "Big Band",
+    Chorus = "Chorus",
+    EasyListening = "Easy Listening",
+    Acoustic = "Acoustic",
+    Humour = "Humour",
+    Speech = "Speech",
+    Chanson = "Chanson",
+    Opera = "Opera",
+    ChamberMusic = "Chamber Music",
+    Sonata = "Sonata",
+    Symphony = "Symphony",
+    BootyBass = "Booty Bass",
+    Primus = "Primus",
+    PornGroove = "Porn Groove",
+    Satire = "Satire",
+    SlowJam = "Slow Jam",
+    Club = "Club",
+    Tango = "Tango",
+    Samba = "Samba",
+    Folklore = "Folklore",
+    Ballad = "Ballad",
+    PowerBallad = "Power Ballad",
+    RhythmicSoul = "Rhythmic Soul",
+    Freestyle = "Freestyle",
+    Duet = "Duet",
+    PunkRock = "Punk Rock",
+    DrumSolo = "Drum Solo",
+    ACapella = "A capella",
+    EuroHouse = "Euro-House",
+    DanceHall = "Dance Hall",
+    Goa = "Goa",
+    DrumAndBass = "Drum & Bass",
+    ClubHouse = "Club-House",
+    Hardcore = "Hardcore",
+    Terror = "Terror",
+    Indie = "Indie",
+    BritPop = "BritPop",
+    AfroPunk = "Afro-Punk",
+    PolskPunk = "Polsk Punk",
+    Beat = "Beat",
+    ChristianGangstaRap = "Christian Gangsta Rap",
+    HeavyMetal = "Heavy Metal",
+    BlackMetal = "Black Metal",
+    Crossover = "Crossover",
+    ContemporaryChristian = "Contemporary Christian",
+    ChristianRock = "Christian Rock",
+    Merengue = "Merengue",
+    Salsa = "Salsa",
+    ThrashMetal = "Thrash Metal",
+    Anime = "Anime",
+    JPop = "JPop",
+    Synthpop = "Synthpop",
+    Abstract = "Abstract",
+    ArtRock = "Art Rock",
+    Baroque = "Baroque",
+    Bhangra = "Bhangra",
+    BigBeat = "Big Beat",
+    Breakbeat = "Breakbeat",
+    Chillout = "Chillout",
+    Downtempo = "Downtempo",
+    Dub = "Dub",
+    EBM = "EBM",
+    Eclectic = "Eclectic",
+    Electro = "Electro",
+    Electroclash = "Electroclash",
+    Emo = "Emo",
+    Experimental = "Experimental",
+    Garage = "Garage",
+    Global = "Global",
+    IDM = "IDM",
+    Illbient = "Illbient",
+    IndustroGoth = "Industro-Goth",
+    JamBand = "Jam Band",
+    Krautrock = "Krautrock",
+    Leftfield = "Leftfield",
+    Lounge = "Lounge",
+    MathRock = "Math Rock",
+    NewRomantic = "New Romantic",
+    NuBreakz = "Nu-Breakz",
+    PostPunk = "Post-Punk",
+    PostRock = "Post-Rock",
+    Psytrance = "Psytrance",
+    Shoegaze = "Shoegaze",
+    SpaceRock = "Space Rock",
+    TropRock = "Trop Rock",
+    WorldMusic = "World Music",
+    Neoclassical = "Neoclassical",
+    Audiobook = "Audiobook",
+    AudioTheatre = "Audio Theatre",
+    NeueDeutscheWelle = "Neue Deutsche Welle",
+    Podcast = "Podcast",
+    IndieRock = "Indie Rock",
+    GFunk = "G-Funk",
+    Dubstep = "Dubstep",
+    GarageRock = "Garage Rock",
+    Psybient = "Psybient",
+}
+
+impl Genre {
+    /// Returns the genre with the given ID3v1 genre number, or `None` if no
+    /// genre is assigned to the number.
+    ///
+    /// # Example
+    /// ```
+    /// use id3::Genre;
+    ///
+    /// assert_eq!(Genre::from_code(9), Some(Genre::Metal));
+    /// assert_eq!(Genre::from_code(255), None);
+    /// ```
+    pub fn from_code(code: u8) -> Option<Genre> {
+        GENRES.get(code as usize).map(|genre| *genre)
+    }
+
+    /// Returns the genre's ID3v1 genre number.
+    pub fn to_code(&self) -> u8 {
+        *self as u8
+    }
+
+    /// Returns the genre with the given name, ignoring ASCII case, or `None`
+    /// if no genre has the name.
+    ///
+    /// # Example
+    /// ```
+    /// use id3::Genre;
+    ///
+    /// assert_eq!(Genre::from_name("rock & roll"), Some(Genre::RockAndRoll));
+    /// assert_eq!(Genre::from_name("Nu Metal"), None);
+    /// ```
+    pub fn from_name(name: &str) -> Option<Genre> {
+        GENRE_NAMES.iter().position(|genre_name| genre_name.eq_ignore_ascii_case(name))
+            .map(|code| GENRES[code])
+    }
+
+    /// Returns the genre's name.
+    pub fn name(&self) -> &'static str {
+        GENRE_NAMES[*self as usize]
+    }
+}
+
+impl fmt::Display for Genre {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str(self.name())
+    }
+}
+
+// Tests {{{
+#[cfg(test)]
+mod tests {
+    use super::Genre;
+
+    #[test]
+    fn test_codes() {
+        assert_eq!(Genre::from_code(9), Some(Genre::Metal));
+        assert_eq!(Genre::Metal.to_code(), 9);
+        assert_eq!(Genre::from_code(0), Some(Genre::Blues));
+        assert_eq!(Genre::from_code(191), Some(Genre::Psybient));
+        assert_eq!(Genre::from_code(192), None);
+        for code in 0..192u32 {
+            assert_eq!(Genre::from_code(code as u8).unwrap().to_code(), code as u8);
+        }
+    }
+
+    #[test]
+    fn test_names() {
+        assert_eq!(Genre::Metal.to_string(), "Metal");
+        assert_eq!(Genre::DrumAndBass.name(), "Drum & Bass");
+        assert_eq!(Genre::from_name("Metal"), Some(Genre::Metal));
+        assert_eq!(Genre::from_name("hip-hop"), Some(Genre::HipHop));
+        assert_eq!(Genre::from_name(""), None);
+    }
+}
+// }}}
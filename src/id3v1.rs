@@ -3,6 +3,9 @@ extern crate byteorder;
 use std::io::{self, Read, Write, Seek, SeekFrom};
 use num::Bounded;
 use std::fmt;
+use genre::Genre;
+use id3v2;
+use util;
 use self::byteorder::{BigEndian, ReadBytesExt};
 
 /// The fields in an ID3v1 tag, including the "1.1" track number field.
@@ -55,6 +58,20 @@ impl XFields {
     }
 }
 
+/// Returns the name of the genre with the given ID3v1 genre number, or `None`
+/// if no genre is assigned to the number.
+///
+/// # Example
+/// ```
+/// use id3::id3v1;
+///
+/// assert_eq!(id3v1::genre_name(9), Some("Metal"));
+/// assert_eq!(id3v1::genre_name(255), None);
+/// ```
+pub fn genre_name(code: u8) -> Option<&'static str> {
+    Genre::from_code(code).map(|genre| genre.name())
+}
+
 /// ID3v1's notion of a four-digit year.
 #[derive(Debug, Copy, Clone)]
 pub struct Year
@@ -63,10 +80,12 @@ pub struct Year
 }
 
 impl Year {
-    fn value(&self) -> u16 {
+    /// Returns the year as a number, 0 if not set.
+    pub fn value(&self) -> u16 {
         self.value
     }
-    fn new(year: u16) -> Option<Year> {
+    /// Creates a year, or returns `None` if it has more than four digits.
+    pub fn new(year: u16) -> Option<Year> {
         #![allow(deprecated)]
         let max: Year = Bounded::max_value();
         if year > max.value() {
@@ -171,6 +190,64 @@ impl Tag {
             genre: 0, speed: 0, genre_str: vec![], start_time: Time::new(0).unwrap(), end_time: Time::new(0).unwrap()
         }
     }
+    /// Creates a tag holding as much of the information in an ID3v2 tag as
+    /// fits: its title, artist, album, year, first comment, track number,
+    /// and genre. Text is encoded as ISO-8859-1, with characters outside it
+    /// replaced by `?`, and truncated to the length of its field. A track
+    /// number which does not fit in a byte is dropped. The genre is set to
+    /// the first of the tag's genres which has an ID3v1 genre number, or to
+    /// 255 (no genre) if there is none.
+    ///
+    /// # Example
+    /// ```
+    /// use id3::{id3v1, id3v2};
+    /// use id3::id3v2::simple::Simple;
+    /// use id3::id3v2::frame::Encoding;
+    ///
+    /// let mut v2 = id3v2::Tag::new();
+    /// v2.set_artist_enc("artist", Encoding::UTF8);
+    ///
+    /// let tag = id3v1::Tag::from_id3v2(&v2);
+    /// assert_eq!(tag.artist, b"artist");
+    /// ```
+    pub fn from_id3v2(v2: &id3v2::Tag) -> Tag {
+        use id3v2::simple::Simple;
+
+        let version = v2.version();
+        let text = |id, field: Fields| -> Vec<u8> {
+            v2.text_frame_str(id).map(|text| encode_text(&*text, field)).unwrap_or_default()
+        };
+
+        let mut tag = Tag::new();
+        tag.title = text(version.title_id(), Fields::Title);
+        tag.artist = text(version.artist_id(), Fields::Artist);
+        tag.album = text(version.album_id(), Fields::Album);
+
+        let year = v2.text_frame_str(version.year_id())
+            .or_else(|| v2.text_frame_str(id3v2::frame::Id::V4(*b"TDRC")));
+        if let Some(year) = year.and_then(|year| year.chars().take(Fields::Year.length()).collect::<String>().parse().ok()) {
+            tag.year = Year::new(year).unwrap_or(tag.year);
+        }
+
+        if let Some((_, comment)) = v2.comments().into_iter().next() {
+            tag.comment = encode_text(&*comment, Fields::Comment);
+        }
+        match v2.track_pair() {
+            Some((track, _)) if track <= u8::max_value() as u32 => tag.track = track as u8,
+            _ => (),
+        }
+        tag.genre = v2.genre_names().iter()
+            .filter_map(|name| Genre::from_name(&*name))
+            .next()
+            .map_or(255, |genre| genre.to_code());
+        tag
+    }
+
+    /// Returns the name of the tag's genre, or `None` if no genre is assigned
+    /// to its genre number.
+    pub fn genre_name(&self) -> Option<&'static str> {
+        genre_name(self.genre)
+    }
     /// Returns whether the tag contains information which would be lost if the extended tag were not written.
     pub fn has_extended_data(&self) -> bool {
         use self::Fields::*;
@@ -192,11 +269,11 @@ impl Tag {
         try!(write_zero_padded(writer, &*self.album, 0, Album.length()));
         try!(write!(writer,"{:04}", self.year.value()));
         if write_track_number {
-            try!(writer.write(&self.comment[..Comment.length()-2]));
+            try!(write_zero_padded(writer, &*self.comment, 0, Comment.length()-2));
             try!(writer.write(&[0]));
             try!(writer.write(&[self.track]));
         } else {
-            try!(writer.write(&self.comment[..Comment.length()]));
+            try!(write_zero_padded(writer, &*self.comment, 0, Comment.length()));
         }
         try!(writer.write(&[self.genre]));
         Ok(())
@@ -365,6 +442,21 @@ pub fn truncate_zeros(mut s: &[u8]) -> &[u8] {
     s
 }
 
+/// Encodes text as ISO-8859-1, truncated to the length of a field.
+fn encode_text(text: &str, field: Fields) -> Vec<u8> {
+    let mut encoded = util::string_to_latin1(text);
+    encoded.truncate(field.length());
+    encoded
+}
+
+/// Decodes a text field padded with zeros or spaces as ISO-8859-1, returning
+/// `None` if the field is empty.
+pub fn decode_text(field: &[u8]) -> Option<String> {
+    util::string_from_latin1(truncate_zeros(field))
+        .map(|text| text.trim_right_matches(' ').to_owned())
+        .and_then(|text| if text.is_empty() { None } else { Some(text) })
+}
+
 /// Read an ID3v1 and any extended tag data, if present, combining extended data
 /// with a previously-read ID3v1 tag. If read_extended is false, does not attempt
 /// to read or merge in extended data.
@@ -386,6 +478,23 @@ pub fn read_seek<R: Read + Seek>(reader: &mut R, read_extended: bool) -> Result<
     Ok(maybe_tag)
 }
 
+/// Read only the genre byte of an ID3v1 tag, if present, without reading the
+/// rest of the tag.
+///
+/// This function seeks to the expected offset of the tag (-TAG_OFFSET relative
+/// to the end of the file) to check for its signature, then to the genre byte
+/// (the last byte of the file).
+pub fn read_genre_only<R: Read + Seek>(reader: &mut R) -> Result<Option<u8>, io::Error> {
+    try!(reader.seek(SeekFrom::End(-TAG_OFFSET)));
+    if !try!(probe_tag(reader)) {
+        return Ok(None)
+    }
+    try!(reader.seek(SeekFrom::End(-1)));
+    let mut genre = [0u8; 1];
+    read_all!(reader, &mut genre);
+    Ok(Some(genre[0]))
+}
+
 /// Read an ID3v1 and any extended tag data, if present, from a reader, 
 /// combining extended data with a previously-read ID3v1 tag.
 ///
@@ -435,3 +544,85 @@ fn test_read() {
     read_xtag(&mut f, &mut tag);
     println!("{:?}", tag);*/
 }
+
+#[test]
+fn test_read_genre_only() {
+    use std::io::Cursor;
+    let mut buf = vec![0u8; 16];
+    buf.extend(b"TAG");
+    buf.extend(&[0u8; TAG_OFFSET as usize - 4]);
+    buf.push(17);
+    assert_eq!(read_genre_only(&mut Cursor::new(&buf[..])).unwrap(), Some(17));
+
+    let notag = [b'x'; TAG_OFFSET as usize];
+    assert_eq!(read_genre_only(&mut Cursor::new(&notag[..])).unwrap(), None);
+}
+
+#[test]
+fn test_genre_name() {
+    assert_eq!(genre_name(0), Some("Blues"));
+    assert_eq!(genre_name(79), Some("Hard Rock"));
+    assert_eq!(genre_name(191), Some("Psybient"));
+    assert_eq!(genre_name(192), None);
+
+    let mut tag = Tag::new();
+    tag.genre = 17;
+    assert_eq!(tag.genre_name(), Some("Rock"));
+}
+
+#[test]
+fn test_from_id3v2() {
+    use id3v2;
+    use id3v2::simple::Simple;
+    use id3v2::frame::Encoding;
+
+    let mut v2 = id3v2::Tag::with_version(id3v2::Version::V3);
+    v2.set_title_enc("a title which is much too long to fit in the field", Encoding::UTF16);
+    v2.set_artist_enc("café ☕", Encoding::UTF16);
+    v2.set_album_enc("album", Encoding::Latin1);
+    v2.set_year(1999);
+    v2.set_comment("eng", "", "comment", Encoding::Latin1);
+    v2.set_track_enc(300, Encoding::Latin1);
+    let id = v2.version().genre_id();
+    v2.add_text_frame_enc(id, "(RX)Hard Rock", Encoding::Latin1);
+
+    let tag = Tag::from_id3v2(&v2);
+    assert_eq!(tag.title, b"a title which is much too long");
+    assert_eq!(tag.artist, b"caf\xE9 ?");
+    assert_eq!(tag.album, b"album");
+    assert_eq!(tag.year.value(), 1999);
+    assert_eq!(tag.comment, b"comment");
+    assert_eq!(tag.track, 0);
+    assert_eq!(tag.genre, 79);
+
+    v2.set_track_enc(12, Encoding::Latin1);
+    v2.remove_frames_by_id(id);
+    let tag = Tag::from_id3v2(&v2);
+    assert_eq!(tag.track, 12);
+    assert_eq!(tag.genre, 255);
+}
+
+#[test]
+fn test_from_id3v2_read() {
+    use id3v2;
+    use id3v2::simple::Simple;
+    use id3v2::frame::Encoding;
+
+    for &version in &[id3v2::Version::V3, id3v2::Version::V4] {
+        let mut v2 = id3v2::Tag::with_version(version);
+        v2.set_title_enc("title", Encoding::UTF16);
+        v2.set_artist_enc("artist", Encoding::Latin1);
+        v2.set_album_enc("album", Encoding::Latin1);
+        v2.set_year(1999);
+
+        let mut written = vec![];
+        v2.write_to(&mut written, false).unwrap();
+        let v2 = id3v2::read_tag(&mut &*written).unwrap().unwrap();
+
+        let tag = Tag::from_id3v2(&v2);
+        assert_eq!(tag.title, b"title");
+        assert_eq!(tag.artist, b"artist");
+        assert_eq!(tag.album, b"album");
+        assert_eq!(tag.year.value(), 1999);
+    }
+}
@@ -1,6 +1,8 @@
 use std::fmt;
 use std::error;
 use std::io;
+use std::str;
+use std::string;
 
 /// Kinds of errors that may occur while performing metadata operations.
 #[derive(Debug)]
@@ -49,6 +51,13 @@ impl Error {
 }
 
 impl error::Error for Error {
+    fn source(&self) -> Option<&(error::Error + 'static)> {
+        match self.kind {
+            ErrorKind::InternalIoError(ref err) => Some(err),
+            _ => None,
+        }
+    }
+
     fn description(&self) -> &str {
         use std::error::Error;
         if self.cause().is_some() {
@@ -67,6 +76,18 @@ impl From<io::Error> for Error {
     }
 }
 
+impl From<string::FromUtf8Error> for Error {
+    fn from(err: string::FromUtf8Error) -> Error {
+        Error::new(ErrorKind::StringDecodingError(err.into_bytes()), "string was not valid UTF-8")
+    }
+}
+
+impl From<str::Utf8Error> for Error {
+    fn from(_: str::Utf8Error) -> Error {
+        Error::new(ErrorKind::StringDecodingError(vec![]), "string was not valid UTF-8")
+    }
+}
+
 impl fmt::Debug for Error {
     fn fmt(&self, out: &mut fmt::Formatter) -> fmt::Result {
         use std::error::Error;
@@ -88,3 +109,29 @@ impl fmt::Display for Error {
         }
     }
 }
+
+// Tests {{{
+#[cfg(test)]
+mod tests {
+    use id3v2::{Error, ErrorKind};
+    use std::error::Error as StdError;
+    use std::io;
+
+    #[test]
+    fn test_from_io_error() {
+        let err: Error = io::Error::new(io::ErrorKind::InvalidInput, "bad").into();
+        assert!(err.is_io_error());
+        assert!(err.source().is_some());
+    }
+
+    #[test]
+    fn test_from_utf8_error() {
+        let err: Error = String::from_utf8(vec![0xC3, 0x28]).unwrap_err().into();
+        match err.kind {
+            ErrorKind::StringDecodingError(ref bytes) => assert_eq!(&**bytes, &[0xC3, 0x28]),
+            _ => panic!("expected a string decoding error"),
+        }
+        assert!(err.source().is_none());
+    }
+}
+// }}}
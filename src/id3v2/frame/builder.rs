@@ -0,0 +1,220 @@
+use id3v2::{Error, ErrorKind};
+use id3v2::frame::{Frame, Id, Encoding, frame_format};
+use id3v2::frame::field::{Field, FieldType, BigNum};
+use util;
+
+/// A value given to a `FrameBuilder`. Text is kept as a string until the
+/// frame's encoding is known.
+#[derive(Debug, Clone)]
+enum Value {
+    Text(String),
+    Field(Field),
+}
+
+/// Builds a frame from values given in the order of the frame's field layout,
+/// as returned by `frame_format`, checking them against the layout when the
+/// frame is built. The text encoding field is filled in from `encoding`, so it
+/// is not given as a value.
+///
+/// # Example
+/// ```
+/// use id3::id3v2::frame::{FrameBuilder, Id, Encoding};
+///
+/// let frame = FrameBuilder::new(Id::V4(*b"COMM"))
+///     .encoding(Encoding::UTF8)
+///     .language(*b"eng")
+///     .text("")
+///     .text("A comment")
+///     .build().unwrap();
+/// assert_eq!(frame.display_text().unwrap(), "A comment");
+///
+/// // a comment needs a language before its description
+/// assert!(FrameBuilder::new(Id::V4(*b"COMM")).text("").text("A comment").build().is_err());
+/// ```
+#[derive(Debug, Clone)]
+pub struct FrameBuilder {
+    id: Id,
+    encoding: Option<Encoding>,
+    values: Vec<Value>,
+}
+
+impl FrameBuilder {
+    /// Creates a builder for a frame with the given ID and no fields.
+    pub fn new(id: Id) -> FrameBuilder {
+        FrameBuilder { id: id, encoding: None, values: vec![] }
+    }
+
+    /// Sets the encoding of the frame's text. The default is the default
+    /// encoding of the ID's version.
+    pub fn encoding(mut self, encoding: Encoding) -> FrameBuilder {
+        self.encoding = Some(encoding);
+        self
+    }
+
+    /// Adds text, to be stored in the frame's encoding, for a string field or
+    /// a single-valued string list field.
+    pub fn text(mut self, text: &str) -> FrameBuilder {
+        self.values.push(Value::Text(text.to_owned()));
+        self
+    }
+
+    /// Adds text, to be stored as Latin-1, for a Latin-1 field or a
+    /// single-valued Latin-1 list field.
+    pub fn latin1(self, text: &str) -> FrameBuilder {
+        self.field(Field::Latin1(util::string_to_latin1(text)))
+    }
+
+    /// Adds a three-letter ISO-639-2 language code for a language field.
+    pub fn language(self, language: [u8; 3]) -> FrameBuilder {
+        self.field(Field::Language(language))
+    }
+
+    /// Adds data for a binary data field.
+    pub fn binary(self, data: Vec<u8>) -> FrameBuilder {
+        self.field(Field::BinaryData(data))
+    }
+
+    /// Adds a value for an 8-bit integer field.
+    pub fn int8(self, n: u8) -> FrameBuilder {
+        self.field(Field::Int8(n))
+    }
+
+    /// Adds a value for a 16-bit integer field.
+    pub fn int16(self, n: u16) -> FrameBuilder {
+        self.field(Field::Int16((n >> 8) as u8, n as u8))
+    }
+
+    /// Adds a value for a 24-bit integer field. Only the low 24 bits of `n`
+    /// are kept.
+    pub fn int24(self, n: u32) -> FrameBuilder {
+        self.field(Field::Int24((n >> 16) as u8, (n >> 8) as u8, n as u8))
+    }
+
+    /// Adds a value for a 32-bit integer field.
+    pub fn int32(self, n: u32) -> FrameBuilder {
+        self.field(Field::Int32((n >> 24) as u8, (n >> 16) as u8, (n >> 8) as u8, n as u8))
+    }
+
+    /// Adds a value for an integer field of 32 bits or more, such as a play
+    /// counter.
+    pub fn int32plus(self, n: BigNum) -> FrameBuilder {
+        self.field(Field::Int32Plus(n))
+    }
+
+    /// Adds a field as it is.
+    pub fn field(mut self, field: Field) -> FrameBuilder {
+        self.values.push(Value::Field(field));
+        self
+    }
+
+    /// Builds the frame, returning an error if the ID is not valid, if the
+    /// encoding is not supported by the ID's version or is set for a frame
+    /// without a text encoding field, or if the values given do not match the
+    /// frame's field layout.
+    pub fn build(self) -> Result<Frame, Error> {
+        if !self.id.is_valid() {
+            return Err(Error::new(ErrorKind::InvalidTag, "invalid frame ID"))
+        }
+        let format = frame_format(self.id).unwrap_or(&[]);
+        let has_encoding = format.contains(&FieldType::TextEncoding);
+        let encoding = match self.encoding {
+            Some(_) if !has_encoding => {
+                return Err(Error::new(ErrorKind::InvalidTag, "frame has no text encoding field"))
+            },
+            Some(encoding) => encoding,
+            None if has_encoding => self.id.version().default_encoding(),
+            None => Encoding::Latin1,
+        };
+        if !self.id.version().encoding_compatible(encoding) {
+            return Err(Error::new(ErrorKind::UnsupportedFeature, "encoding is not supported by the ID's version"))
+        }
+
+        let mut values = self.values.into_iter();
+        let mut fields = Vec::with_capacity(format.len());
+        for &ftype in format.iter() {
+            if ftype == FieldType::TextEncoding {
+                fields.push(Field::TextEncoding(encoding));
+                continue;
+            }
+            let value = match values.next() {
+                Some(value) => value,
+                None => return Err(Error::new(ErrorKind::InvalidTag, "too few fields for the frame"))
+            };
+            let field = match (ftype, value) {
+                (FieldType::String, Value::Text(text)) => Field::String(util::encode_string(&*text, encoding)),
+                (FieldType::StringFull, Value::Text(text)) => Field::StringFull(util::encode_string(&*text, encoding)),
+                (FieldType::StringList, Value::Text(text)) => Field::StringList(vec![util::encode_string(&*text, encoding)]),
+                (FieldType::Latin1Full, Value::Field(Field::Latin1(text))) => Field::Latin1Full(text),
+                (FieldType::Latin1List, Value::Field(Field::Latin1(text))) => Field::Latin1List(vec![text]),
+                (ftype, Value::Field(field)) if field.field_type() == ftype => field,
+                _ => return Err(Error::new(ErrorKind::InvalidTag, "field does not match the frame's field layout"))
+            };
+            fields.push(field);
+        }
+        if values.next().is_some() {
+            return Err(Error::new(ErrorKind::InvalidTag, "too many fields for the frame"))
+        }
+
+        let mut frame = Frame::new(self.id);
+        frame.fields = fields;
+        Ok(frame)
+    }
+}
+
+// Tests {{{
+#[cfg(test)]
+mod tests {
+    use super::FrameBuilder;
+    use id3v2::frame::{Id, Field, Encoding};
+    use id3v2::frame::field::BigNum;
+    use id3v2::ErrorKind;
+    use util;
+
+    #[test]
+    fn test_build_comment() {
+        let frame = FrameBuilder::new(Id::V3(*b"COMM"))
+            .language(*b"eng")
+            .text("desc")
+            .text("comment")
+            .build().unwrap();
+        assert_eq!(frame.fields, vec![
+            Field::TextEncoding(Encoding::UTF16),
+            Field::Language(*b"eng"),
+            Field::String(util::string_to_utf16("desc")),
+            Field::StringFull(util::string_to_utf16("comment")),
+        ]);
+        assert!(frame.fields_match_format());
+    }
+
+    #[test]
+    fn test_build_popularimeter() {
+        let frame = FrameBuilder::new(Id::V4(*b"POPM"))
+            .latin1("someone@example.com")
+            .int8(196)
+            .int32plus(BigNum::new(vec![3]))
+            .build().unwrap();
+        assert_eq!(frame.fields[1], Field::Int8(196));
+        assert!(frame.fields_match_format());
+    }
+
+    #[test]
+    fn test_build_mismatched() {
+        // the language and description are swapped
+        let result = FrameBuilder::new(Id::V4(*b"COMM")).text("desc").language(*b"eng").text("comment").build();
+        match result.unwrap_err().kind {
+            ErrorKind::InvalidTag => {},
+            kind => panic!("unexpected error kind {:?}", kind),
+        }
+
+        assert!(FrameBuilder::new(Id::V4(*b"COMM")).language(*b"eng").text("desc").build().is_err());
+        assert!(FrameBuilder::new(Id::V4(*b"POPM")).latin1("a").int8(1).int32plus(BigNum::new(vec![])).int8(2).build().is_err());
+        assert!(FrameBuilder::new(Id::V4(*b"POPM")).encoding(Encoding::UTF8).latin1("a").int8(1)
+                .int32plus(BigNum::new(vec![])).build().is_err());
+        let result = FrameBuilder::new(Id::V3(*b"COMM")).encoding(Encoding::UTF8).language(*b"eng").text("").text("").build();
+        match result.unwrap_err().kind {
+            ErrorKind::UnsupportedFeature => {},
+            kind => panic!("unexpected error kind {:?}", kind),
+        }
+    }
+}
+// }}}
@@ -0,0 +1,207 @@
+use id3v2::Version;
+use id3v2::frame::Frame;
+use id3v2::frame::field::Field;
+use util;
+
+/// The decoded contents of a CHAP (chapter) frame, which marks a section of
+/// the audio and describes it with frames of its own, such as a title.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Chapter {
+    /// The identifier by which tables of contents refer to the chapter.
+    pub element_id: String,
+    /// The time at which the chapter starts, in milliseconds.
+    pub start_time: u32,
+    /// The time at which the chapter ends, in milliseconds.
+    pub end_time: u32,
+    /// The byte offset in the file at which the chapter starts, or
+    /// 0xFFFFFFFF if the times should be used instead.
+    pub start_offset: u32,
+    /// The byte offset in the file at which the chapter ends, or 0xFFFFFFFF
+    /// if the times should be used instead.
+    pub end_offset: u32,
+    /// The frames describing the chapter.
+    pub frames: Vec<Frame>,
+}
+
+impl Chapter {
+    /// Decodes the fields of a CHAP frame belonging to a tag of the given
+    /// version. Returns `None` if the fields do not have the CHAP layout or if
+    /// the embedded frames cannot be read.
+    pub fn from_fields(fields: &[Field], version: Version) -> Option<Chapter> {
+        match fields {
+            &[Field::Latin1(ref element_id), ref start_time, ref end_time, ref start_offset, ref end_offset,
+                    Field::BinaryData(ref data)] => {
+                let element_id = util::string_from_latin1(&*element_id).unwrap();
+                match (start_time.as_u32(), end_time.as_u32(), start_offset.as_u32(), end_offset.as_u32(),
+                        read_frames(&*data, version)) {
+                    (Some(start_time), Some(end_time), Some(start_offset), Some(end_offset), Some(frames)) => Some(Chapter {
+                        element_id: element_id,
+                        start_time: start_time,
+                        end_time: end_time,
+                        start_offset: start_offset,
+                        end_offset: end_offset,
+                        frames: frames,
+                    }),
+                    _ => None
+                }
+            },
+            _ => None
+        }
+    }
+
+    /// Returns the fields of a CHAP frame holding the chapter.
+    pub fn to_fields(&self) -> Vec<Field> {
+        let int32 = |n: u32| {
+            let bytes = util::u32_to_bytes(n);
+            Field::Int32(bytes[0], bytes[1], bytes[2], bytes[3])
+        };
+        vec![
+            Field::Latin1(util::string_to_latin1(&*self.element_id)),
+            int32(self.start_time),
+            int32(self.end_time),
+            int32(self.start_offset),
+            int32(self.end_offset),
+            Field::BinaryData(write_frames(&*self.frames)),
+        ]
+    }
+}
+
+/// The decoded contents of a CTOC (table of contents) frame, which lists
+/// chapters or nested tables of contents by their element IDs.
+#[derive(Debug, PartialEq, Clone)]
+pub struct TableOfContents {
+    /// The identifier by which other tables of contents refer to this one.
+    pub element_id: String,
+    /// Whether this is the root of the hierarchy of tables of contents.
+    pub top_level: bool,
+    /// Whether the children are meant to be played in the order listed.
+    pub ordered: bool,
+    /// The element IDs of the chapters and tables of contents listed.
+    pub children: Vec<String>,
+    /// The frames describing the table of contents.
+    pub frames: Vec<Frame>,
+}
+
+impl TableOfContents {
+    /// Decodes the fields of a CTOC frame belonging to a tag of the given
+    /// version. Returns `None` if the fields do not have the CTOC layout, if
+    /// fewer children are present than declared, or if the embedded frames
+    /// cannot be read.
+    pub fn from_fields(fields: &[Field], version: Version) -> Option<TableOfContents> {
+        let (element_id, flags, count, data) = match fields {
+            &[Field::Latin1(ref element_id), Field::Int8(flags), Field::Int8(count), Field::BinaryData(ref data)] =>
+                (element_id, flags, count, data),
+            _ => return None
+        };
+
+        let mut children = vec![];
+        let mut data = &data[..];
+        for _ in 0..count {
+            let end = match data.iter().position(|&b| b == 0) {
+                Some(end) => end,
+                None => return None
+            };
+            children.push(util::string_from_latin1(&data[..end]).unwrap());
+            data = &data[end + 1..];
+        }
+
+        read_frames(data, version).map(|frames| TableOfContents {
+            element_id: util::string_from_latin1(&*element_id).unwrap(),
+            top_level: flags & 0x02 != 0,
+            ordered: flags & 0x01 != 0,
+            children: children,
+            frames: frames,
+        })
+    }
+
+    /// Returns the fields of a CTOC frame holding the table of contents. At
+    /// most 255 children can be listed; any more are dropped.
+    pub fn to_fields(&self) -> Vec<Field> {
+        let children = &self.children[..::std::cmp::min(self.children.len(), 255)];
+        let mut data = vec![];
+        for child in children.iter() {
+            data.extend(util::string_to_latin1(&*child));
+            data.push(0);
+        }
+        data.extend(write_frames(&*self.frames));
+
+        let flags = if self.top_level { 0x02 } else { 0 } | if self.ordered { 0x01 } else { 0 };
+        vec![
+            Field::Latin1(util::string_to_latin1(&*self.element_id)),
+            Field::Int8(flags),
+            Field::Int8(children.len() as u8),
+            Field::BinaryData(data),
+        ]
+    }
+}
+
+/// Reads the frames embedded in a CHAP or CTOC frame, stopping at the end of
+/// the data or at padding. Returns `None` if a frame cannot be read.
+fn read_frames(mut data: &[u8], version: Version) -> Option<Vec<Frame>> {
+    let mut frames = vec![];
+    while !data.is_empty() {
+        match Frame::read_from(&mut data, version, false) {
+            Ok((_, Some(frame))) => frames.push(frame),
+            Ok((_, None)) => break,
+            Err(err) => {
+                debug!("invalid embedded frame: {}", err);
+                return None
+            },
+        }
+    }
+    Some(frames)
+}
+
+/// Serializes the frames embedded in a CHAP or CTOC frame.
+fn write_frames(frames: &[Frame]) -> Vec<u8> {
+    let mut data = vec![];
+    for frame in frames.iter() {
+        // serialization only fails if the writer fails to write, and a vec won't
+        drop(frame.write_to(&mut data, false));
+    }
+    data
+}
+
+// Tests {{{
+#[cfg(test)]
+mod tests {
+    use super::{Chapter, TableOfContents};
+    use id3v2::Version::V4;
+    use id3v2::frame::{Frame, Id, Encoding};
+
+    #[test]
+    fn test_chapter_round_trip() {
+        let chapter = Chapter {
+            element_id: "chp0".to_owned(),
+            start_time: 0,
+            end_time: 60000,
+            start_offset: 0xFFFFFFFF,
+            end_offset: 0xFFFFFFFF,
+            frames: vec![Frame::new_text_frame(Id::V4(*b"TIT2"), "Introduction", Encoding::UTF8).unwrap()],
+        };
+        let reread = Chapter::from_fields(&*chapter.to_fields(), V4).unwrap();
+        assert_eq!(reread.element_id, "chp0");
+        assert_eq!(reread.end_time, 60000);
+        assert_eq!(reread.start_offset, 0xFFFFFFFF);
+        assert_eq!(reread.frames.len(), 1);
+        assert_eq!(reread.frames[0].fields_to_bytes(), chapter.frames[0].fields_to_bytes());
+    }
+
+    #[test]
+    fn test_table_of_contents_round_trip() {
+        let toc = TableOfContents {
+            element_id: "toc".to_owned(),
+            top_level: true,
+            ordered: false,
+            children: vec!["chp0".to_owned(), "chp1".to_owned()],
+            frames: vec![],
+        };
+        let fields = toc.to_fields();
+        assert_eq!(TableOfContents::from_fields(&*fields, V4), Some(toc));
+
+        let mut truncated = fields.clone();
+        truncated[2] = ::id3v2::frame::Field::Int8(3);
+        assert!(TableOfContents::from_fields(&*truncated, V4).is_none());
+    }
+}
+// }}}
@@ -0,0 +1,109 @@
+use id3v2::frame::Encoding;
+use id3v2::frame::field::Field;
+use util;
+
+/// The decoded contents of a COMR (commercial) frame, which offers the file
+/// or the release it belongs to for sale.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Commercial {
+    /// The price, as currency codes followed by amounts, several prices being
+    /// separated by slashes, such as "USD0.99/EUR0.89".
+    pub price: String,
+    /// The date until which the price is valid, as eight digits, `YYYYMMDD`.
+    pub valid_until: String,
+    /// The URL or email address of the seller.
+    pub contact_url: String,
+    /// How the file is received when bought, such as 1 for a standard CD
+    /// album or 2 for a compressed audio file.
+    pub received_as: u8,
+    /// The name of the seller.
+    pub seller: String,
+    /// A short description of the product.
+    pub description: String,
+    /// The MIME type of the seller's logo, such as "image/png". Empty if there
+    /// is no logo.
+    pub mime_type: String,
+    /// The image data of the seller's logo. Empty if there is no logo.
+    pub logo: Vec<u8>,
+}
+
+impl Commercial {
+    /// Decodes the fields of a COMR frame. Returns `None` if the fields do not
+    /// have the COMR layout or cannot be decoded.
+    pub fn from_fields(fields: &[Field]) -> Option<Commercial> {
+        let (encoding, price, valid_until, contact_url, received_as, seller, description, mime_type, logo) = match fields {
+            &[Field::TextEncoding(encoding), Field::Latin1(ref price), Field::Latin1(ref valid_until),
+                    Field::Latin1(ref contact_url), Field::Int8(received_as), Field::String(ref seller),
+                    Field::String(ref description), Field::Latin1(ref mime_type), Field::BinaryData(ref logo)] =>
+                (encoding, price, valid_until, contact_url, received_as, seller, description, mime_type, logo),
+            _ => return None
+        };
+        let latin1 = |data: &[u8]| util::string_from_latin1(data);
+        let text = |data: &[u8]| util::string_from_encoding(encoding, data).map(util::clean_decoded);
+        match (latin1(&*price), latin1(&*valid_until), latin1(&*contact_url), text(&*seller), text(&*description),
+               latin1(&*mime_type)) {
+            (Some(price), Some(valid_until), Some(contact_url), Some(seller), Some(description), Some(mime_type)) => {
+                Some(Commercial {
+                    price: price,
+                    valid_until: valid_until,
+                    contact_url: contact_url,
+                    received_as: received_as,
+                    seller: seller,
+                    description: description,
+                    mime_type: mime_type,
+                    logo: logo.clone(),
+                })
+            },
+            _ => None,
+        }
+    }
+
+    /// Returns the fields of a COMR frame holding the offer, with the seller
+    /// and description encoded using the given encoding.
+    pub fn to_fields(&self, encoding: Encoding) -> Vec<Field> {
+        vec![
+            Field::TextEncoding(encoding),
+            Field::Latin1(util::string_to_latin1(&*self.price)),
+            Field::Latin1(util::string_to_latin1(&*self.valid_until)),
+            Field::Latin1(util::string_to_latin1(&*self.contact_url)),
+            Field::Int8(self.received_as),
+            Field::String(util::encode_string(&*self.seller, encoding)),
+            Field::String(util::encode_string(&*self.description, encoding)),
+            Field::Latin1(util::string_to_latin1(&*self.mime_type)),
+            Field::BinaryData(self.logo.clone()),
+        ]
+    }
+}
+
+// Tests {{{
+#[cfg(test)]
+mod tests {
+    use super::Commercial;
+    use id3v2::frame::Encoding;
+    use id3v2::frame::field::Field;
+    use util;
+
+    #[test]
+    fn test_from_fields() {
+        let fields = vec![
+            Field::TextEncoding(Encoding::UTF16),
+            Field::Latin1(b"USD9.99".to_vec()),
+            Field::Latin1(b"20151231".to_vec()),
+            Field::Latin1(b"http://example.com/".to_vec()),
+            Field::Int8(2),
+            Field::String(util::string_to_utf16("Record Store")),
+            Field::String(util::string_to_utf16("The album")),
+            Field::Latin1(b"image/png".to_vec()),
+            Field::BinaryData(vec![0x89, b'P', b'N', b'G']),
+        ];
+        let commercial = Commercial::from_fields(&*fields).unwrap();
+        assert_eq!(&commercial.seller, "Record Store");
+        assert_eq!(&commercial.description, "The album");
+        assert_eq!(commercial.received_as, 2);
+        assert_eq!(commercial.logo, vec![0x89, b'P', b'N', b'G']);
+        assert_eq!(commercial.to_fields(Encoding::UTF16), fields);
+
+        assert!(Commercial::from_fields(&fields[..8]).is_none());
+    }
+}
+// }}}
@@ -1,3 +1,5 @@
+use std::str::FromStr;
+
 /// Text encodings used in ID3v2 frames.
 #[derive(Debug, PartialEq, Copy, Clone)]
 #[repr(u8)]
@@ -12,6 +14,17 @@ pub enum Encoding {
     UTF8 = 3,
 }
 
+/// What to do when text contains characters which the encoding it is being
+/// transcoded to cannot represent. Only Latin-1 cannot represent every
+/// character.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum TextEncodingPolicy {
+    /// Fail rather than lose any characters.
+    Strict,
+    /// Replace characters which cannot be represented with '?' or U+FFFD.
+    Lossy,
+}
+
 impl Encoding
 {
     /// Returns the encoding specified by the given byte value in an encoding field of 
@@ -27,4 +40,75 @@ impl Encoding
             _ => None,
         }
     }
+
+    /// Returns the encoding indicated by the byte order mark at the start of
+    /// the data, along with the length of the byte order mark, if there is
+    /// one. Byte order marks of either endianness indicate `UTF16`, the
+    /// encoding which carries them.
+    pub fn from_bom(data: &[u8]) -> Option<(Encoding, usize)>
+    {
+        match data
+        {
+            &[0xFF, 0xFE, ..] | &[0xFE, 0xFF, ..] => Some((Encoding::UTF16, 2)),
+            &[0xEF, 0xBB, 0xBF, ..] => Some((Encoding::UTF8, 3)),
+            _ => None,
+        }
+    }
+
+    /// Returns the conventional name of the encoding, such as "UTF-16BE".
+    pub fn name(&self) -> &'static str
+    {
+        match *self
+        {
+            Encoding::Latin1 => "Latin1",
+            Encoding::UTF16 => "UTF-16",
+            Encoding::UTF16BE => "UTF-16BE",
+            Encoding::UTF8 => "UTF-8",
+        }
+    }
+}
+
+impl FromStr for Encoding {
+    type Err = ();
+
+    /// Parses the name of an encoding, ignoring case, hyphens and underscores,
+    /// so that "utf-8", "UTF8" and "utf_16be" are all accepted. Latin-1 may also
+    /// be called "ISO-8859-1".
+    fn from_str(s: &str) -> Result<Encoding, ()> {
+        let name: String = s.chars()
+            .filter(|&c| c != '-' && c != '_')
+            .flat_map(|c| c.to_lowercase())
+            .collect();
+        match &name[..] {
+            "latin1" | "iso88591" => Ok(Encoding::Latin1),
+            "utf16" => Ok(Encoding::UTF16),
+            "utf16be" => Ok(Encoding::UTF16BE),
+            "utf8" => Ok(Encoding::UTF8),
+            _ => Err(()),
+        }
+    }
+}
+
+// Tests {{{
+#[cfg(test)]
+mod tests {
+    use super::Encoding;
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!("utf-8".parse(), Ok(Encoding::UTF8));
+        assert_eq!("UTF16BE".parse(), Ok(Encoding::UTF16BE));
+        assert_eq!("utf_16".parse(), Ok(Encoding::UTF16));
+        assert_eq!("ISO-8859-1".parse(), Ok(Encoding::Latin1));
+        assert_eq!("utf-32".parse::<Encoding>(), Err(()));
+        assert_eq!("".parse::<Encoding>(), Err(()));
+    }
+
+    #[test]
+    fn test_name_round_trip() {
+        for &encoding in [Encoding::Latin1, Encoding::UTF16, Encoding::UTF16BE, Encoding::UTF8].iter() {
+            assert_eq!(encoding.name().parse(), Ok(encoding));
+        }
+    }
 }
+// }}}
@@ -0,0 +1,84 @@
+/// Methods of interpolating between the adjustment points of an EQU2 frame.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum Interpolation {
+    /// No interpolation; each adjustment applies until the next point is reached.
+    Band,
+    /// Linear interpolation between adjustment points.
+    Linear,
+}
+
+impl Interpolation {
+    /// Returns the interpolation method with the given EQU2 byte value, if valid.
+    pub fn from_u8(n: u8) -> Option<Interpolation> {
+        match n {
+            0 => Some(Interpolation::Band),
+            1 => Some(Interpolation::Linear),
+            _ => None,
+        }
+    }
+}
+
+/// A single point of an equalisation curve.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct AdjustmentPoint {
+    /// The frequency, in units of 1/2 Hz.
+    pub frequency: u16,
+    /// The volume adjustment, in units of 1/512 dB.
+    pub adjustment: i16,
+}
+
+impl AdjustmentPoint {
+    /// Returns the frequency in Hz.
+    pub fn frequency_hz(&self) -> f32 {
+        self.frequency as f32 / 2.0
+    }
+
+    /// Returns the volume adjustment in dB.
+    pub fn adjustment_db(&self) -> f32 {
+        self.adjustment as f32 / 512.0
+    }
+}
+
+/// The decoded contents of an EQU2 (equalisation) frame.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Equalization {
+    /// How to interpolate between the adjustment points.
+    pub interpolation: Interpolation,
+    /// The identification string describing the equalisation preset.
+    pub identification: Vec<u8>,
+    /// The adjustment points, in the order in which they were stored.
+    pub points: Vec<AdjustmentPoint>,
+}
+
+impl Equalization {
+    /// Decodes the adjustment points stored in the binary body of an EQU2 frame.
+    /// Returns `None` if the body is not a whole number of 4-byte points.
+    pub fn parse_points(data: &[u8]) -> Option<Vec<AdjustmentPoint>> {
+        if data.len() % 4 != 0 {
+            return None
+        }
+        Some(data.chunks(4).map(|point| AdjustmentPoint {
+            frequency: (point[0] as u16) << 8 | point[1] as u16,
+            adjustment: ((point[2] as u16) << 8 | point[3] as u16) as i16,
+        }).collect())
+    }
+}
+
+// Tests {{{
+#[cfg(test)]
+mod tests {
+    use super::{Equalization, AdjustmentPoint};
+
+    #[test]
+    fn test_parse_points() {
+        let points = Equalization::parse_points(&[0x03, 0xE8, 0xFE, 0x00, 0x4E, 0x20, 0x02, 0x00]).unwrap();
+        assert_eq!(points, vec![
+            AdjustmentPoint { frequency: 1000, adjustment: -512 },
+            AdjustmentPoint { frequency: 20000, adjustment: 512 },
+        ]);
+        assert_eq!(points[0].frequency_hz(), 500.0);
+        assert_eq!(points[0].adjustment_db(), -1.0);
+        assert!(Equalization::parse_points(&[0x03, 0xE8, 0xFE]).is_none());
+    }
+}
+// }}}
@@ -0,0 +1,70 @@
+use id3v2::frame::TimestampFormat;
+use id3v2::frame::field::Field;
+use util;
+
+/// The decoded contents of an ETCO (event timing codes) frame, which marks
+/// when key events in the audio, such as the start of the main part or the
+/// end of the intro, occur.
+#[derive(Debug, PartialEq, Clone)]
+pub struct EventTimingCodes {
+    /// The units of the timestamps.
+    pub timestamp_format: TimestampFormat,
+    /// Each event's type along with the time at which it occurs.
+    pub events: Vec<(u8, u32)>,
+}
+
+impl EventTimingCodes {
+    /// Decodes the fields of an ETCO frame. Returns `None` if the fields do
+    /// not have the ETCO layout or if the events are not a whole number of
+    /// 5-byte entries.
+    pub fn from_fields(fields: &[Field]) -> Option<EventTimingCodes> {
+        let (format, data) = match fields {
+            &[Field::Int8(format), Field::BinaryData(ref data)] => (format, data),
+            _ => return None
+        };
+        let timestamp_format = match TimestampFormat::from_u8(format) {
+            Some(timestamp_format) => timestamp_format,
+            None => return None
+        };
+        if data.len() % 5 != 0 {
+            return None
+        }
+        Some(EventTimingCodes {
+            timestamp_format: timestamp_format,
+            events: data.chunks(5).map(|event| {
+                (event[0], event[1..].iter().fold(0, |n, &b| n << 8 | b as u32))
+            }).collect(),
+        })
+    }
+
+    /// Returns the fields of an ETCO frame holding the events.
+    pub fn to_fields(&self) -> Vec<Field> {
+        let mut data = vec![];
+        for &(event_type, timestamp) in self.events.iter() {
+            data.push(event_type);
+            data.extend(&util::u32_to_bytes(timestamp));
+        }
+        vec![Field::Int8(self.timestamp_format.to_u8()), Field::BinaryData(data)]
+    }
+}
+
+// Tests {{{
+#[cfg(test)]
+mod tests {
+    use super::EventTimingCodes;
+    use id3v2::frame::TimestampFormat;
+    use id3v2::frame::field::Field;
+
+    #[test]
+    fn test_from_fields() {
+        let fields = vec![Field::Int8(2), Field::BinaryData(vec![0x02, 0, 0, 0x03, 0xE8, 0xFD, 0, 0x01, 0, 0])];
+        let codes = EventTimingCodes::from_fields(&*fields).unwrap();
+        assert_eq!(codes.timestamp_format, TimestampFormat::Milliseconds);
+        assert_eq!(codes.events, vec![(0x02, 1000), (0xFD, 0x01000000)]);
+        assert_eq!(codes.to_fields(), fields);
+
+        assert!(EventTimingCodes::from_fields(&[Field::Int8(2), Field::BinaryData(vec![0x02, 0])]).is_none());
+        assert!(EventTimingCodes::from_fields(&[Field::Int8(0), Field::BinaryData(vec![])]).is_none());
+    }
+}
+// }}}
@@ -87,6 +87,47 @@ impl BigNum {
         BigNum::drop_leading_zeros(&mut data);
         BigNum {data: data}
     }
+    /// Creates a bignum from its big-endian binary representation, as stored
+    /// in counter fields.
+    pub fn from_be_bytes(bytes: &[u8]) -> BigNum {
+        let mut data: Vec<u8> = vec![];
+        for &byte in bytes {
+            // multiply by 256 and add the byte, limb by limb
+            let mut carry = byte as u32;
+            for limb in data.iter_mut() {
+                let value = *limb as u32 * 256 + carry;
+                *limb = (value % 100) as u8;
+                carry = value / 100;
+            }
+            while carry > 0 {
+                data.push((carry % 100) as u8);
+                carry /= 100;
+            }
+        }
+        BigNum::new(data)
+    }
+    /// Returns the big-endian binary representation of the bignum, as stored
+    /// in counter fields, which are at least 4 bytes long.
+    pub fn to_be_bytes(&self) -> Vec<u8> {
+        let mut limbs = self.data.clone();
+        let mut bytes = vec![];
+        while !limbs.is_empty() {
+            // divide by 256, keeping the remainder as the next byte
+            let mut remainder = 0u32;
+            for limb in limbs.iter_mut().rev() {
+                let value = remainder * 100 + *limb as u32;
+                *limb = (value / 256) as u8;
+                remainder = value % 256;
+            }
+            bytes.push(remainder as u8);
+            BigNum::drop_leading_zeros(&mut limbs);
+        }
+        while bytes.len() < 4 {
+            bytes.push(0);
+        }
+        bytes.reverse();
+        bytes
+    }
     /// Increments the value stored in the bignum by 1.
     pub fn incr(&mut self) {
         for i in &mut self.data {
@@ -202,6 +243,18 @@ fn test_bignum_incr() {
     assert_eq!(b, BigNum::new(vec![00, 1]));
 }
 
+#[test]
+fn test_bignum_bytes() {
+    assert_eq!(BigNum::from_be_bytes(&[0, 0, 0, 0]), BigNum::new(vec![0]));
+    assert_eq!(BigNum::from_be_bytes(&[0x01, 0x00]).to_string(), "256");
+    assert_eq!(BigNum::from_be_bytes(&[0xFF, 0xFF, 0xFF, 0xFF]).to_string(), "4294967295");
+    assert_eq!(BigNum::from_be_bytes(&[0x01, 0x00, 0x00, 0x00, 0x00]).to_string(), "4294967296");
+
+    assert_eq!(BigNum::new(vec![]).to_be_bytes(), vec![0, 0, 0, 0]);
+    assert_eq!("256".parse::<BigNum>().unwrap().to_be_bytes(), vec![0, 0, 0x01, 0x00]);
+    assert_eq!("4294967296".parse::<BigNum>().unwrap().to_be_bytes(), vec![0x01, 0, 0, 0, 0]);
+}
+
 #[test]
 fn test_bignum_roundtrip() {
     let mut x = "0009954".parse::<BigNum>().unwrap();
@@ -215,7 +268,7 @@ fn test_bignum_roundtrip() {
 /// A parsed ID3v2 field, which is the atomic component from which frames are
 /// composed, and which stores one primitive or a list of homogeneous string primitives.
 #[allow(missing_docs)]
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub enum Field {
     TextEncoding(Encoding),
     Latin1(Vec<u8>),
@@ -236,13 +289,72 @@ pub enum Field {
 }
 
 impl Field {
+    /// Returns the value of an integer field (`Int8`, `Int16`, `Int24`, or
+    /// `Int32`), whose bytes are stored big-endian. Returns `None` for other
+    /// fields.
+    pub fn as_u32(&self) -> Option<u32> {
+        use self::Field::*;
+        match *self {
+            Int8(a) => Some(a as u32),
+            Int16(a, b) => Some((a as u32) << 8 | b as u32),
+            Int24(a, b, c) => Some((a as u32) << 16 | (b as u32) << 8 | c as u32),
+            Int32(a, b, c, d) => Some((a as u32) << 24 | (b as u32) << 16 | (c as u32) << 8 | d as u32),
+            _ => None,
+        }
+    }
+
+    /// Creates an integer field of the given type holding `n`, split into
+    /// big-endian bytes. Returns `None` if the type is not `Int8`, `Int16`,
+    /// `Int24`, or `Int32`, or if `n` is too large to fit in it.
+    pub fn from_u32(ty: FieldType, n: u32) -> Option<Field> {
+        let bytes = util::u32_to_bytes(n);
+        match ty {
+            FieldType::Int8 if n <= 0xFF => Some(Field::Int8(bytes[3])),
+            FieldType::Int16 if n <= 0xFFFF => Some(Field::Int16(bytes[2], bytes[3])),
+            FieldType::Int24 if n <= 0xFFFFFF => Some(Field::Int24(bytes[1], bytes[2], bytes[3])),
+            FieldType::Int32 => Some(Field::Int32(bytes[0], bytes[1], bytes[2], bytes[3])),
+            _ => None,
+        }
+    }
+
+    /// Returns the type of the field.
+    pub fn field_type(&self) -> FieldType {
+        match *self {
+            Field::TextEncoding(_) => FieldType::TextEncoding,
+            Field::Latin1(_) => FieldType::Latin1,
+            Field::Latin1Full(_) => FieldType::Latin1Full,
+            Field::Latin1List(_) => FieldType::Latin1List,
+            Field::String(_) => FieldType::String,
+            Field::StringFull(_) => FieldType::StringFull,
+            Field::StringList(_) => FieldType::StringList,
+            Field::Language(_) => FieldType::Language,
+            Field::FrameIdV2(_) => FieldType::FrameIdV2,
+            Field::FrameIdV34(_) => FieldType::FrameIdV34,
+            Field::Int8(_) => FieldType::Int8,
+            Field::Int16(..) => FieldType::Int16,
+            Field::Int24(..) => FieldType::Int24,
+            Field::Int32(..) => FieldType::Int32,
+            Field::Int32Plus(_) => FieldType::Int32Plus,
+            Field::BinaryData(_) => FieldType::BinaryData,
+        }
+    }
+
     /// Write the field to the given writer. If @unsync is true, any byte patterns
     /// of the form "%11111111 111xxxxx" are written as "%11111111 00000000 111xxxxx".
     /// Can only fail due to errors originating in the writer itself, rather than 
     /// serialization.
+    ///
+    /// A false sync may also span the boundary between two fields, so frames
+    /// written with the unsynchronization flag are unsynchronized as a whole
+    /// rather than field by field.
     pub fn serialize<W: Write>(&self, writer: &mut W, encoding: Option<Encoding>, is_last: bool, unsync: bool) -> io::Result<()> {
         use self::Field::*;
-        //TODO(sp3d): support unsync!
+        if unsync {
+            let mut buf = vec![];
+            try!(self.serialize(&mut buf, encoding, is_last, false));
+            util::unsynchronize(&mut buf);
+            return writer.write_all(&*buf);
+        }
         match *self
         {
             TextEncoding(ref enc) => try!(writer.write(&[*enc as u8])),
@@ -252,14 +364,25 @@ impl Field {
                     try!(writer.write(util::delim(Encoding::Latin1)))
                 }else{0}
             },
-            Latin1List(ref strs) => try!(writer.write(&*strs[0])),//TODO(sp3d): this is wrong.
+            Latin1List(ref strs) => {
+                try!(Field::write_delimited(writer, strs, Encoding::Latin1));
+                if !is_last {
+                    try!(writer.write(util::delim(Encoding::Latin1)))
+                }else{0}
+            },
             String(ref s)|StringFull(ref s) => {
                 try!(writer.write(&*s));
                 if !is_last {
                     try!(writer.write(util::delim(encoding.expect("String fields' encoding must be specified for serialization"))))
                 }else{0}
             },
-            StringList(ref strs) => try!(writer.write(&*strs[0])),//TODO(sp3d): this is wrong.
+            StringList(ref strs) => {
+                let encoding = encoding.expect("String fields' encoding must be specified for serialization");
+                try!(Field::write_delimited(writer, strs, encoding));
+                if !is_last {
+                    try!(writer.write(util::delim(encoding)))
+                }else{0}
+            },
             Language(ref lang) => try!(writer.write(&*lang)),
             FrameIdV2(ref id) => try!(writer.write(&*id)),
             FrameIdV34(ref id) => try!(writer.write(&*id)),
@@ -267,12 +390,53 @@ impl Field {
             Int16(b1, b0) => try!(writer.write(&[b1,b0])),
             Int24(b2, b1, b0) => try!(writer.write(&[b2,b1,b0])),
             Int32(b3, b2, b1, b0) => try!(writer.write(&[b3,b2,b1,b0])),
-            Int32Plus(ref bignum) => try!(writer.write(&*bignum.data)),
+            Int32Plus(ref bignum) => try!(writer.write(&*bignum.to_be_bytes())),
             BinaryData(ref data) => try!(writer.write(&*data)),
         };
         Ok(())
     }
 
+    /// Returns the number of bytes `serialize` writes for the field, without
+    /// serializing it.
+    pub fn serialized_len(&self, encoding: Option<Encoding>, is_last: bool) -> usize {
+        use self::Field::*;
+        let delimited_len = |strs: &[Vec<u8>], encoding: Encoding| {
+            strs.iter().map(|s| s.len()).sum::<usize>() + strs.len().saturating_sub(1) * util::delim_len(encoding)
+        };
+        let (len, delim_encoding) = match *self {
+            TextEncoding(_) | Int8(_) => (1, None),
+            Latin1(ref s) | Latin1Full(ref s) => (s.len(), Some(Encoding::Latin1)),
+            Latin1List(ref strs) => (delimited_len(strs, Encoding::Latin1), Some(Encoding::Latin1)),
+            String(ref s) | StringFull(ref s) => {
+                (s.len(), Some(encoding.expect("String fields' encoding must be specified for serialization")))
+            },
+            StringList(ref strs) => {
+                let encoding = encoding.expect("String fields' encoding must be specified for serialization");
+                (delimited_len(strs, encoding), Some(encoding))
+            },
+            Language(_) | FrameIdV2(_) | Int24(..) => (3, None),
+            FrameIdV34(_) | Int32(..) => (4, None),
+            Int16(..) => (2, None),
+            Int32Plus(ref bignum) => (bignum.to_be_bytes().len(), None),
+            BinaryData(ref data) => (data.len(), None),
+        };
+        match delim_encoding {
+            Some(encoding) if !is_last => len + util::delim_len(encoding),
+            _ => len,
+        }
+    }
+
+    /// Writes a list of strings separated by the delimiter for the given encoding.
+    fn write_delimited<W: Write>(writer: &mut W, strs: &[Vec<u8>], encoding: Encoding) -> io::Result<()> {
+        for (i, s) in strs.iter().enumerate() {
+            if i > 0 {
+                try!(writer.write(util::delim(encoding)));
+            }
+            try!(writer.write(&*s));
+        }
+        Ok(())
+    }
+
     /// Read a sequence of bytes until `delim_len` consecutive zero bytes are read
     /// or max_len bytes are read, whichever comes first. Reads but discards the
     /// sequence of zero bytes.
@@ -317,6 +481,28 @@ impl Field {
         (buf, Some(consecutive) == delim_len)
     }
 
+    /// Splits a buffer into the strings separated by delimiters of `delim_len`
+    /// zero bytes. Delimiters are only recognized at offsets which are a
+    /// multiple of `delim_len` from the start of the current string, so that
+    /// zero bytes within UTF-16 code units are not mistaken for delimiters. A
+    /// terminal delimiter does not produce a trailing empty string.
+    fn split_delimited(buf: &[u8], delim_len: usize) -> Vec<Vec<u8>> {
+        let mut strs = vec![];
+        let mut start = 0;
+        let mut i = 0;
+        while i + delim_len <= buf.len() {
+            if buf[i..i + delim_len].iter().all(|&b| b == 0) {
+                strs.push(buf[start..i].to_vec());
+                start = i + delim_len;
+            }
+            i += delim_len;
+        }
+        if start < buf.len() || strs.is_empty() {
+            strs.push(buf[start..].to_vec());
+        }
+        strs
+    }
+
     /// Attempt to read a field of the given type. If the field is malformed,
     /// writes the bytes which could not be parsed to the given writer, if any.
     pub fn parse<R: Read, W: Write>(reader: &mut R, ftype: FieldType, encoding: Option<Encoding>, len: usize, is_last: bool, unparsable: Option<&mut W>) -> io::Result<Field> {
@@ -374,7 +560,7 @@ impl Field {
         //on non-final fields, delimiters are mandatory for delimited field types
         if !is_last {
             if delim_len.is_some() && !saw_delim {
-                return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("No delimiter encountered for stringlike ({:?}) field", ftype)))
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("No delimiter encountered for {} field", ftype.name())))
             }
         }
 
@@ -382,7 +568,7 @@ impl Field {
             TextEncoding => {
                 match Encoding::from_u8(buf[0]) {
                     Some(encoding) => Ok(Field::TextEncoding(encoding)),
-                    None => Err(io::Error::new(io::ErrorKind::InvalidInput, "Invalid encoding specifier")),
+                    None => Err(io::Error::new(io::ErrorKind::InvalidInput, format!("Invalid encoding specifier {:#04x}", buf[0]))),
                 }
             },
             Latin1 => {
@@ -393,8 +579,8 @@ impl Field {
                 Ok(Field::Latin1Full(buf.to_vec()))
             },
             Latin1List => {
-                //TODO(sp3d): check encoding? reject newlines? is this right?
-                Ok(Field::Latin1List(vec![buf.to_vec()]))
+                //TODO(sp3d): check encoding? reject newlines?
+                Ok(Field::Latin1List(Field::split_delimited(buf, 1)))
             },
             String => {
                 //TODO(sp3d): reject newlines? check encoding?
@@ -405,16 +591,10 @@ impl Field {
                 Ok(Field::StringFull(buf.to_vec()))
             },
             StringList => {
-                //TODO(sp3d): check encoding? reject newlines? is this right?
-                //buf.split(delim)
-                Ok(Field::StringList(vec![buf.to_vec()]))
-                /*let mut strings = vec![];
-                let mut remaining = len - len_read;
-                while remaining > 0 {
-                    let read_vec = read_until_delim(reader, delim_len, remaining);
-                    remaining -= read_vec.len();
-                }*/
-            },//panic!("how the heck do you encode a stringlist even tho"),
+                //TODO(sp3d): check encoding? reject newlines?
+                let delim_len = util::delim_len(encoding.expect("String fields' encoding must be specified for parsing"));
+                Ok(Field::StringList(Field::split_delimited(buf, delim_len)))
+            },
             Language => {
                 let mut lang = [0u8; 3];
                 for (i, j) in &mut lang.iter_mut().zip(buf.iter())
@@ -452,7 +632,7 @@ impl Field {
                 Ok(Field::Int32(buf[0], buf[1], buf[2], buf[3]))
             },
             Int32Plus => {
-                Ok(Field::Int32Plus(BigNum::new(buf.to_vec())))
+                Ok(Field::Int32Plus(BigNum::from_be_bytes(buf)))
             },
             BinaryData =>  {
                 Ok(Field::BinaryData(buf.to_vec()))
@@ -461,3 +641,51 @@ impl Field {
     }
     //let unused: Vec<u8> = buf.slice_from(len_read).to_vec();
 }
+
+#[test]
+fn test_parse_string_list() {
+    let parse = |data: &[u8], encoding| {
+        let out: Option<&mut Vec<u8>> = None;
+        Field::parse(&mut &*data, FieldType::StringList, Some(encoding), data.len(), true, out).unwrap()
+    };
+
+    let list = parse(b"first\0second", Encoding::Latin1);
+    assert_eq!(list, Field::StringList(vec![b"first".to_vec(), b"second".to_vec()]));
+    let list = parse(b"first\0second\0", Encoding::UTF8);
+    assert_eq!(list, Field::StringList(vec![b"first".to_vec(), b"second".to_vec()]));
+    assert_eq!(parse(b"", Encoding::UTF8), Field::StringList(vec![vec![]]));
+
+    // the zero high byte of 'a' must not be taken for part of a delimiter
+    let utf16 = [0xFF, 0xFE, b'a', 0x00, 0x00, 0x00, 0xFF, 0xFE, b'b', 0x00];
+    assert_eq!(parse(&utf16, Encoding::UTF16), Field::StringList(vec![
+        vec![0xFF, 0xFE, b'a', 0x00], vec![0xFF, 0xFE, b'b', 0x00]]));
+}
+
+#[test]
+fn test_integer_fields() {
+    assert_eq!(Field::Int8(0x12).as_u32(), Some(0x12));
+    assert_eq!(Field::Int16(0x12, 0x34).as_u32(), Some(0x1234));
+    assert_eq!(Field::Int24(0x12, 0x34, 0x56).as_u32(), Some(0x123456));
+    assert_eq!(Field::Int32(0x12, 0x34, 0x56, 0x78).as_u32(), Some(0x12345678));
+    assert_eq!(Field::BinaryData(vec![0x12]).as_u32(), None);
+
+    for &(ty, n) in &[(FieldType::Int8, 0xFE), (FieldType::Int16, 0xFEDC), (FieldType::Int24, 0xFEDCBA), (FieldType::Int32, 0xFEDCBA98)] {
+        assert_eq!(Field::from_u32(ty, n).unwrap().as_u32(), Some(n));
+    }
+    assert_eq!(Field::from_u32(FieldType::Int24, 0x123456), Some(Field::Int24(0x12, 0x34, 0x56)));
+    assert_eq!(Field::from_u32(FieldType::Int16, 0x10000), None);
+    assert_eq!(Field::from_u32(FieldType::String, 1), None);
+}
+
+#[test]
+fn test_serialize_unsync() {
+    let serialize = |field: &Field, unsync: bool| {
+        let mut out = vec![];
+        field.serialize(&mut out, None, true, unsync).unwrap();
+        out
+    };
+    let field = Field::BinaryData(vec![0xFF, 0xFB, 0x90, 0xFF, 0x00, 0xFF]);
+    assert_eq!(serialize(&field, false), vec![0xFF, 0xFB, 0x90, 0xFF, 0x00, 0xFF]);
+    assert_eq!(serialize(&field, true), vec![0xFF, 0x00, 0xFB, 0x90, 0xFF, 0x00, 0x00, 0xFF]);
+    assert_eq!(serialize(&Field::Int16(0xFF, 0x12), true), vec![0xFF, 0x12]);
+}
@@ -124,8 +124,10 @@ static FRAME_INFO_V34: phf::Map<[u8; 4], FrameInfo<'static>> = phf_map! {
     [65, 69, 78, 67] => frame_info!([Latin1,Int16,Int16,BinaryData,], "Audio encryption"),
     [65, 80, 73, 67] => frame_info!([TextEncoding,Latin1,Int8,String,BinaryData,], "Attached picture"),
 
+    [67, 72, 65, 80] => frame_info!([Latin1,Int32,Int32,Int32,Int32,BinaryData,], "Chapter"),
     [67, 79, 77, 77] => frame_info!([TextEncoding,Language,String,StringFull,], "Comments"),
     [67, 79, 77, 82] => frame_info!([TextEncoding,Latin1,Latin1,Latin1,Int8,String,String,Latin1,BinaryData,], "Commercial frame"),
+    [67, 84, 79, 67] => frame_info!([Latin1,Int8,Int8,BinaryData,], "Table of contents"),
 
     [69, 78, 67, 82] => frame_info!([Latin1,Int8,BinaryData,], "Encryption method registration"),
     [69, 84, 67, 79] => frame_info!([Int8,BinaryData,], "Event timing codes"),
@@ -235,6 +237,9 @@ pub fn frame_description(id: Id) -> &'static str {
 }
 
 /// Returns the layout of fields within the frame, according to the specification.
+/// Unknown frames other than text and URL frames, such as vendor-specific
+/// ones, are laid out as a single binary data field so that their content is
+/// kept as it is.
 #[inline]
 pub fn frame_format(id: Id) -> Option<&'static [FieldType]> {
     match get_frame_info(id) {
@@ -242,7 +247,7 @@ pub fn frame_format(id: Id) -> Option<&'static [FieldType]> {
         None => match id.name()[0] {
             b'T' => Some({static _F: &'static [FieldType] = &[TextEncoding,StringList,]; _F}),
             b'W' => Some({static _F: &'static [FieldType] = &[Latin1,]; _F}),
-            _ => None,
+            _ => Some({static _F: &'static [FieldType] = &[BinaryData,]; _F}),
         }
     }
 }
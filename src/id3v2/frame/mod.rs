@@ -1,10 +1,19 @@
 extern crate std;
 extern crate flate2;
 
-pub use self::encoding::Encoding;
+pub use self::encoding::{Encoding, TextEncodingPolicy};
 pub use self::picture::PictureType;
+pub use self::equalization::{Equalization, Interpolation, AdjustmentPoint};
+pub use self::popularimeter::Popularimeter;
+pub use self::ownership::Ownership;
+pub use self::commercial::Commercial;
+pub use self::synced_lyrics::{SyncedLyrics, TimestampFormat};
+pub use self::event_timing_codes::EventTimingCodes;
+pub use self::chapter::{Chapter, TableOfContents};
 pub use self::flags::FrameFlags;
+pub use self::builder::FrameBuilder;
 pub use self::field::Field;
+use self::field::{FieldType, BigNum};
 use self::flate2::read::ZlibDecoder;
 
 pub use self::frameinfo::{frame_description, frame_format, convert_id_2_to_3,
@@ -12,7 +21,7 @@ convert_id_3_to_2};
 
 use self::stream::{FrameStream, FrameV2, FrameV3, FrameV4};
 use id3v2::Version;
-use id3v2::Error;
+use id3v2::{Error, ErrorKind};
 
 use std::io::{self, Read, Write};
 
@@ -21,10 +30,19 @@ use parsers;
 use parsers::{DecoderRequest, EncoderRequest};
 
 use std::fmt;
+use std::mem;
 
 mod picture;
+mod equalization;
+mod popularimeter;
+mod ownership;
+mod commercial;
+mod synced_lyrics;
+mod event_timing_codes;
+mod chapter;
 mod encoding;
 mod flags;
+mod builder;
 mod stream;
 mod frameinfo;
 /// Atomic units which are composed to make up ID3v2 frames.
@@ -62,6 +80,20 @@ impl Id {
             Id::V4(ref id) => &*id,
         }
     }
+    /// Returns whether the ID consists only of the characters the
+    /// specification allows in frame IDs: uppercase letters and digits.
+    ///
+    /// # Example
+    /// ```
+    /// use id3::id3v2::frame::Id;
+    ///
+    /// assert!(Id::V4(*b"TIT2").is_valid());
+    /// assert!(!Id::V4(*b"TAL ").is_valid());
+    /// assert!(!Id::V3(*b"tit2").is_valid());
+    /// ```
+    pub fn is_valid(&self) -> bool {
+        self.name().iter().all(|&c| (b'A' <= c && c <= b'Z') || (b'0' <= c && c <= b'9'))
+    }
     /// Returns whether this ID corresponds to a standard-layout text frame.
     /// Note that this category excludes the TXX/TXXX frames, which have
     /// different layout and semantics.
@@ -89,7 +121,7 @@ impl fmt::Debug for Id {
 }
 
 /// An ID3v2 frame, containing an ID specifying its purpose/format and a set of fields which constitute its content.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Frame {
     /// The frame identifier, namespaced to the ID3v2.x version to which the frame belongs.
     pub id: Id,
@@ -105,17 +137,52 @@ pub struct Frame {
     /// Byte with similar semantics to the "group symbol", but for frame-level encryption and
     /// with owners specified in an ENCR frame.
     encryption_method: u8,
+    /// The size of the frame's content before compression and
+    /// unsynchronization, as given by the data length indicator it was read
+    /// with (ID3v2.4).
+    data_length: Option<u32>,
+    /// The frame as it was originally read, if it was read with
+    /// `read_from_preserving`.
+    original: Option<Box<Original>>,
 }
 
-impl PartialEq for Frame {
-    #[inline]
-    fn eq(&self, other: &Frame) -> bool {
-        self == other
+/// The state of a frame when it was read, along with its serialized bytes, so
+/// that an unmodified frame can be written back byte-for-byte.
+#[derive(Debug, Clone)]
+struct Original {
+    id: Id,
+    flags: FrameFlags,
+    group_symbol: u8,
+    encryption_method: u8,
+    fields: Vec<Field>,
+    unsynchronization: bool,
+    bytes: Vec<u8>,
+}
+
+/// A reader which keeps a copy of every byte read through it.
+struct RecordingReader<'a> {
+    inner: &'a mut Read,
+    bytes: Vec<u8>,
+}
+
+impl<'a> Read for RecordingReader<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = try!(self.inner.read(buf));
+        self.bytes.extend_from_slice(&buf[..n]);
+        Ok(n)
     }
+}
 
+// The serialized bytes a frame was read from are deliberately not compared, so
+// a frame read from a file equals the same frame built in memory.
+impl PartialEq for Frame {
     #[inline]
-    fn ne(&self, other: &Frame) -> bool {
-        self != other
+    fn eq(&self, other: &Frame) -> bool {
+        self.id == other.id
+            && self.flags == other.flags
+            && self.fields == other.fields
+            && self.group_symbol == other.group_symbol
+            && self.encryption_method == other.encryption_method
     }
 }
 
@@ -129,25 +196,90 @@ impl Frame {
             fields: vec![],
             group_symbol: 0,
             encryption_method: 0,
+            data_length: None,
+            original: None,
         }
     }
 
     /// Returns the size in bytes of this frame when serialized.
     pub fn size(&self, unsynchronize: bool) -> u32 {
-        self.write_to(std::io::sink().by_ref(), unsynchronize).unwrap()
+        self.serialized_len(unsynchronize)
+    }
+
+    /// Returns the size in bytes of this frame when serialized, as returned by
+    /// `write_to`, computed from the lengths of the fields rather than by
+    /// serializing them. Only compressed frames and unsynchronized ID3v2.4
+    /// frames, whose size depends on the transformed content, are serialized
+    /// to be measured.
+    pub fn serialized_len(&self, unsynchronization: bool) -> u32 {
+        if let Some(ref original) = self.original {
+            if original.unsynchronization == unsynchronization && !self.is_modified() {
+                return original.bytes.len() as u32;
+            }
+        }
+        if self.flags.compression || (unsynchronization && self.version() == Version::V4) {
+            return self.write_to(std::io::sink().by_ref(), unsynchronization).unwrap();
+        }
+
+        let encoding = match self.fields.first() {
+            Some(&Field::TextEncoding(encoding)) => Some(encoding),
+            _ => None
+        };
+        let last = self.fields.len().saturating_sub(1);
+        let content_len = self.fields.iter().enumerate()
+            .map(|(i, field)| field.serialized_len(encoding, i == last))
+            .sum::<usize>() as u32;
+
+        // ID3v2.2 and ID3v2.3 report the size before unsynchronization
+        match self.version() {
+            Version::V2 => 6 + content_len,
+            Version::V3 => 10 + content_len + self.flags.grouping_identity as u32,
+            Version::V4 => {
+                10 + content_len + self.flags.grouping_identity as u32
+                    + if self.flags.data_length_indicator { 4 } else { 0 }
+            },
+        }
     }
 
     /// Creates a new ID3v2 text frame with the specified version and identifier,
     /// using the provided string as the text frame's content. The string will
     /// be transcoded to the specified encoding for storage in the frame.
     ///
-    /// Returns `None` if the given id does not specify a text frame, or if the
-    /// specified encoding is not compatible with the version of the ID. Note
+    /// Returns `None` if the given id does not specify a text frame, if it is
+    /// not a valid ID, or if the specified encoding is not compatible with the
+    /// version of the ID. Note
     /// that TXX/TXXX are not "regular" text frames and cannot be created with
     /// this function.
     pub fn new_text_frame(id: Id, s: &str, encoding: Encoding) -> Option<Frame> {
-        if !id.is_text() || !id.version().encoding_compatible(encoding) {
-            return None
+        Frame::try_new_text_frame(id, s, encoding, TextEncodingPolicy::Lossy).ok()
+    }
+
+    /// Creates a new ID3v2 text frame like `new_text_frame`, with the policy
+    /// determining what happens to characters which the encoding cannot
+    /// represent.
+    ///
+    /// Returns an error if the given id does not specify a text frame, if it
+    /// is not a valid ID, if the specified encoding is not compatible with the
+    /// version of the ID, or if the policy is `Strict` and the string cannot be
+    /// represented in the encoding.
+    ///
+    /// # Example
+    /// ```
+    /// use id3::id3v2::frame::{Frame, Id, Encoding, TextEncodingPolicy};
+    ///
+    /// let id = Id::V3(*b"TIT2");
+    /// assert!(Frame::try_new_text_frame(id, "café", Encoding::Latin1, TextEncodingPolicy::Strict).is_ok());
+    /// assert!(Frame::try_new_text_frame(id, "東京", Encoding::Latin1, TextEncodingPolicy::Strict).is_err());
+    /// ```
+    pub fn try_new_text_frame(id: Id, s: &str, encoding: Encoding, policy: TextEncodingPolicy) -> Result<Frame, Error> {
+        if !id.is_text() || !id.is_valid() {
+            return Err(Error::new(ErrorKind::InvalidTag, "ID is not a valid text frame ID"))
+        }
+        if !id.version().encoding_compatible(encoding) {
+            return Err(Error::new(ErrorKind::UnsupportedFeature, "encoding is not supported by the ID's version"))
+        }
+        if policy == TextEncodingPolicy::Strict && !util::is_encodable(s, encoding) {
+            return Err(Error::new(ErrorKind::InvalidTag, "text cannot be represented in the encoding"))
         }
         let mut frame = Frame::new(id);
         let encoded: Vec<u8> = util::encode_string(s, encoding);
@@ -158,17 +290,18 @@ impl Frame {
             //TODO(sp3d): StringList for V4?
             Version::V4 => vec![Field::TextEncoding(encoding), Field::String(encoded)],
         };
-        Some(frame)
+        Ok(frame)
     }
 
     /// Creates a new ID3v2 URL frame with the specified version and identifier,
     /// using the provided URL (encoded in Latin-1) as the frame's URL.
     ///
-    /// Returns `None` if the given id does not specify a URL frame. Note that
+    /// Returns `None` if the given id does not specify a URL frame or is not a
+    /// valid ID. Note that
     /// WXX/WXXX are not "regular" URL frames and cannot be created with this
     /// function.
     pub fn new_url_frame(id: Id, url: &[u8]) -> Option<Frame> {
-        if !id.is_url() {
+        if !id.is_url() || !id.is_valid() {
             return None
         }
         let mut frame = Frame::new(id);
@@ -194,42 +327,57 @@ impl Frame {
     /// encoding to the new one. Returns `true` if successful.
     ///
     /// Returns `false` and does not modify the frame if the specified encoding
-    /// is not compatible with the frame's version.
-    ///
-    /// Returns `true` and does nothing if the frame does not begin with a
-    /// `TextEncoding` field.
+    /// is not compatible with the frame's version, if the frame does not begin
+    /// with a `TextEncoding` field, or if some string is not valid in the old
+    /// encoding.
     pub fn set_encoding(&mut self, encoding: Encoding) -> bool {
         if !self.version().encoding_compatible(encoding) {
             return false;
         }
 
-        let old_encoding;
-        if let Some(&mut Field::TextEncoding(ref mut enc)) = self.fields.get_mut(0) {
-            old_encoding = *enc;
-            *enc = encoding;
-        } else {
-            return false;
-        }
+        let old_encoding = match self.fields.get(0) {
+            Some(&Field::TextEncoding(enc)) => enc,
+            _ => return false,
+        };
 
         if old_encoding == encoding {
             return true;
         }
 
-        //TODO(sp3d): transcode strings!
-        for f in self.fields.iter_mut() {
-            match f {
-                &mut Field::String(ref mut _s) => {
-                    
+        let transcode = |s: &Vec<u8>| if s.is_empty() {
+            Some(vec![])
+        } else {
+            util::string_from_encoding(old_encoding, &*s).map(|s| util::encode_string(&*s, encoding))
+        };
+
+        let mut transcoded = Vec::with_capacity(self.fields.len());
+        for field in self.fields.iter() {
+            transcoded.push(match field {
+                &Field::TextEncoding(_) => Field::TextEncoding(encoding),
+                &Field::String(ref s) => match transcode(s) {
+                    Some(s) => Field::String(s),
+                    None => return false,
                 },
-                &mut Field::StringFull(ref mut _s) => {
-                    
+                &Field::StringFull(ref s) => match transcode(s) {
+                    Some(s) => Field::StringFull(s),
+                    None => return false,
                 },
-                &mut Field::StringList(ref mut _s) => {
-                    
+                &Field::StringList(ref strs) => {
+                    let mut list = Vec::with_capacity(strs.len());
+                    for s in strs.iter() {
+                        match transcode(s) {
+                            Some(s) => list.push(s),
+                            None => return false,
+                        }
+                    }
+                    Field::StringList(list)
                 },
-                _ => (),
-            }
+                // Latin-1 fields (including Latin1List) do not depend on the
+                // frame's text encoding
+                other => other.clone(),
+            });
         }
+        self.fields = transcoded;
         true
     }
 
@@ -239,6 +387,14 @@ impl Frame {
         self.flags.compression
     }
 
+    #[inline]
+    /// Returns the size of the frame's content before compression and
+    /// unsynchronization, as declared by the data length indicator the frame
+    /// was read with, if any (ID3v2.4).
+    pub fn data_length_indicator(&self) -> Option<u32> {
+        self.data_length
+    }
+
     #[inline]
     /// Sets whether zlib compression will be used when storing the frame.
     pub fn set_compression(&mut self, compression: bool) {
@@ -248,6 +404,21 @@ impl Frame {
         }
     }
 
+    #[inline]
+    /// Returns the group symbol of the frame, if it belongs to a group of
+    /// frames which should be processed as a unit (ID3v2.3 and ID3v2.4).
+    pub fn group(&self) -> Option<u8> {
+        if self.flags.grouping_identity { Some(self.group_symbol) } else { None }
+    }
+
+    #[inline]
+    /// Sets the group symbol of the frame, or removes the frame from its group
+    /// if `None` is given. The group symbol is stored after the frame header.
+    pub fn set_group(&mut self, group: Option<u8>) {
+        self.flags.grouping_identity = group.is_some();
+        self.group_symbol = group.unwrap_or(0);
+    }
+
     #[inline]
     /// Returns the frame's "tag alter preservation" flag.
     ///
@@ -333,19 +504,21 @@ impl Frame {
     ///
     /// Returns `true` if the conversion was successful. Returns `false` if the
     /// frame identifier could not be converted.
-    ///
-    /// Warning: not fully implemented yet! Calling this *will* result in
-    /// mangled tags!
-    //#[deprecated = "not fully implemented yet!"]
     pub fn convert_version(&mut self, to: Version) -> bool {
         use id3v2::Version::*;
         let from = self.id;
 
         // convert frame ID
-        // no-op if versions are equal or "compatible" like V3/V4 are
+        // no-op if versions are equal; V3 and V4 share their IDs
         match (from, to) {
             (x, y) if x.version() == y => { return true },
-            (Id::V3(_), V4) | (Id::V4(_), V3) => { return true },
+            (Id::V3(id), V4) => {
+                self.id = Id::V4(id);
+                if self.flags.compression {
+                    self.flags.data_length_indicator = true;
+                }
+            },
+            (Id::V4(id), V3) => { self.id = Id::V3(id) },
             (Id::V3(id), V2) | (Id::V4(id), V2) => {
                 // attempt to convert the id
                 self.id = match frameinfo::convert_id_3_to_2(id) {
@@ -374,10 +547,10 @@ impl Frame {
             _ => unreachable!(),
         }
 
-        //TODO(sp3d): convert frame format itself, adding/dropping fields!
+        self.convert_fields(from);
 
         // convert text fields to an encoding compatible with the new version
-        match (self.id.version(), to) {
+        match (from.version(), to) {
             // ID3v2.3 and ID3v2.2 do not support UTF-16BE or UTF-8 encodings
             (V4, V3) | (V4, V2) => {
                 match self.encoding() {
@@ -394,6 +567,88 @@ impl Frame {
         true
     }
 
+    /// Returns whether the frame's fields, once serialized, can be parsed back
+    /// according to the field layout of the frame's ID, so that the frame can
+    /// be written and read again.
+    pub fn fields_match_format(&self) -> bool {
+        parsers::decode(DecoderRequest { id: self.id, data: &*self.fields_to_bytes() }).is_ok()
+    }
+
+    /// Returns a best-effort textual rendering of the frame's content: the
+    /// text of text frames, with several values joined by slashes; the URL of
+    /// URL frames; and `"description: value"` for user defined text and URL
+    /// frames, comments, and lyrics, or just the value if the description is
+    /// empty. Returns `None` for other frames or if the text cannot be decoded.
+    ///
+    /// # Example
+    /// ```
+    /// use id3::id3v2::frame::{Frame, Field, Id, Encoding};
+    ///
+    /// let mut frame = Frame::new(Id::V4(*b"TXXX"));
+    /// frame.fields = vec![Field::TextEncoding(Encoding::UTF8), Field::String(b"mood".to_vec()),
+    ///     Field::String(b"calm".to_vec())];
+    /// assert_eq!(frame.display_text().unwrap(), "mood: calm");
+    /// ```
+    pub fn display_text(&self) -> Option<String> {
+        let keyed = match self.id.name() {
+            b"TXX" | b"TXXX" | b"WXX" | b"WXXX" | b"COM" | b"COMM" | b"ULT" | b"USLT" => true,
+            _ => false,
+        };
+        if !keyed && !self.id.is_text() && !self.id.is_url() {
+            return None;
+        }
+
+        let encoding = self.encoding();
+        let decode = |text: &[u8]| encoding.and_then(|encoding| util::string_from_encoding(encoding, text)).map(util::clean_decoded);
+        let mut texts = vec![];
+        for field in self.fields.iter() {
+            let text = match *field {
+                Field::Latin1(ref text) | Field::Latin1Full(ref text) => util::string_from_latin1(&*text),
+                Field::String(ref text) | Field::StringFull(ref text) => decode(&*text),
+                Field::StringList(ref values) => values.iter()
+                    .map(|value| decode(&*value))
+                    .collect::<Option<Vec<_>>>()
+                    .map(|values| values.join("/")),
+                _ => continue,
+            };
+            match text {
+                Some(text) => texts.push(text),
+                None => return None,
+            }
+        }
+
+        match texts.len() {
+            1 => texts.pop(),
+            2 if keyed => {
+                let value = texts.pop().unwrap();
+                let key = texts.pop().unwrap();
+                Some(if key.is_empty() { value } else { format!("{}: {}", key, value) })
+            },
+            _ => None,
+        }
+    }
+
+    /// Rearranges the fields of a frame whose ID has been converted from
+    /// `from` to match the layout of its new ID. Fields are matched up by
+    /// position; those missing from the old layout are filled with empty
+    /// values and those missing from the new layout are dropped.
+    fn convert_fields(&mut self, from: Id) {
+        let (old_format, new_format) = match (frame_format(from), frame_format(self.id)) {
+            (Some(old_format), Some(new_format)) => (old_format, new_format),
+            _ => return
+        };
+        if old_format == new_format {
+            return;
+        }
+
+        let encoding = self.encoding();
+        let mut old_fields = mem::replace(&mut self.fields, vec![]).into_iter();
+        self.fields = new_format.iter().map(|&ftype| match old_fields.next() {
+            Some(field) => convert_field(field, ftype, encoding),
+            None => empty_field(ftype, encoding),
+        }).collect();
+    }
+
     /// Attempts to read a frame from the reader.
     ///
     /// Returns either an error or a tuple containing the number of bytes read
@@ -411,9 +666,93 @@ impl Frame {
         }
     }
 
-    /// Attempts to write the frame to the writer.
+    /// Attempts to read a frame from the reader, additionally reporting the
+    /// size of the frame's content as declared in its header.
+    ///
+    /// Returns either an error or, if a frame was read, a tuple containing the
+    /// number of bytes read, the declared content size, and the frame. Returns
+    /// `Ok(None)` if padding is encountered. A declared size which disagrees
+    /// with the size of the frame's reserialized fields may indicate trailing
+    /// garbage in the frame.
+    pub fn read_from_with_size(reader: &mut Read, version: Version, unsynchronization: bool) -> Result<Option<(u32, u32, Frame)>, Error> {
+        let mut recorder = RecordingReader { inner: reader, bytes: vec![] };
+        let (bytes_read, frame) = try!(Frame::read_from(&mut recorder, version, unsynchronization));
+        let frame = match frame {
+            Some(frame) => frame,
+            None => return Ok(None),
+        };
+
+        // the size field follows the frame ID in the header
+        let header = &recorder.bytes;
+        let declared_size = match version {
+            Version::V2 => (header[3] as u32) << 16 | (header[4] as u32) << 8 | header[5] as u32,
+            Version::V3 => (header[4] as u32) << 24 | (header[5] as u32) << 16 | (header[6] as u32) << 8 | header[7] as u32,
+            Version::V4 => util::unsynchsafe((header[4] as u32) << 24 | (header[5] as u32) << 16
+                | (header[6] as u32) << 8 | header[7] as u32),
+        };
+        Ok(Some((bytes_read, declared_size, frame)))
+    }
+
+    /// Attempts to read a frame from the reader like `read_from`, additionally
+    /// remembering the frame's serialized bytes. As long as the frame is not
+    /// modified, `write_to` will write back exactly those bytes rather than
+    /// reserializing the frame's fields.
+    pub fn read_from_preserving(reader: &mut Read, version: Version, unsynchronization: bool) -> Result<(u32, Option<Frame>), Error> {
+        let mut recorder = RecordingReader { inner: reader, bytes: vec![] };
+        let (bytes_read, frame) = try!(Frame::read_from(&mut recorder, version, unsynchronization));
+        Ok((bytes_read, frame.map(|mut frame| {
+            frame.original = Some(Box::new(Original {
+                id: frame.id,
+                flags: frame.flags,
+                group_symbol: frame.group_symbol,
+                encryption_method: frame.encryption_method,
+                fields: frame.fields.clone(),
+                unsynchronization: unsynchronization,
+                bytes: recorder.bytes,
+            }));
+            frame
+        })))
+    }
+
+    /// Returns whether the frame differs from its state when it was read.
+    /// Frames which were not read with `read_from_preserving` are always
+    /// considered modified.
+    ///
+    /// # Example
+    /// ```
+    /// use id3::id3v2::frame::{Frame, Id};
+    ///
+    /// let frame = Frame::new(Id::V4(*b"TALB"));
+    /// assert!(frame.is_modified());
+    /// ```
+    pub fn is_modified(&self) -> bool {
+        match self.original {
+            Some(ref original) => original.id != self.id
+                || original.flags != self.flags
+                || original.group_symbol != self.group_symbol
+                || original.encryption_method != self.encryption_method
+                || original.fields != self.fields,
+            None => true,
+        }
+    }
+
+    /// Discards the frame's original serialized bytes, if any, so that the
+    /// frame will be reserialized from its fields when written.
+    pub fn forget_original(&mut self) {
+        self.original = None;
+    }
+
+    /// Attempts to write the frame to the writer. If the frame was read with
+    /// `read_from_preserving` and has not since been modified, the bytes it was
+    /// read from are written instead of reserializing its fields.
     #[inline]
     pub fn write_to(&self, writer: &mut Write, unsynchronization: bool) -> Result<u32, io::Error> {
+        if let Some(ref original) = self.original {
+            if original.unsynchronization == unsynchronization && !self.is_modified() {
+                try!(writer.write_all(&*original.bytes));
+                return Ok(original.bytes.len() as u32);
+            }
+        }
         match self.version() {
             Version::V2 => FrameStream::write(writer, self, None::<FrameV2>, unsynchronization),
             Version::V3 => FrameStream::write(writer, self, None::<FrameV3>, unsynchronization),
@@ -436,7 +775,7 @@ impl Frame {
     pub fn parse_fields(&self, data: &[u8]) -> Result<Vec<Field>, Error> {
         let decompressed_opt = if self.flags.compression {
             let mut decoder = ZlibDecoder::new(data);
-            let mut decompressed = Vec::new();
+            let mut decompressed = Vec::with_capacity(self.data_length.unwrap_or(0) as usize);
             try!(decoder.read_to_end(&mut decompressed));
             Some(decompressed)
         } else {
@@ -467,12 +806,115 @@ impl Frame {
     pub fn description(&self) -> &'static str {
         frameinfo::frame_description(self.id)
     }
+
+    /// Returns the content of the frame's description field (as found in,
+    /// e.g., APIC, GEOB, COMM, USLT, SYLT, TXXX, and WXXX frames), decoded
+    /// using the frame's text encoding.
+    ///
+    /// Returns `None` if the frame type has no description field or if the
+    /// description could not be decoded.
+    ///
+    /// # Example
+    /// ```
+    /// use id3::id3v2::frame::{Frame, Field, Id, Encoding};
+    ///
+    /// let mut frame = Frame::new(Id::V4(*b"GEOB"));
+    /// frame.fields = vec![Field::TextEncoding(Encoding::UTF8), Field::Latin1(b"text/plain".to_vec()),
+    ///     Field::String(b"notes.txt".to_vec()), Field::String(b"liner notes".to_vec()),
+    ///     Field::BinaryData(vec![])];
+    /// assert_eq!(frame.description_text().unwrap(), "liner notes");
+    /// ```
+    pub fn description_text(&self) -> Option<String> {
+        let index = match self.id.name() {
+            b"TXX" | b"TXXX" | b"WXX" | b"WXXX" => 1,
+            b"COM" | b"COMM" | b"ULT" | b"USLT" => 2,
+            b"PIC" | b"APIC" | b"GEO" | b"GEOB" => 3,
+            b"SLT" | b"SYLT" => 4,
+            _ => return None,
+        };
+        match (self.encoding(), self.fields.get(index)) {
+            (Some(encoding), Some(&Field::String(ref text))) => util::string_from_encoding(encoding, &*text),
+            _ => None,
+        }
+    }
+}
+
+/// Converts a field to the given type, preserving its content where the two
+/// types can represent it, as with ID3v2.2 image formats and MIME types, or
+/// single strings and string lists.
+fn convert_field(field: Field, ftype: FieldType, encoding: Option<Encoding>) -> Field {
+    match (field, ftype) {
+        (Field::Int24(a, b, c), FieldType::Latin1) => Field::Latin1(util::format_mime_type(&[a, b, c]).into_bytes()),
+        (Field::Latin1(mime_type), FieldType::Int24) => {
+            let format = util::mime_type_format(&*String::from_utf8_lossy(&*mime_type));
+            Field::Int24(format[0], format[1], format[2])
+        },
+        (Field::FrameIdV2(id), FieldType::FrameIdV34) => match frameinfo::convert_id_2_to_3(id) {
+            Some(id) => Field::FrameIdV34(id),
+            None => empty_field(ftype, encoding),
+        },
+        (Field::FrameIdV34(id), FieldType::FrameIdV2) => match frameinfo::convert_id_3_to_2(id) {
+            Some(id) => Field::FrameIdV2(id),
+            None => empty_field(ftype, encoding),
+        },
+        (Field::StringList(values), FieldType::String) | (Field::StringList(values), FieldType::StringFull) => {
+            // versions without string lists separate multiple values with slashes
+            let joined = match encoding {
+                Some(encoding) => values.iter()
+                    .map(|value| util::string_from_encoding(encoding, &*value).map(util::clean_decoded))
+                    .collect::<Option<Vec<_>>>()
+                    .map(|values| util::encode_string(&*values.join("/"), encoding)),
+                None => None,
+            };
+            let text = joined.unwrap_or_else(|| values.into_iter().next().unwrap_or(vec![]));
+            convert_field(Field::String(text), ftype, encoding)
+        },
+        (Field::Latin1List(values), FieldType::Latin1) | (Field::Latin1List(values), FieldType::Latin1Full) =>
+            convert_field(Field::Latin1(values.join(&b'/')), ftype, encoding),
+        (Field::Latin1(text), _) | (Field::Latin1Full(text), _)
+            | (Field::String(text), _) | (Field::StringFull(text), _) => match ftype {
+            FieldType::Latin1 => Field::Latin1(text),
+            FieldType::Latin1Full => Field::Latin1Full(text),
+            FieldType::Latin1List => Field::Latin1List(vec![text]),
+            FieldType::String => Field::String(text),
+            FieldType::StringFull => Field::StringFull(text),
+            FieldType::StringList => Field::StringList(vec![text]),
+            _ => empty_field(ftype, encoding),
+        },
+        (Field::Latin1List(values), FieldType::StringList) => Field::StringList(values),
+        (Field::StringList(values), FieldType::Latin1List) => Field::Latin1List(values),
+        (field, ftype) => if field.field_type() == ftype {
+            field
+        } else {
+            empty_field(ftype, encoding)
+        },
+    }
+}
+
+/// Returns an empty or zero field of the given type.
+fn empty_field(ftype: FieldType, encoding: Option<Encoding>) -> Field {
+    match ftype {
+        FieldType::TextEncoding => Field::TextEncoding(encoding.unwrap_or(Encoding::Latin1)),
+        FieldType::Latin1 => Field::Latin1(vec![]),
+        FieldType::Latin1Full => Field::Latin1Full(vec![]),
+        FieldType::Latin1List => Field::Latin1List(vec![]),
+        FieldType::String => Field::String(vec![]),
+        FieldType::StringFull => Field::StringFull(vec![]),
+        FieldType::StringList => Field::StringList(vec![]),
+        FieldType::Language => Field::Language(*b"XXX"),
+        FieldType::FrameIdV2 => Field::FrameIdV2([0; 3]),
+        FieldType::FrameIdV34 => Field::FrameIdV34([0; 4]),
+        FieldType::Int32Plus => Field::Int32Plus(BigNum::new(vec![])),
+        FieldType::BinaryData => Field::BinaryData(vec![]),
+        FieldType::Int8 | FieldType::Int16 | FieldType::Int24 | FieldType::Int32 => Field::from_u32(ftype, 0).unwrap(),
+    }
 }
 
 // Tests {{{
 #[cfg(test)]
 mod tests {
-    use id3v2::frame::{Id, Frame, FrameFlags, Encoding};
+    use id3v2::Version;
+    use id3v2::frame::{Id, Frame, FrameFlags, Field, Encoding};
     use util;
 
     #[test]
@@ -581,4 +1023,323 @@ mod tests {
         frame.write_to(&mut writer, false).unwrap();
         assert_eq!(writer, bytes);
     }
+
+    #[test]
+    fn test_data_length_indicator() {
+        let mut frame = Frame::new_text_frame(Id::V4(*b"TALB"), "album album album album", Encoding::UTF8).unwrap();
+        frame.set_compression(true);
+        assert!(frame.flags.data_length_indicator);
+        let data_length = frame.fields_to_bytes().len() as u32;
+
+        let mut bytes = Vec::new();
+        frame.write_to(&mut bytes, false).unwrap();
+        assert_eq!(bytes[9] & 0x09, 0x09);
+        assert_eq!(&bytes[10..14], &util::u32_to_bytes(util::synchsafe(data_length)));
+
+        let (_, reread) = Frame::read_from(&mut &*bytes, Version::V4, false).unwrap();
+        let reread = reread.unwrap();
+        assert_eq!(reread.data_length_indicator(), Some(data_length));
+        assert!(reread.compression());
+        assert_eq!(reread.fields_to_bytes(), frame.fields_to_bytes());
+    }
+
+    #[test]
+    fn test_compression_round_trip() {
+        for &(id, version) in &[(Id::V3(*b"TALB"), Version::V3), (Id::V4(*b"TALB"), Version::V4)] {
+            let text = "album album album album album album";
+            let mut frame = Frame::new_text_frame(id, text, Encoding::UTF16).unwrap();
+            // set directly, so that ID3v2.4 frames lack the data length indicator flag
+            frame.flags.compression = true;
+
+            let mut bytes = Vec::new();
+            let written = frame.write_to(&mut bytes, false).unwrap();
+            assert_eq!(written as usize, bytes.len());
+            assert!(bytes.len() < 10 + frame.fields_to_bytes().len());
+
+            let (read, reread) = Frame::read_from(&mut &*bytes, version, false).unwrap();
+            let reread = reread.unwrap();
+            assert_eq!(read, written);
+            assert!(reread.compression());
+            assert_eq!(reread.fields_to_bytes(), frame.fields_to_bytes());
+        }
+    }
+
+    #[test]
+    fn test_string_list_round_trip() {
+        use id3v2::frame::Field;
+
+        for &encoding in &[Encoding::Latin1, Encoding::UTF8, Encoding::UTF16, Encoding::UTF16BE] {
+            let mut frame = Frame::new(Id::V4(*b"TALB"));
+            frame.fields = vec![Field::TextEncoding(encoding), Field::StringList(vec![
+                util::encode_string("first", encoding), util::encode_string("second", encoding)])];
+
+            let bytes = frame.fields_to_bytes();
+            assert_eq!(frame.parse_fields(&*bytes).unwrap(), frame.fields);
+        }
+    }
+
+    #[test]
+    fn test_set_encoding() {
+        use id3v2::frame::Field;
+
+        let text = "śốмễ śŧŗỉňĝ";
+        let mut frame = Frame::new_text_frame(Id::V4(*b"TALB"), text, Encoding::UTF16).unwrap();
+        frame.fields.push(Field::StringList(vec![util::string_to_utf16("one"), vec![]]));
+
+        assert!(frame.set_encoding(Encoding::UTF8));
+        assert_eq!(frame.encoding(), Some(Encoding::UTF8));
+        assert_eq!(frame.fields[1], Field::String(text.as_bytes().to_vec()));
+        assert_eq!(frame.fields[2], Field::StringList(vec![b"one".to_vec(), vec![]]));
+
+        assert!(frame.set_encoding(Encoding::UTF16));
+        match frame.fields[1] {
+            Field::String(ref s) => assert_eq!(util::string_from_utf16(&*s).unwrap(), text),
+            _ => panic!("expected a string field"),
+        }
+
+        let mut v3 = Frame::new_text_frame(Id::V3(*b"TALB"), text, Encoding::UTF16).unwrap();
+        assert!(!v3.set_encoding(Encoding::UTF8));
+        assert_eq!(v3.encoding(), Some(Encoding::UTF16));
+    }
+
+    #[test]
+    fn test_frame_eq() {
+        use id3v2::frame::Field;
+
+        let frame = |text: &[u8]| {
+            let mut frame = Frame::new(Id::V4(*b"TALB"));
+            frame.fields = vec![Field::TextEncoding(Encoding::Latin1), Field::String(text.to_vec())];
+            frame
+        };
+        assert_eq!(frame(b"album"), frame(b"album"));
+        assert!(frame(b"album") != frame(b"other"));
+
+        let mut compressed = frame(b"album");
+        compressed.set_compression(true);
+        assert!(compressed != frame(b"album"));
+        assert!(Frame::new(Id::V4(*b"TALB")) != Frame::new(Id::V4(*b"TIT2")));
+    }
+
+    #[test]
+    fn test_read_from_preserving() {
+        use id3v2::Version;
+        use id3v2::frame::Field;
+
+        let mut bytes = Vec::new();
+        bytes.extend(b"TALB");
+        bytes.extend(&util::u32_to_bytes(util::synchsafe(7)));
+        bytes.extend(&[0x00, 0x00]);
+        bytes.extend(b"\x00album\x00");
+
+        let (bytes_read, frame) = Frame::read_from_preserving(&mut &*bytes, Version::V4, false).unwrap();
+        let mut frame = frame.unwrap();
+        assert_eq!(bytes_read as usize, bytes.len());
+        assert!(!frame.is_modified());
+
+        let mut writer = Vec::new();
+        frame.write_to(&mut writer, false).unwrap();
+        assert_eq!(writer, bytes);
+
+        frame.fields = vec![Field::TextEncoding(Encoding::Latin1), Field::String(b"other".to_vec())];
+        assert!(frame.is_modified());
+        let mut writer = Vec::new();
+        frame.write_to(&mut writer, false).unwrap();
+        assert!(writer != bytes);
+    }
+
+    #[test]
+    fn test_read_from_with_size() {
+        use id3v2::Version;
+
+        // a recommended buffer size frame followed by four bytes of garbage
+        let mut bytes = Vec::new();
+        bytes.extend(b"RBUF");
+        bytes.extend(&util::u32_to_bytes(12));
+        bytes.extend(&[0x00, 0x00]);
+        bytes.extend(&[0x00, 0x10, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00]);
+        bytes.extend(b"junk");
+
+        let (bytes_read, declared_size, frame) = Frame::read_from_with_size(&mut &*bytes, Version::V3, false).unwrap().unwrap();
+        assert_eq!(bytes_read as usize, bytes.len());
+        assert_eq!(declared_size, 12);
+        assert_eq!(frame.fields_to_bytes().len(), 8);
+
+        assert!(Frame::read_from_with_size(&mut &[0u8; 4][..], Version::V3, false).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_display_text() {
+        let mut txxx = Frame::new(Id::V3(*b"TXXX"));
+        txxx.fields = vec![Field::TextEncoding(Encoding::UTF16), Field::String(util::string_to_utf16("mood")),
+            Field::String(util::string_to_utf16("calm"))];
+        assert_eq!(txxx.display_text().unwrap(), "mood: calm");
+
+        let title = Frame::new_text_frame(Id::V4(*b"TIT2"), "title", Encoding::UTF8).unwrap();
+        assert_eq!(title.display_text().unwrap(), "title");
+        let mut artists = Frame::new(Id::V4(*b"TPE1"));
+        artists.fields = vec![Field::TextEncoding(Encoding::Latin1), Field::StringList(vec![b"a".to_vec(), b"b".to_vec()])];
+        assert_eq!(artists.display_text().unwrap(), "a/b");
+
+        let mut comment = Frame::new(Id::V4(*b"COMM"));
+        comment.fields = vec![Field::TextEncoding(Encoding::Latin1), Field::Language(*b"eng"),
+            Field::String(vec![]), Field::StringFull(b"text".to_vec())];
+        assert_eq!(comment.display_text().unwrap(), "text");
+
+        let url = Frame::new_url_frame(Id::V4(*b"WOAR"), b"http://example.com/").unwrap();
+        assert_eq!(url.display_text().unwrap(), "http://example.com/");
+
+        let mut count = Frame::new(Id::V4(*b"PCNT"));
+        count.fields = vec![Field::Int32(0, 0, 0, 1)];
+        assert!(count.display_text().is_none());
+    }
+
+    #[test]
+    fn test_try_new_text_frame() {
+        use id3v2::frame::TextEncodingPolicy::*;
+
+        let id = Id::V3(*b"TIT2");
+        assert!(Frame::try_new_text_frame(id, "東京 tower", Encoding::Latin1, Strict).is_err());
+        let frame = Frame::try_new_text_frame(id, "東京 tower", Encoding::Latin1, Lossy).unwrap();
+        assert_eq!(frame.fields[1], Field::String(b"?? tower".to_vec()));
+        assert_eq!(Frame::new_text_frame(id, "東京 tower", Encoding::Latin1).unwrap().fields, frame.fields);
+
+        let frame = Frame::try_new_text_frame(id, "東京 tower", Encoding::UTF16, Strict).unwrap();
+        assert_eq!(util::string_from_encoding(Encoding::UTF16, match frame.fields[1] {
+            Field::String(ref s) => &**s,
+            _ => panic!("expected a string field"),
+        }).unwrap(), "東京 tower");
+    }
+
+    #[test]
+    fn test_serialized_len() {
+        use std::io;
+        use id3v2::frame::field::BigNum;
+
+        let mut frames = vec![];
+        for &version in &[Version::V2, Version::V3, Version::V4] {
+            let encoding = if version == Version::V4 { Encoding::UTF8 } else { Encoding::UTF16 };
+            let mut text = Frame::new(version.title_id());
+            text.fields = vec![Field::TextEncoding(encoding), Field::StringList(vec![b"a".to_vec(), b"b\xFF\xE0".to_vec()])];
+            frames.push(text);
+
+            let mut comment = Frame::new(version.comment_id());
+            comment.fields = vec![Field::TextEncoding(encoding), Field::Language(*b"eng"),
+                Field::String(b"description".to_vec()), Field::StringFull(b"text".to_vec())];
+            frames.push(comment);
+
+            let mut count = Frame::new(version.play_count_id());
+            count.fields = vec![Field::Int32Plus(BigNum::new(vec![1, 2, 3, 4, 5]))];
+            frames.push(count);
+
+            let mut picture = Frame::new(version.picture_id());
+            picture.fields = vec![Field::TextEncoding(Encoding::Latin1),
+                if version == Version::V2 { Field::Int24(b'P', b'N', b'G') } else { Field::Latin1(b"image/png".to_vec()) },
+                Field::Int8(3), Field::String(vec![]), Field::BinaryData(vec![0xFF, 0xE0, 0xFF])];
+            frames.push(picture.clone());
+
+            if version != Version::V2 {
+                picture.set_group(Some(0x80));
+                frames.push(picture.clone());
+                picture.set_compression(true);
+                frames.push(picture);
+            }
+        }
+
+        for frame in frames.iter() {
+            for &unsync in &[false, true] {
+                let written = frame.write_to(&mut io::sink(), unsync).unwrap();
+                assert_eq!(frame.serialized_len(unsync), written);
+            }
+        }
+    }
+
+    #[test]
+    fn test_group_symbol() {
+        for &(id, version) in &[(Id::V3(*b"TALB"), Version::V3), (Id::V4(*b"TALB"), Version::V4)] {
+            let mut frame = Frame::new_text_frame(id, "album", Encoding::Latin1).unwrap();
+            assert_eq!(frame.group(), None);
+            frame.set_group(Some(0xA5));
+            assert_eq!(frame.group(), Some(0xA5));
+
+            let mut bytes = Vec::new();
+            let written = frame.write_to(&mut bytes, false).unwrap();
+            assert_eq!(written as usize, bytes.len());
+            assert_eq!(bytes[10], 0xA5);
+            assert_eq!(&bytes[11..], &*frame.fields_to_bytes());
+
+            let (read, reread) = Frame::read_from(&mut &*bytes, version, false).unwrap();
+            let mut reread = reread.unwrap();
+            assert_eq!(read, written);
+            assert_eq!(reread.group(), Some(0xA5));
+            assert_eq!(reread.fields_to_bytes(), frame.fields_to_bytes());
+
+            reread.set_group(None);
+            let mut bytes = Vec::new();
+            reread.write_to(&mut bytes, false).unwrap();
+            assert_eq!(&bytes[10..], &*frame.fields_to_bytes());
+        }
+    }
+
+    #[test]
+    fn test_set_group_of_preserved_frame() {
+        for &(id, version) in &[(Id::V3(*b"TALB"), Version::V3), (Id::V4(*b"TALB"), Version::V4)] {
+            let mut frame = Frame::new_text_frame(id, "album", Encoding::Latin1).unwrap();
+            frame.set_group(Some(0xA5));
+            let mut bytes = Vec::new();
+            frame.write_to(&mut bytes, false).unwrap();
+
+            let (_, reread) = Frame::read_from_preserving(&mut &*bytes, version, false).unwrap();
+            let mut reread = reread.unwrap();
+            assert!(!reread.is_modified());
+            reread.set_group(Some(0x5A));
+            assert!(reread.is_modified());
+
+            let mut rewritten = Vec::new();
+            reread.write_to(&mut rewritten, false).unwrap();
+            assert_eq!(rewritten[10], 0x5A);
+            assert_eq!(&rewritten[11..], &bytes[11..]);
+        }
+    }
+
+    #[test]
+    fn test_convert_picture() {
+        use id3v2::frame::Field;
+
+        let data = vec![0xF9, 0x90, 0x3A, 0x02, 0xBD];
+        let mut frame = Frame::new(Id::V2(*b"PIC"));
+        frame.fields = vec![Field::TextEncoding(Encoding::UTF16), Field::Int24(b'J', b'P', b'G'),
+            Field::Int8(3), Field::String(util::string_to_utf16("cover")), Field::BinaryData(data.clone())];
+
+        assert!(frame.convert_version(Version::V3));
+        assert_eq!(frame.id, Id::V3(*b"APIC"));
+        assert_eq!(frame.fields, vec![Field::TextEncoding(Encoding::UTF16), Field::Latin1(b"image/jpeg".to_vec()),
+            Field::Int8(3), Field::String(util::string_to_utf16("cover")), Field::BinaryData(data.clone())]);
+
+        let mut frame = Frame::new(Id::V4(*b"APIC"));
+        frame.fields = vec![Field::TextEncoding(Encoding::UTF8), Field::Latin1(b"image/png".to_vec()),
+            Field::Int8(3), Field::String(b"cover".to_vec()), Field::BinaryData(data.clone())];
+
+        assert!(frame.convert_version(Version::V2));
+        assert_eq!(frame.id, Id::V2(*b"PIC"));
+        assert_eq!(frame.fields, vec![Field::TextEncoding(Encoding::UTF16), Field::Int24(b'P', b'N', b'G'),
+            Field::Int8(3), Field::String(util::string_to_utf16("cover")), Field::BinaryData(data)]);
+        let bytes = frame.fields_to_bytes();
+        assert_eq!(frame.parse_fields(&*bytes).unwrap(), frame.fields);
+    }
+
+    #[test]
+    fn test_convert_text() {
+        use id3v2::frame::Field;
+
+        let mut frame = Frame::new(Id::V2(*b"TP1"));
+        frame.fields = vec![Field::TextEncoding(Encoding::Latin1), Field::String(b"artist".to_vec())];
+        assert!(frame.convert_version(Version::V4));
+        assert_eq!(frame.fields, vec![Field::TextEncoding(Encoding::Latin1), Field::StringList(vec![b"artist".to_vec()])]);
+
+        let mut frame = Frame::new(Id::V3(*b"TPE1"));
+        frame.fields = vec![Field::TextEncoding(Encoding::Latin1),
+            Field::StringList(vec![b"first".to_vec(), b"second".to_vec()])];
+        assert!(frame.convert_version(Version::V2));
+        assert_eq!(frame.fields, vec![Field::TextEncoding(Encoding::Latin1), Field::String(b"first/second".to_vec())]);
+    }
 }
@@ -0,0 +1,92 @@
+use id3v2::frame::Encoding;
+use id3v2::frame::field::Field;
+use util;
+
+/// The decoded contents of an OWNE (ownership) frame, which records the
+/// purchase of the file.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Ownership {
+    /// The price paid, as a currency code followed by the amount, such as
+    /// "USD0.99".
+    pub price: String,
+    /// The date of purchase as eight digits, `YYYYMMDD`.
+    pub date: String,
+    /// The name of the seller.
+    pub seller: String,
+}
+
+impl Ownership {
+    /// Decodes the fields of an OWNE frame. Returns `None` if the fields do
+    /// not have the OWNE layout or cannot be decoded.
+    pub fn from_fields(fields: &[Field]) -> Option<Ownership> {
+        let (encoding, price, date, seller) = match fields {
+            &[Field::TextEncoding(encoding), Field::Latin1(ref price), Field::Latin1(ref date), Field::String(ref seller)] =>
+                (encoding, price, date, seller),
+            _ => return None
+        };
+        match (util::string_from_latin1(&*price), util::string_from_latin1(&*date),
+               util::string_from_encoding(encoding, &*seller)) {
+            (Some(price), Some(date), Some(seller)) => Some(Ownership {
+                price: price,
+                date: date,
+                seller: util::clean_decoded(seller),
+            }),
+            _ => None,
+        }
+    }
+
+    /// Returns whether the date of purchase is eight digits, as the OWNE
+    /// frame requires.
+    pub fn is_valid_date(&self) -> bool {
+        self.date.len() == 8 && self.date.bytes().all(|b| b'0' <= b && b <= b'9')
+    }
+
+    /// Returns the fields of an OWNE frame holding the ownership, with the
+    /// seller encoded using the given encoding.
+    pub fn to_fields(&self, encoding: Encoding) -> Vec<Field> {
+        vec![
+            Field::TextEncoding(encoding),
+            Field::Latin1(util::string_to_latin1(&*self.price)),
+            Field::Latin1(util::string_to_latin1(&*self.date)),
+            Field::String(util::encode_string(&*self.seller, encoding)),
+        ]
+    }
+}
+
+// Tests {{{
+#[cfg(test)]
+mod tests {
+    use super::Ownership;
+    use id3v2::frame::Encoding;
+    use id3v2::frame::field::Field;
+
+    #[test]
+    fn test_from_fields() {
+        let fields = vec![
+            Field::TextEncoding(Encoding::UTF8),
+            Field::Latin1(b"EUR1.29".to_vec()),
+            Field::Latin1(b"20140321".to_vec()),
+            Field::String("Plattenladen Köln".as_bytes().to_vec()),
+        ];
+        let ownership = Ownership::from_fields(&*fields).unwrap();
+        assert_eq!(ownership, Ownership {
+            price: "EUR1.29".to_owned(),
+            date: "20140321".to_owned(),
+            seller: "Plattenladen Köln".to_owned(),
+        });
+        assert!(ownership.is_valid_date());
+        assert_eq!(ownership.to_fields(Encoding::UTF8), fields);
+
+        assert!(Ownership::from_fields(&fields[..3]).is_none());
+    }
+
+    #[test]
+    fn test_is_valid_date() {
+        let ownership = |date: &str| Ownership { price: String::new(), date: date.to_owned(), seller: String::new() };
+        assert!(ownership("19991231").is_valid_date());
+        assert!(!ownership("1999123").is_valid_date());
+        assert!(!ownership("1999-12-31").is_valid_date());
+        assert!(!ownership("1999123a").is_valid_date());
+    }
+}
+// }}}
@@ -24,3 +24,16 @@ pub enum PictureType {
     BandLogo,
     PublisherLogo
 }
+
+impl PictureType {
+    /// Returns the picture type with the given APIC/PIC byte value, if valid.
+    pub fn from_u8(n: u8) -> Option<PictureType> {
+        use self::PictureType::*;
+        static TYPES: [PictureType; 21] = [
+            Other, Icon, OtherIcon, CoverFront, CoverBack, Leaflet, Media, LeadArtist, Artist,
+            Conductor, Band, Composer, Lyricist, RecordingLocation, DuringRecording,
+            DuringPerformance, ScreenCapture, BrightFish, Illustration, BandLogo, PublisherLogo,
+        ];
+        TYPES.get(n as usize).cloned()
+    }
+}
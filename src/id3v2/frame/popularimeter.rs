@@ -0,0 +1,42 @@
+use id3v2::frame::field::{Field, BigNum};
+use util;
+
+/// The decoded contents of a POPM (popularimeter) frame, which records how
+/// much a user, identified by email address, likes the file.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Popularimeter {
+    /// The email address of the user the rating belongs to.
+    pub email: String,
+    /// The rating, from 1 (worst) to 255 (best); 0 means unknown.
+    pub rating: u8,
+    /// The number of times the user has played the file.
+    pub counter: BigNum,
+}
+
+impl Popularimeter {
+    /// Decodes the fields of a POPM frame. The counter may be omitted, in
+    /// which case it is taken to be zero.
+    pub fn from_fields(fields: &[Field]) -> Option<Popularimeter> {
+        let (email, rating, counter) = match fields {
+            &[Field::Latin1(ref email), Field::Int8(rating), Field::Int32Plus(ref counter)] =>
+                (email, rating, counter.clone()),
+            &[Field::Latin1(ref email), Field::Int8(rating)] =>
+                (email, rating, BigNum::new(vec![])),
+            _ => return None
+        };
+        util::string_from_latin1(&*email).map(|email| Popularimeter {
+            email: email,
+            rating: rating,
+            counter: counter,
+        })
+    }
+
+    /// Returns the fields of a POPM frame holding the popularimeter.
+    pub fn to_fields(&self) -> Vec<Field> {
+        vec![
+            Field::Latin1(util::string_to_latin1(&*self.email)),
+            Field::Int8(self.rating),
+            Field::Int32Plus(self.counter.clone()),
+        ]
+    }
+}
@@ -11,11 +11,11 @@ macro_rules! id_or_padding {
     ($reader:ident, $n:expr) => {
         {
             let mut buf = [0; $n];
-            try!($reader.read(&mut buf[0..1]));
+            read_all!($reader, &mut buf[0..1]);
             if buf[0] == 0 { // padding
                 return Ok((1, None));
             }
-            try!($reader.read(&mut buf[1..]));
+            read_all!($reader, &mut buf[1..]);
             buf
         }
     };
@@ -5,6 +5,7 @@ use self::byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
 use id3v2::frame::stream::FrameStream;
 use id3v2::frame::{Frame, Id};
 use id3v2::Error;
+use id3v2::ErrorKind::InvalidTag;
 use std::io::{self, Read, Write};
 use self::flate2::write::ZlibEncoder;
 use util;
@@ -34,10 +35,16 @@ impl FrameStream for FrameV3 {
             k - Grouping identity
         */
 
-        let mut read_size = content_size;
+        // the frame size includes the data following the header
+        let header_data_size = if frame.flags.compression { 4 } else { 0 }
+            + frame.flags.encryption as u32 + frame.flags.grouping_identity as u32;
+        if content_size < header_data_size {
+            return Err(Error::new(InvalidTag, "frame is too small to hold its header data"));
+        }
+        let read_size = content_size - header_data_size;
+
         if frame.flags.compression {
             let _decompressed_size = try!(reader.read_u32::<BigEndian>());
-            read_size -= 4;
         }
 
         if frame.flags.encryption {
@@ -71,6 +78,9 @@ impl FrameStream for FrameV3 {
             content_bytes = try!(encoder.finish());
             content_size = content_bytes.len() as u32 + 4;
         }
+        if frame.flags.grouping_identity {
+            content_size += 1;
+        }
 
         if let Id::V3(id_bytes)=frame.id {
             try!(writer.write(&id_bytes));
@@ -82,6 +92,9 @@ impl FrameStream for FrameV3 {
         if frame.flags.compression {
             try!(writer.write(&util::u32_to_bytes(decompressed_size)));
         }
+        if frame.flags.grouping_identity {
+            try!(writer.write(&[frame.group_symbol]));
+        }
         if unsynchronization {
             util::unsynchronize(&mut content_bytes);
         }
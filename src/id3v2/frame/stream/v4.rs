@@ -38,9 +38,6 @@ impl FrameStream for FrameV4 {
             p - Data length indicator
         */
 
-        if frame.flags.grouping_identity {
-            frame.group_symbol = try!(reader.read_u8());
-        }
         if frame.flags.compression {
             if !frame.flags.data_length_indicator {
                 debug!("[{:?}] compression without data length indicator", frame.id);
@@ -53,8 +50,18 @@ impl FrameStream for FrameV4 {
             return Err(Error::new(UnsupportedFeature, "encryption is not supported"));
         }
         let mut read_size = content_size;
+        if frame.flags.grouping_identity {
+            if read_size < 1 {
+                return Err(Error::new(InvalidTag, "frame is too small to hold its group symbol"));
+            }
+            frame.group_symbol = try!(reader.read_u8());
+            read_size -= 1;
+        }
         if frame.flags.data_length_indicator {
-            let _decompressed_size = util::unsynchsafe(try!(reader.read_u32::<BigEndian>()));
+            if read_size < 4 {
+                return Err(Error::new(InvalidTag, "frame is too small to hold its data length indicator"));
+            }
+            frame.data_length = Some(util::unsynchsafe(try!(reader.read_u32::<BigEndian>())));
             read_size -= 4;
         }
 
@@ -81,9 +88,28 @@ impl FrameStream for FrameV4 {
             content_size = content_bytes.len() as u32;
         }
 
-        if frame.flags.data_length_indicator {
+        // compressed frames must carry a data length indicator
+        let data_length_indicator = frame.flags.data_length_indicator || frame.flags.compression;
+        if data_length_indicator {
             content_size += 4;
         }
+        if frame.flags.grouping_identity {
+            content_size += 1;
+        }
+
+        // the unsynchronization flag must describe the content as written,
+        // regardless of whether the frame was unsynchronized when read
+        let mut flag_bytes = frame.flags.to_bytes(0x4);
+        flag_bytes[1] &= !0x02;
+        if data_length_indicator {
+            flag_bytes[1] |= 0x01;
+        }
+        if unsynchronization {
+            let synced_len = content_bytes.len();
+            util::unsynchronize(&mut content_bytes);
+            content_size += (content_bytes.len() - synced_len) as u32;
+            flag_bytes[1] |= 0x02;
+        }
 
         if let Id::V4(id_bytes)=frame.id {
             try!(writer.write(&id_bytes));
@@ -91,14 +117,14 @@ impl FrameStream for FrameV4 {
             panic!("internal error: writing v2.4 frame but frame ID is not v2.4!");
         }
         try!(writer.write(&util::u32_to_bytes(util::synchsafe(content_size))));
-        try!(writer.write(&frame.flags.to_bytes(0x4)));
-        if frame.flags.data_length_indicator {
+        try!(writer.write(&flag_bytes));
+        if frame.flags.grouping_identity {
+            try!(writer.write(&[frame.group_symbol]));
+        }
+        if data_length_indicator {
             debug!("[{:?}] adding data length indicator", frame.id);
             try!(writer.write(&util::u32_to_bytes(util::synchsafe(decompressed_size))));
         }
-        if unsynchronization {
-            util::unsynchronize(&mut content_bytes);
-        }
         try!(writer.write(&*content_bytes));
 
         Ok(10 + content_size)
@@ -0,0 +1,168 @@
+use id3v2::frame::Encoding;
+use id3v2::frame::field::Field;
+use util;
+
+/// Units of the timestamps in a SYLT frame.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum TimestampFormat {
+    /// Timestamps count MPEG frames from the start of the file.
+    MpegFrames,
+    /// Timestamps count milliseconds from the start of the file.
+    Milliseconds,
+}
+
+impl TimestampFormat {
+    /// Returns the timestamp format with the given SYLT byte value, if valid.
+    pub fn from_u8(n: u8) -> Option<TimestampFormat> {
+        match n {
+            1 => Some(TimestampFormat::MpegFrames),
+            2 => Some(TimestampFormat::Milliseconds),
+            _ => None,
+        }
+    }
+
+    /// Returns the SYLT byte value of the timestamp format.
+    pub fn to_u8(&self) -> u8 {
+        match *self {
+            TimestampFormat::MpegFrames => 1,
+            TimestampFormat::Milliseconds => 2,
+        }
+    }
+}
+
+/// The decoded contents of a SYLT (synchronised lyrics) frame, which times
+/// lines of lyrics or other text to the audio.
+#[derive(Debug, PartialEq, Clone)]
+pub struct SyncedLyrics {
+    /// The ISO-639-2 language code of the text.
+    pub lang: String,
+    /// The units of the timestamps.
+    pub timestamp_format: TimestampFormat,
+    /// The type of the text, such as 1 for lyrics or 2 for a transcription.
+    pub content_type: u8,
+    /// The content descriptor.
+    pub description: String,
+    /// Each piece of text along with the time at which it starts.
+    pub content: Vec<(String, u32)>,
+}
+
+impl SyncedLyrics {
+    /// Decodes the fields of a SYLT frame. Returns `None` if the fields do not
+    /// have the SYLT layout or if the timed text is truncated or cannot be
+    /// decoded.
+    pub fn from_fields(fields: &[Field]) -> Option<SyncedLyrics> {
+        let (encoding, lang, format, content_type, description, data) = match fields {
+            &[Field::TextEncoding(encoding), Field::Language(lang), Field::Int8(format), Field::Int8(content_type),
+                    Field::String(ref description), Field::BinaryData(ref data)] =>
+                (encoding, lang, format, content_type, description, data),
+            _ => return None
+        };
+        let timestamp_format = match TimestampFormat::from_u8(format) {
+            Some(timestamp_format) => timestamp_format,
+            None => return None
+        };
+        let description = match util::string_from_encoding(encoding, &*description) {
+            Some(description) => description,
+            None => return None
+        };
+
+        let mut content = vec![];
+        let mut data = &data[..];
+        while !data.is_empty() {
+            let end = match find_delim(data, encoding) {
+                Some(end) => end,
+                None => return None
+            };
+            let text_end = end + util::delim_len(encoding);
+            if data.len() < text_end + 4 {
+                return None;
+            }
+            let text = match util::string_from_encoding(encoding, &data[..end]) {
+                Some(text) => util::clean_decoded(text),
+                None => return None
+            };
+            let timestamp = data[text_end..text_end + 4].iter().fold(0, |n, &b| n << 8 | b as u32);
+            content.push((text, timestamp));
+            data = &data[text_end + 4..];
+        }
+
+        Some(SyncedLyrics {
+            lang: String::from_utf8_lossy(&lang).into_owned(),
+            timestamp_format: timestamp_format,
+            content_type: content_type,
+            description: description,
+            content: content,
+        })
+    }
+
+    /// Returns the fields of a SYLT frame holding the lyrics, with text
+    /// encoded using the given encoding.
+    pub fn to_fields(&self, encoding: Encoding) -> Vec<Field> {
+        let mut data = vec![];
+        for &(ref text, timestamp) in self.content.iter() {
+            data.extend(util::encode_string(&*text, encoding));
+            data.extend(util::delim(encoding));
+            data.extend(&util::u32_to_bytes(timestamp));
+        }
+
+        vec![
+            Field::TextEncoding(encoding),
+            Field::Language(util::language_code(&*self.lang)),
+            Field::Int8(self.timestamp_format.to_u8()),
+            Field::Int8(self.content_type),
+            Field::String(util::encode_string(&*self.description, encoding)),
+            Field::BinaryData(data),
+        ]
+    }
+}
+
+/// Returns the offset of the first delimiter in text of the given encoding.
+/// UTF-16 delimiters are only found at even offsets, so that the zero high or
+/// low byte of a character is not mistaken for one.
+fn find_delim(data: &[u8], encoding: Encoding) -> Option<usize> {
+    let len = util::delim_len(encoding);
+    let mut i = 0;
+    while i + len <= data.len() {
+        if data[i..i + len].iter().all(|&b| b == 0) {
+            return Some(i);
+        }
+        i += len;
+    }
+    None
+}
+
+// Tests {{{
+#[cfg(test)]
+mod tests {
+    use super::{SyncedLyrics, TimestampFormat};
+    use id3v2::frame::Encoding;
+    use id3v2::frame::field::Field;
+
+    #[test]
+    fn test_round_trip() {
+        for &encoding in &[Encoding::Latin1, Encoding::UTF16, Encoding::UTF8] {
+            let lyrics = SyncedLyrics {
+                lang: "eng".to_owned(),
+                timestamp_format: TimestampFormat::Milliseconds,
+                content_type: 1,
+                description: "description".to_owned(),
+                content: vec![("first line".to_owned(), 1000), ("second line".to_owned(), 0x01020304)],
+            };
+            let fields = lyrics.to_fields(encoding);
+            assert_eq!(SyncedLyrics::from_fields(&*fields).unwrap(), lyrics);
+        }
+    }
+
+    #[test]
+    fn test_truncated() {
+        let fields = |data: &[u8]| vec![Field::TextEncoding(Encoding::Latin1), Field::Language(*b"eng"),
+            Field::Int8(1), Field::Int8(1), Field::String(vec![]), Field::BinaryData(data.to_vec())];
+        let lyrics = SyncedLyrics::from_fields(&*fields(b"line\0\0\0\0\x10")).unwrap();
+        assert_eq!(lyrics.timestamp_format, TimestampFormat::MpegFrames);
+        assert_eq!(lyrics.content, vec![("line".to_owned(), 0x10)]);
+
+        assert!(SyncedLyrics::from_fields(&*fields(b"line\0\0\0\0")).is_none());
+        assert!(SyncedLyrics::from_fields(&*fields(b"line")).is_none());
+    }
+}
+// }}}
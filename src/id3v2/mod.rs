@@ -1,17 +1,22 @@
 extern crate byteorder;
 extern crate flate2;
 
-use std::io::{self, Read, Write};
+use std::io::{self, Read, Write, Seek, SeekFrom};
 use std::io::ErrorKind::InvalidInput;
-use self::frame::{Frame, Encoding, Id};
-use self::frame::field::Field;
+use self::frame::{Frame, Encoding, Id, TextEncodingPolicy};
+use self::frame::field::{Field, BigNum};
 
-use self::byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use self::byteorder::{BigEndian, ByteOrder, ReadBytesExt, WriteBytesExt};
 
 pub use self::error::{Error, ErrorKind};
 
 use util;
+use id3v1;
+use genre::Genre;
 use std::fmt;
+use std::str;
+use std::{slice, vec};
+use std::borrow::Cow;
 
 mod error;
 
@@ -19,9 +24,17 @@ mod error;
 pub mod frame;
 /// High-level, lossy, and simple accessors for basic tag content.
 pub mod simple;
+mod restrictions;
+mod timestamp;
+
+pub use self::restrictions::{TagRestrictions, TagSizeRestriction, TextSizeRestriction, ImageSizeRestriction};
+pub use self::timestamp::Timestamp;
+
+/// The length of the padding written after the frames of a new tag.
+const DEFAULT_PADDING_LEN: u32 = 1024;
 
 /// An ID3v2 tag containing metadata frames.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Tag {
     /// The version of the ID3v2 tag.
     version: Version,
@@ -29,14 +42,31 @@ pub struct Tag {
     flags: TagFlags,
     /// A vector of frames included in the tag.
     pub frames: Vec<Frame>,
-    /// The size of padding which was included in the tag's serialized form.
+    /// The length of the padding which will be written after the frames.
     padding_len: u32,
+    /// Whether the frames will be written in canonical order.
+    sort_frames_on_write: bool,
     /// Extended header data (ID3v2.3 or ID3v2.4), if present.
     extended_header: Option<ExtendedHeader>,
 }
 
+/// How `Tag::merge` resolves conflicts between a frame of the tag being merged
+/// into and one of the tag being merged, which conflict if they should be
+/// unique within a tag and share the key `dedup` uses: the frame ID, along
+/// with the description, language, picture type or owner for frames which
+/// may appear several times with different ones.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum MergePolicy {
+    /// Keep the existing frame and discard the conflicting one.
+    KeepExisting,
+    /// Replace the existing frame with the conflicting one.
+    PreferOther,
+    /// Keep both frames, leaving any conflicts in the tag.
+    AppendAll,
+}
+
 /// A flag indicating the presence of a particular piece of ID3v2 extended header data.
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Clone)]
 pub enum ExtendedFlag {
     /// Indicates that this ID3v2 tag is an update to an earlier tag in the stream, as
     /// might occur in streaming media playback to override the previous track's title
@@ -61,16 +91,26 @@ impl ExtendedFlag
     /// Find the index of an extended header flag in a tag of the given ID3v2 version,
     /// counting from 0 at the first byte's MSB.
     pub fn to_index(&self, version: Version) -> u8
+    {
+        match self.index(version) {
+            Some(n) => n,
+            None => panic!("extended header flag incompatible with ID3v2 version"),
+        }
+    }
+
+    /// Like `to_index`, but returns `None` if the flag cannot be represented in
+    /// a tag of the given version.
+    pub fn index(&self, version: Version) -> Option<u8>
     {
         match (version, self)
         {
-            (Version::V3, &ExtendedFlag::Crc) => 0,
-            (Version::V3, &ExtendedFlag::Unknown(n)) => n,
-            (Version::V4, &ExtendedFlag::Update) => 1,
-            (Version::V4, &ExtendedFlag::Crc) => 2,
-            (Version::V4, &ExtendedFlag::TagRestrictions) => 3,
-            (Version::V4, &ExtendedFlag::Unknown(n)) => n,
-            _ => panic!("extended header flag incompatible with ID3v2 version"),
+            (Version::V3, &ExtendedFlag::Crc) => Some(0),
+            (Version::V3, &ExtendedFlag::Unknown(n)) => Some(n),
+            (Version::V4, &ExtendedFlag::Update) => Some(1),
+            (Version::V4, &ExtendedFlag::Crc) => Some(2),
+            (Version::V4, &ExtendedFlag::TagRestrictions) => Some(3),
+            (Version::V4, &ExtendedFlag::Unknown(n)) => Some(n),
+            _ => None,
         }
     }
     /// Obtain the meaning of an ID3v2 extended header flag from the index of its
@@ -178,47 +218,133 @@ impl<T, V, I: Iterator<Item=T>, F: Fn(I) -> V> Iterator for GroupBy<I, T>
 
 /// An ID3v2 extended header, which consists of a series of flags and
 /// corresponding data payloads.
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Clone)]
 pub struct ExtendedHeader {
-    flag_data: Vec<(ExtendedFlag, Vec<u8>)>
+    flag_data: Vec<(ExtendedFlag, Vec<u8>)>,
+    /// The size of the tag's padding, as recorded in an ID3v2.3 extended header.
+    padding_size: u32,
+}
+
+/// Encodes a CRC-32 as an extended header payload: four bytes in ID3v2.3 and
+/// a 35-bit synchsafe integer in ID3v2.4.
+fn crc_payload(crc: u32, version: Version) -> Vec<u8> {
+    match version {
+        Version::V4 => vec![(crc >> 28) as u8, (crc >> 21) as u8 & 0x7F, (crc >> 14) as u8 & 0x7F,
+                            (crc >> 7) as u8 & 0x7F, crc as u8 & 0x7F],
+        _ => util::u32_to_bytes(crc).to_vec(),
+    }
+}
+
+/// Decodes a CRC-32 from an extended header payload written by `crc_payload`.
+fn crc_value(payload: &[u8], version: Version) -> u32 {
+    match version {
+        Version::V4 => payload.iter().fold(0, |crc, &byte| crc << 7 | (byte & 0x7F) as u32),
+        _ => payload.iter().fold(0, |crc, &byte| crc << 8 | byte as u32),
+    }
+}
+
+/// Returns the number of bytes needed to hold the bits of the given flags.
+fn count_flag_bytes(flag_data: &[(u8, &Vec<u8>)], version: Version) -> u8 {
+    match version {
+        Version::V3 => 2,
+        _ => flag_data.iter().map(|&(index, _)| index / 8 + 1).max().unwrap_or(1),
+    }
 }
 
 impl ExtendedHeader {
-    /// Return the size in bytes of the serialized extended header.
-    pub fn size(&self) -> usize {
-        let flag_data_len: usize=self.flag_data.iter().map(|&(_, ref vec)| vec.len()).sum();
-        4/*size field*/+1/*bytes of flags*/+flag_data_len
+    /// Return the size in bytes of the extended header when serialized for a
+    /// tag of the given version. Flags the version cannot represent are left
+    /// out, and ID3v2.2 tags have no extended header at all.
+    pub fn size(&self, version: Version) -> usize {
+        let flag_data = self.indexed_flag_data(version);
+        match version {
+            Version::V2 => 0,
+            // size field, flag bytes, padding size, and CRC if present
+            Version::V3 => 4 + 2 + 4 + flag_data.iter().map(|&(_, data)| data.len()).sum::<usize>(),
+            // size field, number of flag bytes, flag bytes, and each flag's length byte and payload
+            Version::V4 => 4 + 1 + count_flag_bytes(&*flag_data, version) as usize
+                + flag_data.iter().map(|&(_, data)| 1 + data.len()).sum::<usize>(),
+        }
+    }
+
+    /// Returns the extended header to write for a tag of the given version
+    /// whose frames serialize to `frames` and are followed by `padding_len`
+    /// bytes of padding: the CRC, if present, is computed over the frames, and
+    /// an ID3v2.3 header records the padding's length.
+    fn describing(&self, frames: &[u8], padding_len: u32, version: Version) -> ExtendedHeader {
+        let flag_data = self.flag_data.iter().map(|&(ref flag, ref data)| match *flag {
+            ExtendedFlag::Crc => (ExtendedFlag::Crc, crc_payload(util::crc32(frames), version)),
+            _ => (flag.clone(), data.clone()),
+        }).collect();
+        ExtendedHeader {
+            flag_data: flag_data,
+            padding_size: if version == Version::V3 { padding_len } else { 0 },
+        }
+    }
+
+    /// Returns the index of each flag which a tag of the given version can
+    /// represent together with its payload, in the order of the flags' bits.
+    fn indexed_flag_data(&self, version: Version) -> Vec<(u8, &Vec<u8>)> {
+        let mut flag_data: Vec<_> = self.flag_data.iter()
+            .filter_map(|&(ref flag, ref data)| flag.index(version).map(|index| (index, data)))
+            .collect();
+        flag_data.sort_by_key(|&(index, _)| index);
+        flag_data
     }
-    /// Write the extended header to a writer.
+
+    /// Write the extended header to a writer. Flags the version cannot
+    /// represent are skipped, and nothing is written for an ID3v2.2 tag.
     pub fn write_to(&self, writer: &mut Write, version: Version) -> io::Result<u32> {
-        let size = self.size() as u32;
-        //TODO: verify endianness?
-        try!(writer.write_u32::<BigEndian>(util::synchsafe(size)));
+        let size = self.size(version) as u32;
+        // payloads are written in the order of their flags' bits
+        let flag_data = self.indexed_flag_data(version);
+        let n_flag_bytes = count_flag_bytes(&*flag_data, version);
+
+        let mut flag_bytes = vec![0u8; n_flag_bytes as usize];
+        for &(index, _) in flag_data.iter() {
+            flag_bytes[(index / 8) as usize] |= 0x80 >> (index % 8);
+        }
+
         match version
         {
-            Version::V2 => panic!("attempting to write extended header for an ID3v2.2 tag"),
-            Version::V3 => try!(writer.write(&[1u8])),
-            Version::V4 => try!(writer.write(&[42u8])),//TODO(sp3d): try!(writer.write(n_flag_bytes)),
+            Version::V2 => (),
+            Version::V3 => {
+                // the ID3v2.3 size is not synchsafe and excludes the size field itself
+                try!(writer.write_u32::<BigEndian>(size - 4));
+                try!(writer.write(&*flag_bytes));
+                try!(writer.write_u32::<BigEndian>(self.padding_size));
+                for &(_, data) in flag_data.iter() {
+                    try!(writer.write(&*data));
+                }
+            },
+            Version::V4 => {
+                try!(writer.write_u32::<BigEndian>(util::synchsafe(size)));
+                try!(writer.write(&[n_flag_bytes]));
+                try!(writer.write(&*flag_bytes));
+                for &(_, data) in flag_data.iter() {
+                    try!(writer.write(&[data.len() as u8]));
+                    try!(writer.write(&*data));
+                }
+            },
         };
-        //TODO(sp3d): write flag bytes
-        //write flag payloads
-        for &(_, ref vec) in self.flag_data.iter() {
-            try!(writer.write(&[vec.len() as u8]));
-            try!(writer.write(&*vec));
-        }
         Ok(size)
     }
+
     /// Parse an ID3v2 extended header for a tag with the given ID3v2 version from a reader.
-    /// The version must be Version::V3 or Version::V4.
+    /// Returns an error if the version is Version::V2, which has no extended header, or
+    /// if the extended header is truncated or malformed.
     pub fn parse<R: Read>(reader: &mut R, version: Version) -> io::Result<(ExtendedHeader, usize)> {
         let mut offset = 0;
-        let size = util::unsynchsafe(try!(reader.read_u32::<BigEndian>()));
+        let size = match version {
+            Version::V3 => try!(reader.read_u32::<BigEndian>()),
+            _ => util::unsynchsafe(try!(reader.read_u32::<BigEndian>())),
+        };
         offset += 4;
 
         //figure out how many bytes of flags to read
         let n_flag_bytes = match version
         {
-            Version::V2 => panic!("attempting to parse extended header for an ID3v2.2 tag"),
+            Version::V2 => return Err(io::Error::new(InvalidInput, "ID3v2.2 tags have no extended header")),
             Version::V3 => 2,
             Version::V4 => {
                 offset += 1;
@@ -245,29 +371,51 @@ impl ExtendedHeader {
             }
         }
         let mut flag_data=vec![];
-        let mut size_remaining = size;
-
-        //read the payload, in (data_size, data) format, for each flag
-        for flag in flags
-        {
-            let data_size = try!(reader.read_u8()) as u32;
-            offset += 1;
+        let mut padding_size = 0;
+
+        if version == Version::V3 {
+            // ID3v2.3 has a fixed layout: the padding size, then the CRC if present
+            padding_size = try!(reader.read_u32::<BigEndian>());
+            offset += 4;
+            for flag in flags {
+                if let ExtendedFlag::Crc = flag {
+                    let mut crc = vec![0; 4]; read_all!(reader, &mut *crc);
+                    offset += 4;
+                    flag_data.push((flag, crc));
+                } else {
+                    debug!("dropping unknown ID3v2.3 extended header flag {:?}", flag);
+                }
+            }
+            // skip anything we did not understand
+            let size_read = offset - 4;
+            if (size as usize) > size_read {
+                let mut skipped = vec![];
+                read_all_vec!(reader, skipped, size as usize - size_read);
+                offset += skipped.len();
+            }
+        } else {
+            let mut size_remaining = (size as usize).saturating_sub(offset);
 
-            if size_remaining < data_size
+            //read the payload, in (data_size, data) format, for each flag
+            for flag in flags
             {
-                //TODO(sp3d): return error
-                //return Err("ran out of data before running out of flags");
-                panic!("ran out of data before running out of flags");
-            }
+                let data_size = try!(reader.read_u8()) as usize;
+                offset += 1;
 
-            let mut flag_datum = vec![0; data_size as usize]; try!(reader.read(&mut flag_datum)); //read_all!(reader, &mut ext_header);
-            flag_data.push((flag, flag_datum));
+                if size_remaining < data_size + 1
+                {
+                    return Err(io::Error::new(InvalidInput, "ran out of extended header data before running out of flags"));
+                }
 
-            size_remaining -= data_size;
-            offset += data_size as usize;
+                let mut flag_datum = vec![0; data_size]; read_all!(reader, &mut *flag_datum);
+                flag_data.push((flag, flag_datum));
+
+                size_remaining -= data_size + 1;
+                offset += data_size;
+            }
         }
 
-        Ok((ExtendedHeader { flag_data: flag_data }, offset))
+        Ok((ExtendedHeader { flag_data: flag_data, padding_size: padding_size }, offset))
     }
 }
 
@@ -452,22 +600,71 @@ impl Version {
     id_func!(genre_id, b"TCO", b"TCON");
     id_func!(year_id, b"TYE", b"TYER");
     id_func!(track_id, b"TRK", b"TRCK");
+    id_func!(disc_id, b"TPA", b"TPOS");
     id_func!(lyrics_id, b"ULT", b"USLT");
     id_func!(picture_id, b"PIC", b"APIC");
     id_func!(comment_id, b"COM", b"COMM");
     id_func!(txxx_id, b"TXX", b"TXXX");
+    id_func!(wxxx_id, b"WXX", b"WXXX");
+    id_func!(isrc_id, b"TRC", b"TSRC");
+    id_func!(play_count_id, b"CNT", b"PCNT");
+    id_func!(popularimeter_id, b"POP", b"POPM");
+    id_func!(unique_file_id_id, b"UFI", b"UFID");
+    id_func!(synced_lyrics_id, b"SLT", b"SYLT");
+    id_func!(event_timing_codes_id, b"ETC", b"ETCO");
 // }}}
 
 /// Checks for presence of the signature indicating an ID3v2 tag at the reader's current offset.
 /// Consumes 3 bytes from the reader.
 pub fn probe_tag<R: Read>(reader: &mut R) -> io::Result<bool> {
     let mut identifier = [0u8; 3];
-    try!(reader.read(&mut identifier));
-    Ok(identifier == *b"ID3")
+    let len = try!(util::read_fully(reader, &mut identifier));
+    Ok(len == identifier.len() && identifier == *b"ID3")
+}
+
+/// Options controlling how `read_tag_with` reads a tag.
+#[derive(Debug, Default, PartialEq, Copy, Clone)]
+pub struct ParseOptions {
+    /// Whether to skip frames which cannot be read, using the size declared in
+    /// their header to find the next frame, rather than failing to read the
+    /// tag. A frame whose declared size overruns the tag ends the tag's frames.
+    pub skip_bad_frames: bool,
 }
 
 /// Read an ID3v2 tag from a reader.
-pub fn read_tag<R: Read>(mut reader: &mut R) -> Result<Option<Tag>, io::Error> {
+pub fn read_tag<R: Read>(reader: &mut R) -> Result<Option<Tag>, io::Error> {
+    read_tag_impl(reader, false, ParseOptions::default(), &mut vec![])
+}
+
+/// Read an ID3v2 tag from a reader according to the options. Along with the
+/// tag, returns the errors of any frames which were skipped.
+///
+/// # Example
+/// ```
+/// use id3::id3v2::{self, ParseOptions};
+///
+/// let mut data: &[u8] = b"ID3\x03\x00\x00\x00\x00\x00\x0BTIT2\x00\x00\x00\x01\x00\x00\x07";
+/// assert!(id3v2::read_tag(&mut data.clone()).is_err());
+///
+/// let (tag, errors) = id3v2::read_tag_with(&mut data, ParseOptions { skip_bad_frames: true }).unwrap().unwrap();
+/// assert!(tag.frames.is_empty());
+/// assert_eq!(errors.len(), 1);
+/// ```
+pub fn read_tag_with<R: Read>(reader: &mut R, options: ParseOptions) -> Result<Option<(Tag, Vec<Error>)>, io::Error> {
+    let mut errors = vec![];
+    let tag = try!(read_tag_impl(reader, false, options, &mut errors));
+    Ok(tag.map(|tag| (tag, errors)))
+}
+
+/// Read an ID3v2 tag from a reader, remembering the serialized form of each
+/// frame. When the tag is written, frames which have not been modified are
+/// written back byte-for-byte, so that editing one frame changes as little of
+/// the tag as possible.
+pub fn read_tag_preserving<R: Read>(reader: &mut R) -> Result<Option<Tag>, io::Error> {
+    read_tag_impl(reader, true, ParseOptions::default(), &mut vec![])
+}
+
+fn read_tag_impl<R: Read>(reader: &mut R, preserve: bool, options: ParseOptions, errors: &mut Vec<Error>) -> Result<Option<Tag>, io::Error> {
     use self::TagFlag::*;
     let mut tag = Tag::new();
 
@@ -476,7 +673,7 @@ pub fn read_tag<R: Read>(mut reader: &mut R) -> Result<Option<Tag>, io::Error> {
     }
 
     let mut version_bytes = [0u8; 2];
-    try!(reader.read(&mut version_bytes));
+    read_all!(reader, &mut version_bytes);
 
     debug!("tag version bytes {:?}", version_bytes);
 
@@ -490,15 +687,56 @@ pub fn read_tag<R: Read>(mut reader: &mut R) -> Result<Option<Tag>, io::Error> {
     tag.flags = TagFlags::from_byte(try!(reader.read_u8()), tag.version());
 
     if tag.flags.get(Compression) {
-        panic!("ID3v2.2 compression is unsupported");
+        return Err(io::Error::new(InvalidInput, "ID3v2.2 compression is unsupported"));
     }
 
     let tag_size = util::unsynchsafe(try!(reader.read_u32::<BigEndian>()));
 
-    let mut offset = 10;
+    // ID3v2.4 unsynchronizes each frame separately, indicating so in the frame
+    // flags, while earlier versions unsynchronize the whole tag body
+    if tag.flags.get(Unsynchronization) && tag.version() < Version::V4 {
+        let mut body = vec![];
+        try!(util::ResyncReader::new(reader.by_ref().take(tag_size as u64)).read_to_end(&mut body));
+        debug!("removed {} unsynchronization bytes", tag_size as usize - body.len());
+        let body_size = body.len() as u32;
+        try!(read_tag_body(&mut &*body, &mut tag, body_size, false, preserve, options, errors));
+    } else {
+        let frame_unsync = tag.flags.get(Unsynchronization);
+        try!(read_tag_body(reader, &mut tag, tag_size, frame_unsync, preserve, options, errors));
+    }
+
+    if tag.flags.get(Footer) {
+        let mut footer = [0u8; 10];
+        read_all!(reader, &mut footer);
+        if &footer[..3] != b"3DI" {
+            warn!("ID3v2 footer signature not found after tag body");
+        }
+    }
+
+    Ok(Some(tag))
+}
+
+/// Checks for an ID3v2.4 footer at the reader's current offset, returning the
+/// size of the tag body it declares. Consumes 10 bytes from the reader.
+fn probe_footer<R: Read>(reader: &mut R) -> io::Result<Option<u32>> {
+    let mut footer = [0u8; 10];
+    read_all!(reader, &mut footer);
+    if &footer[..3] != b"3DI" || footer[3] != 4 {
+        return Ok(None);
+    }
+    Ok(Some(util::unsynchsafe(BigEndian::read_u32(&footer[6..10]))))
+}
+
+/// Reads the extended header, frames and padding of a tag whose body (the part
+/// following the 10-byte header) is `body_size` bytes long. Frames which cannot
+/// be read are skipped if the options say so, with their errors added to
+/// `errors`.
+fn read_tag_body<R: Read>(mut reader: &mut R, tag: &mut Tag, body_size: u32, unsynchronization: bool, preserve: bool,
+                          options: ParseOptions, errors: &mut Vec<Error>) -> Result<(), io::Error> {
+    let mut offset = 0;
 
     // TODO actually use the extended header data
-    if tag.flags.get(ExtendedHeader) {
+    if tag.flags.get(TagFlag::ExtendedHeader) {
         let (eh, eh_size) = try!(self::ExtendedHeader::parse(&mut reader, tag.version));
         tag.extended_header = Some(eh);
         offset += eh_size;
@@ -506,8 +744,39 @@ pub fn read_tag<R: Read>(mut reader: &mut R) -> Result<Option<Tag>, io::Error> {
 
     let mut padding_len = 0;
 
-    while offset < tag_size as usize + 10 {
-        let frame = match Frame::read_from(reader, tag.version(), tag.flags.get(Unsynchronization)) {
+    if options.skip_bad_frames {
+        // the frames are buffered so that the reader can skip back to the end
+        // of a frame which failed partway through
+        let mut body = vec![];
+        try!(reader.by_ref().take(body_size.saturating_sub(offset as u32) as u64).read_to_end(&mut body));
+        let mut pos = 0;
+        while pos < body.len() {
+            match read_frame(&mut &body[pos..], tag.version(), unsynchronization, preserve) {
+                Ok((bytes_read, Some(frame))) => {
+                    pos += bytes_read as usize;
+                    tag.frames.push(frame);
+                },
+                Ok((bytes_read, None)) => {
+                    pos += bytes_read as usize;
+                    padding_len += bytes_read;
+                },
+                Err(err) => {
+                    debug!("skipping unreadable frame: {}", err);
+                    errors.push(err);
+                    match declared_frame_len(&body[pos..], tag.version()) {
+                        Some(len) if len <= body.len() - pos => pos += len,
+                        _ => break,
+                    }
+                },
+            }
+        }
+        tag.padding_len = padding_len as u32;
+        return Ok(());
+    }
+
+    while offset < body_size as usize {
+        let result = read_frame(reader, tag.version(), unsynchronization, preserve);
+        let frame = match result {
             Ok((bytes_read, maybe_frame)) => {
                 offset += bytes_read as usize;
                 match maybe_frame {
@@ -526,7 +795,72 @@ pub fn read_tag<R: Read>(mut reader: &mut R) -> Result<Option<Tag>, io::Error> {
 
     tag.padding_len = padding_len as u32;
 
-    Ok(Some(tag))
+    Ok(())
+}
+
+fn read_frame(reader: &mut Read, version: Version, unsynchronization: bool, preserve: bool) -> Result<(u32, Option<Frame>), Error> {
+    if preserve {
+        Frame::read_from_preserving(reader, version, unsynchronization)
+    } else {
+        Frame::read_from(reader, version, unsynchronization)
+    }
+}
+
+/// Returns the length, including the header, which the header at the start of
+/// the data declares for its frame, or `None` if the header is incomplete.
+fn declared_frame_len(data: &[u8], version: Version) -> Option<usize> {
+    match version {
+        Version::V2 if data.len() >= 6 => Some(6 + BigEndian::read_uint(&data[3..6], 3) as usize),
+        Version::V3 if data.len() >= 10 => Some(10 + BigEndian::read_u32(&data[4..8]) as usize),
+        Version::V4 if data.len() >= 10 => Some(10 + util::unsynchsafe(BigEndian::read_u32(&data[4..8])) as usize),
+        _ => None,
+    }
+}
+
+/// Returns the name for a genre reference: an ID3v1 genre number, or one of the
+/// special `RX` (remix) and `CR` (cover) tokens.
+fn genre_reference_name(reference: &str) -> Option<&'static str> {
+    match reference {
+        "RX" => Some("Remix"),
+        "CR" => Some("Cover"),
+        number => number.parse::<u8>().ok().and_then(id3v1::genre_name),
+    }
+}
+
+/// Expands the genre references in a single genre frame value, appending the
+/// resulting names to `names`.
+fn expand_genre(value: &str, names: &mut Vec<String>) {
+    // ID3v2.4 stores references without parentheses
+    if let Some(name) = genre_reference_name(value) {
+        names.push(name.to_owned());
+        return
+    }
+
+    let mut rest = value;
+    let mut expanded = 0;
+    while rest.starts_with('(') && !rest.starts_with("((") {
+        let name = match rest.find(')') {
+            Some(end) => genre_reference_name(&rest[1..end]).map(|name| (name, end)),
+            None => None,
+        };
+        match name {
+            Some((name, end)) => {
+                names.push(name.to_owned());
+                expanded += 1;
+                rest = &rest[end + 1..];
+            },
+            None => break,
+        }
+    }
+
+    // a doubled parenthesis escapes a refinement which begins with one
+    if rest.starts_with("((") {
+        rest = &rest[1..];
+    }
+    let repeats_last = expanded > 0 && names.last().map_or(false, |last| last == rest);
+    if !rest.is_empty() && !repeats_last {
+        names.push(rest.to_owned());
+    }
 }
 
 // Tag {{{
@@ -540,85 +874,99 @@ impl Tag {
     /// Create a new ID3 tag with the specified version.
     #[inline]
     pub fn with_version(version: Version) -> Tag {
+        Tag::with_capacity(version, 0)
+    }
+
+    /// Create a new ID3 tag with the specified version and room for `capacity`
+    /// frames to be added without reallocating.
+    #[inline]
+    pub fn with_capacity(version: Version, capacity: usize) -> Tag {
         Tag {
             version: version,
             flags: TagFlags::new(version),
-            frames: Vec::new(),
-            padding_len: 0,
+            frames: Vec::with_capacity(capacity),
+            padding_len: DEFAULT_PADDING_LEN,
+            sort_frames_on_write: false,
             extended_header: None,
         }
     }
 
-    /// Get the tag's ID3v2 version.
-    #[inline]
-    pub fn version(&self) -> Version {
-        self.version
-    }
-
-    /// Get the serialized size of the tag.
+    /// Reserves room for at least `additional` more frames to be added to the
+    /// tag without reallocating.
     #[inline]
-    pub fn size(&self, unsynchronization: bool) -> u32 {
-        10 + self.frames.iter().map(|x| x.size(unsynchronization)).sum::<u32>()
-    }
-
-    /// Serialize the ID3v2 tag to a writer. If successful, returns the number
-    /// of bytes written.
-    pub fn write_to(&self, writer: &mut Write, unsynchronization: bool) -> Result<u32, io::Error> {
-        try!(writer.write(b"ID3"));
-        try!(writer.write(&self.version().to_bytes()));
-        try!(writer.write_u8(self.flags().to_byte()));
-        try!(writer.write_u32::<BigEndian>(util::synchsafe(self.size(unsynchronization))));
-
-        let mut bytes_written = 10;
-
-        if let Some(ref extended) = self.extended_header {
-            debug!("writing extended header");
-            try!(extended.write_to(writer, self.version));
-        };
-
-        for frame in &self.frames {
-            debug!("writing {:?}", frame.id);
-            bytes_written += try!(frame.write_to(writer, unsynchronization));
-        }
-        Ok(bytes_written)
+    pub fn reserve(&mut self, additional: usize) {
+        self.frames.reserve(additional);
     }
 
-    /// Converts the tag to the specified version, dropping any data that
-    /// cannot be represented in the new version.
-    ///
-    /// Since this is a lossy conversion, converting a tag from version A to
-    /// version B and then back to its original version is unlikely to preserve
-    /// all tag data.
+    /// Creates a tag of the specified version holding the information in an
+    /// ID3v1 tag: its title, artist, album, year, comment, ID3v1.1 track
+    /// number, and genre. The genre is written as a reference to the ID3v1
+    /// genre number, refined by the extended genre string if there is one.
+    /// Text is stored with ISO-8859-1 encoding, the encoding of ID3v1 tags.
     ///
     /// # Example
     /// ```
-    /// use id3::id3v2;
-    /// use id3::id3v2::Version::{V3, V4};
+    /// use id3::{id3v1, id3v2};
+    /// use id3::id3v2::Version::V3;
     ///
-    /// let mut tag = id3v2::Tag::with_version(V4);
-    /// assert_eq!(tag.version(), V4);
+    /// let mut v1 = id3v1::Tag::new();
+    /// v1.artist = b"artist".to_vec();
     ///
-    /// tag.convert_version(V3);
-    /// assert_eq!(tag.version(), V3);
+    /// let tag = id3v2::Tag::from_id3v1(&v1, V3);
+    /// assert_eq!(tag.text_frame_text(V3.artist_id()).unwrap(), "artist");
     /// ```
-    pub fn convert_version(&mut self, version: Version) {
-        if self.version == version {
-            return;
+    pub fn from_id3v1(v1: &id3v1::Tag, version: Version) -> Tag {
+        use self::simple::Simple;
+
+        let mut tag = Tag::with_version(version);
+        let texts = [(version.title_id(), &v1.title), (version.artist_id(), &v1.artist), (version.album_id(), &v1.album)];
+        for &(id, field) in texts.iter() {
+            if let Some(text) = id3v1::decode_text(&*field) {
+                tag.add_text_frame_enc(id, &*text, Encoding::Latin1);
+            }
         }
 
-        self.version = version;
+        if v1.year.value() != 0 {
+            let id = match version {
+                Version::V4 => Id::V4(*b"TDRC"),
+                _ => version.year_id(),
+            };
+            tag.add_text_frame_enc(id, &*v1.year.value().to_string(), Encoding::Latin1);
+        }
+        if let Some(comment) = id3v1::decode_text(&*v1.comment) {
+            tag.set_comment("eng", "", &*comment, Encoding::Latin1);
+        }
+        if v1.track != 0 {
+            let id = version.track_id();
+            tag.add_text_frame_enc(id, &*v1.track.to_string(), Encoding::Latin1);
+        }
 
-        let mut remove = Vec::new();
-        for frame in self.frames.iter_mut() {
-            if !frame.convert_version(version) {
-                remove.push(frame as *mut _ as *const _);
-            }
+        let refinement = id3v1::decode_text(&*v1.genre_str);
+        let genre = match (Genre::from_code(v1.genre), refinement) {
+            (Some(genre), Some(refinement)) => Some(match version {
+                Version::V4 => format!("{}\0{}", genre.to_code(), refinement),
+                _ => format!("({}){}", genre.to_code(), refinement),
+            }),
+            (Some(genre), None) => Some(match version {
+                Version::V4 => genre.to_code().to_string(),
+                _ => format!("({})", genre.to_code()),
+            }),
+            (None, refinement) => refinement,
+        };
+        if let Some(genre) = genre {
+            let id = version.genre_id();
+            tag.add_text_frame_enc(id, &*genre, Encoding::Latin1);
         }
+        tag
+    }
 
-        self.frames.retain(|frame: &Frame| !remove.contains(&(frame as *const _)));
+    /// Get the tag's ID3v2 version.
+    #[inline]
+    pub fn version(&self) -> Version {
+        self.version
     }
 
-    /// Returns a vector of references to all frames in the tag.
+    /// Returns whether the tag contains no frames.
     ///
     /// # Example
     /// ```
@@ -626,24 +974,18 @@ impl Tag {
     /// use id3::id3v2::frame::{Frame, Id};
     ///
     /// let mut tag = id3v2::Tag::new();
+    /// assert!(tag.is_empty());
     ///
-    /// tag.add_frame(Frame::new(Id::V4(*b"TPE1")));
-    /// tag.add_frame(Frame::new(Id::V4(*b"APIC")));
-    ///
-    /// assert_eq!(tag.get_frames().len(), 2);
+    /// tag.add_frame(Frame::new(Id::V4(*b"TIT2")));
+    /// assert!(!tag.is_empty());
     /// ```
     #[inline]
-    pub fn get_frames<'a>(&'a self) -> &'a Vec<Frame> {
-        &self.frames
-    }
-
-    /// Get a tag's flags.
-    #[inline]
-    pub fn flags(&self) -> TagFlags {
-        self.flags
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
     }
 
-    /// Returns a reference to the first frame with the specified identifier.
+    /// Removes all frames from the tag, keeping its version, flags, and
+    /// extended header.
     ///
     /// # Example
     /// ```
@@ -651,127 +993,519 @@ impl Tag {
     /// use id3::id3v2::frame::{Frame, Id};
     ///
     /// let mut tag = id3v2::Tag::new();
-    ///
     /// tag.add_frame(Frame::new(Id::V4(*b"TIT2")));
-    ///
-    /// assert!(tag.get_frame_by_id(Id::V4(*b"TIT2")).is_some());
-    /// assert!(tag.get_frame_by_id(Id::V4(*b"TCON")).is_none());
+    /// tag.clear();
+    /// assert!(tag.is_empty());
     /// ```
-    pub fn get_frame_by_id<'a>(&'a self, id: frame::Id) -> Option<&'a Frame> {
-        for frame in self.frames.iter() {
-            if frame.id == id {
-                return Some(frame);
+    #[inline]
+    pub fn clear(&mut self) {
+        self.frames.clear();
+    }
+
+    /// Get the serialized size of the tag, including its padding and any
+    /// footer. Unless an ID3v2.2 or ID3v2.3 tag is unsynchronized, which
+    /// depends on every byte written, this is computed from the lengths of the
+    /// parts rather than by serializing the tag.
+    pub fn size(&self, unsynchronization: bool) -> u32 {
+        if unsynchronization && self.version() < Version::V4 {
+            // a body which fails to serialize can't be written either, so the
+            // size computed below is as good an answer as any
+            if let Ok(body) = self.body_bytes(true) {
+                return 10 + body.len() as u32 + self.footer_len();
             }
         }
 
-        None
+        let extended_len = self.extended_header.as_ref().map_or(0, |extended| extended.size(self.version) as u32);
+        let frames_len = self.frames.iter().map(|frame| frame.serialized_len(unsynchronization)).sum::<u32>();
+        // padding is forbidden in tags with a footer
+        let padding_len = if self.footer_len() > 0 { 0 } else { self.padding_len };
+        10 + extended_len + frames_len + padding_len + self.footer_len()
     }
 
-    /// Returns a vector of references to frames with the specified identifier.
+    /// Returns the number of bytes `to_bytes` produces, and so the space the
+    /// tag takes up in a file: the header, the extended header, the frames,
+    /// the padding, and the footer, using unsynchronization if the tag's flags
+    /// call for it.
     ///
     /// # Example
     /// ```
     /// use id3::id3v2;
-    /// use id3::id3v2::frame::{Frame, Id};
+    /// use id3::id3v2::frame::Id;
     ///
     /// let mut tag = id3v2::Tag::new();
+    /// tag.add_text_frame(Id::V4(*b"TIT2"), "title");
+    /// tag.set_padding_len(100);
     ///
-    /// tag.add_frame(Frame::new(Id::V4(*b"TXXX")));
-    /// tag.add_frame(Frame::new(Id::V4(*b"TXXX")));
-    /// tag.add_frame(Frame::new(Id::V4(*b"TALB")));
-    ///
-    /// assert_eq!(tag.get_frames_by_id(Id::V4(*b"TXXX")).len(), 2);
-    /// assert_eq!(tag.get_frames_by_id(Id::V4(*b"TALB")).len(), 1);
+    /// assert_eq!(tag.on_disk_size(), 10 + 10 + 6 + 100);
+    /// assert_eq!(tag.on_disk_size() as usize, tag.to_bytes().unwrap().len());
     /// ```
-    pub fn get_frames_by_id<'a>(&'a self, id: frame::Id) -> Vec<&'a Frame> {
-        let mut matches = Vec::new();
-        for frame in self.frames.iter() {
-            if frame.id == id {
-                matches.push(frame);
-            }
-        }
+    #[inline]
+    pub fn on_disk_size(&self) -> u32 {
+        self.size(self.flags.get(TagFlag::Unsynchronization))
+    }
 
-        matches
+    /// Returns the length of the footer which will be written after the
+    /// frames: 10 bytes if the tag has the footer flag set, and zero otherwise.
+    #[inline]
+    fn footer_len(&self) -> u32 {
+        if self.flags.get(TagFlag::Footer) { 10 } else { 0 }
     }
 
-    /// Adds a frame to the tag. The versions of the tag and frame must match.
-    ///
-    /// Returns TRUE after adding the frame if the versions matched, and
-    /// returns FALSE and does nothing if not.
-    ///
-    /// # Example
-    /// ```
-    /// use id3::id3v2;
-    /// use id3::id3v2::frame::{Frame, Id};
-    ///
-    /// let id = Id::V4(*b"TALB");
-    /// let mut tag = id3v2::Tag::new();
-    /// tag.add_frame(Frame::new(id));
-    /// assert_eq!(tag.get_frames()[0].id, id);
-    /// ```
-    pub fn add_frame(&mut self, frame: Frame) -> bool {
-        if frame.version() != self.version() {
-            return false;
-        }
-        self.frames.push(frame);
-        true
+    /// Returns the length of the padding which will be written after the
+    /// frames. Unless set with `set_padding_len`, this is the length of the
+    /// padding the tag was read with, so that rewriting a tag keeps the
+    /// layout of the file stable, or 1024 bytes for a new tag.
+    #[inline]
+    pub fn padding_len(&self) -> u32 {
+        self.padding_len
     }
 
-    /// Adds a text frame with the given ID and a UTF-8 string as content.
-    /// Returns whether the frame successfully created.
+    /// Sets the length of the padding which will be written after the
+    /// frames. Padding leaves room for the tag to grow when it is next stored
+    /// without moving the data following it. A tag with a footer is never
+    /// padded, as ID3v2.4 forbids it.
     ///
     /// # Example
     /// ```
     /// use id3::id3v2;
-    /// use id3::id3v2::frame::Id;
     ///
-    /// let id = Id::V4(*b"TCON");
     /// let mut tag = id3v2::Tag::new();
-    /// tag.add_text_frame(id, "Metal");
-    /// assert_eq!(tag.text_frame_text(id).unwrap(), "Metal");
+    /// tag.set_padding_len(0);
+    /// assert_eq!(tag.size(false), 10);
     /// ```
     #[inline]
-    pub fn add_text_frame(&mut self, id: frame::Id, text: &str) -> bool {
-        match Frame::new_text_frame(id, text, Encoding::UTF8) {
-            Some(frame) => {
-                self.remove_frames_by_id(id);
-                self.frames.push(frame);
-                true
-            }
-            None => false,
-        }
+    pub fn set_padding_len(&mut self, padding_len: u32) {
+        self.padding_len = padding_len;
     }
 
-    /// Adds a text frame with the given contents, which will be transcoded from
-    /// UTF-8 to the specified encoding.
+    /// Reads an ID3v2.4 tag appended to the end of a seekable reader, as in
+    /// streams, by locating its footer. The footer may be followed by an
+    /// ID3v1 tag. Returns `None` if no footer is found.
     ///
-    /// # Example
-    /// ```
-    /// use id3::id3v2;
-    /// use id3::id3v2::frame::Id;
-    /// use id3::id3v2::frame::Encoding::UTF16;
-    ///
-    /// let id = Id::V4(*b"TRCK");
-    /// let mut tag = id3v2::Tag::new();
-    /// tag.add_text_frame_enc(id, "1/13", UTF16);
-    /// assert_eq!(tag.text_frame_text(id).unwrap(), "1/13");
-    /// ```
+    /// If a tag is found, the reader is left positioned just past its footer.
+    pub fn read_from_end<R: Read + Seek>(reader: &mut R) -> Result<Option<Tag>, io::Error> {
+        let end = try!(reader.seek(SeekFrom::End(0)));
+        // the footer is either the last thing in the stream or just precedes an ID3v1 tag
+        for &trailer_len in &[0u64, id3v1::TAG_OFFSET as u64] {
+            if end < 20 + trailer_len {
+                continue;
+            }
+            let footer_offset = end - trailer_len - 10;
+            try!(reader.seek(SeekFrom::Start(footer_offset)));
+            let body_size = match try!(probe_footer(reader)) {
+                Some(body_size) => body_size as u64,
+                None => continue,
+            };
+            if footer_offset < 10 + body_size {
+                continue;
+            }
 
-    /* TODO(sp3d): find a more type-safe way to encode this
-    as formulated, there are lots of errors that can be made:
-    incompatible version+encoding, lossy transcoding into Latin-1, non-text IDs
-    some of these should be preventable in the typesystem
-    or handled explicitly as behavior option arguments for encoding*/
-    pub fn add_text_frame_enc(&mut self, id: frame::Id, text: &str, encoding: Encoding) {
-        self.remove_frames_by_id(id);
-        let frame = Frame::new_text_frame(id, text, encoding).expect("ID is not a text frame!");
-        self.frames.push(frame);
+            try!(reader.seek(SeekFrom::Start(footer_offset - body_size - 10)));
+            return read_tag(reader);
+        }
+        Ok(None)
     }
 
-    /// Removes all frames with the specified identifier.
-    ///
-    /// # Example
-    /// ```
+    /// Serialize the ID3v2 tag to a writer. If successful, returns the number
+    /// of bytes written. If the footer flag is set, the tag is followed by a
+    /// footer and no padding. Fails without writing anything if the tag
+    /// following its header is 256MB or larger, more than its size field can
+    /// represent.
+    pub fn write_to(&self, writer: &mut Write, unsynchronization: bool) -> Result<u32, io::Error> {
+        let body = try!(self.body_bytes(unsynchronization));
+        try!(self.write_with_body(writer, unsynchronization, &*body));
+        Ok(10 + body.len() as u32 + self.footer_len())
+    }
+
+    /// Serializes the tag to a writer, padding it to exactly `total_size`
+    /// bytes, as when overwriting a tag in place without moving the data
+    /// following it. Returns FALSE and writes nothing if the tag does not fit.
+    /// A tag with a footer may not be padded, so it only fits if it is exactly
+    /// `total_size` bytes long.
+    pub fn write_to_fit(&self, writer: &mut Write, unsynchronization: bool, total_size: u32) -> Result<bool, io::Error> {
+        let mut body = try!(self.unpadded_body_bytes(unsynchronization, 0));
+        let unpadded_size = 10 + body.len() + self.footer_len() as usize;
+        // the padding must be nonempty if it has to guard against a false sync
+        let min_padding = if self.needs_sync_guard(unsynchronization, &*body) { 1 } else { 0 };
+        let fits = if self.footer_len() > 0 {
+            unpadded_size == total_size as usize
+        } else {
+            unpadded_size + min_padding <= total_size as usize
+        };
+        if !fits {
+            return Ok(false);
+        }
+
+        let mut padding_len = total_size as usize - unpadded_size;
+        if self.extended_header.is_some() {
+            // the extended header records the length of the padding, which in
+            // rare cases changes the length of an unsynchronized body
+            body = try!(self.unpadded_body_bytes(unsynchronization, padding_len as u32));
+            match (total_size as usize).checked_sub(10 + body.len() + self.footer_len() as usize) {
+                Some(len) if len >= min_padding => padding_len = len,
+                _ => return Ok(false),
+            }
+        }
+        body.extend(vec![0; padding_len]);
+        try!(self.write_with_body(writer, unsynchronization, &*body));
+        Ok(true)
+    }
+
+    /// Writes the tag's 10-byte header followed by the given body and, if the
+    /// footer flag is set, the footer. Fails without writing anything if the
+    /// body is too large for the header's synchsafe size field.
+    fn write_with_body(&self, writer: &mut Write, unsynchronization: bool, body: &[u8]) -> Result<(), io::Error> {
+        let size = match util::synchsafe_checked(body.len() as u32) {
+            Some(size) if body.len() <= u32::max_value() as usize => size,
+            _ => return Err(io::Error::new(InvalidInput, "tag is too large for its size to be represented")),
+        };
+        let mut flags = self.flags();
+        flags.set(TagFlag::Unsynchronization, unsynchronization);
+
+        // the footer repeats the header, save for its signature
+        let mut header = vec![];
+        try!(header.write(&self.version().to_bytes()));
+        try!(header.write_u8(flags.to_byte()));
+        // the size field counts everything after the header, excluding the footer
+        try!(header.write_u32::<BigEndian>(size));
+
+        try!(writer.write(b"ID3"));
+        try!(writer.write_all(&*header));
+        try!(writer.write_all(body));
+        if self.footer_len() > 0 {
+            try!(writer.write(b"3DI"));
+            try!(writer.write_all(&*header));
+        }
+        Ok(())
+    }
+
+    /// Serializes everything following the tag's 10-byte header: the extended
+    /// header, the frames, and the padding.
+    fn body_bytes(&self, unsynchronization: bool) -> Result<Vec<u8>, io::Error> {
+        // padding is forbidden in tags with a footer
+        let mut padding_len = if self.footer_len() > 0 { 0 } else { self.padding_len };
+        let mut body = try!(self.unpadded_body_bytes(unsynchronization, padding_len));
+        if padding_len == 0 && self.needs_sync_guard(unsynchronization, &*body) {
+            // the guard is a byte of padding, which the extended header records
+            padding_len = 1;
+            if self.extended_header.is_some() {
+                body = try!(self.unpadded_body_bytes(unsynchronization, padding_len));
+            }
+        }
+        body.extend(vec![0; padding_len as usize]);
+
+        Ok(body)
+    }
+
+    /// Returns whether an unsynchronized body ends with a byte which would form
+    /// a false sync with whatever follows the tag, unless padding follows it.
+    fn needs_sync_guard(&self, unsynchronization: bool, body: &[u8]) -> bool {
+        unsynchronization && self.version() < Version::V4 && body.last() == Some(&0xFF)
+    }
+
+    /// Serializes the extended header and the frames. ID3v2.4 frames are
+    /// unsynchronized individually, while for earlier versions the whole body
+    /// is unsynchronized at once. The extended header is written to describe
+    /// the frames and the `padding_len` bytes of padding which will follow.
+    fn unpadded_body_bytes(&self, unsynchronization: bool, padding_len: u32) -> Result<Vec<u8>, io::Error> {
+        let mut frames: Vec<&Frame> = self.frames.iter().collect();
+        if self.sort_frames_on_write {
+            frames.sort_by_key(|frame| frame_order_class(frame.id));
+        }
+
+        let frame_unsync = unsynchronization && self.version() >= Version::V4;
+        let mut frame_bytes = vec![];
+        for frame in frames {
+            debug!("writing {:?}", frame.id);
+            try!(frame.write_to(&mut frame_bytes, frame_unsync));
+        }
+
+        let mut body = vec![];
+        if let Some(ref extended) = self.extended_header {
+            debug!("writing extended header");
+            try!(extended.describing(&*frame_bytes, padding_len, self.version).write_to(&mut body, self.version));
+        };
+        body.extend(frame_bytes);
+
+        if unsynchronization && self.version() < Version::V4 {
+            util::unsynchronize(&mut body);
+        }
+
+        Ok(body)
+    }
+
+    /// Serializes the tag to a byte vector, using unsynchronization if the
+    /// tag's flags call for it. Like `write_to`, fails if the tag is too large
+    /// for its size field.
+    ///
+    /// # Example
+    /// ```
+    /// use id3::id3v2;
+    /// use id3::id3v2::frame::Id;
+    ///
+    /// let mut tag = id3v2::Tag::new();
+    /// tag.add_text_frame(Id::V4(*b"TIT2"), "title");
+    ///
+    /// let bytes = tag.to_bytes().unwrap();
+    /// assert_eq!(&bytes[..3], b"ID3");
+    /// assert_eq!(id3v2::Tag::from_bytes(&*bytes).unwrap().unwrap().frames.len(), 1);
+    /// ```
+    pub fn to_bytes(&self) -> Result<Vec<u8>, io::Error> {
+        let mut bytes = vec![];
+        // writing to a vec cannot fail, so an error means the tag is too large
+        try!(self.write_to(&mut bytes, self.flags.get(TagFlag::Unsynchronization)));
+        Ok(bytes)
+    }
+
+    /// Parses an ID3v2 tag from the start of a byte slice. Returns `Ok(None)`
+    /// if the slice does not begin with a tag.
+    pub fn from_bytes(data: &[u8]) -> Result<Option<Tag>, Error> {
+        let mut reader = data;
+        read_tag(&mut reader).map_err(From::from)
+    }
+
+    /// Seeks to the given absolute offset in a writer and serializes the tag
+    /// there, as when rewriting a tag chunk of a container in place. Uses
+    /// unsynchronization if the tag's flags call for it. If successful,
+    /// returns the number of bytes written.
+    ///
+    /// # Example
+    /// ```
+    /// use std::io::Cursor;
+    /// use id3::id3v2;
+    ///
+    /// let tag = id3v2::Tag::new();
+    /// let mut buf = Cursor::new(vec![0xFFu8; 32]);
+    /// let written = tag.write_at(&mut buf, 8).unwrap();
+    /// assert_eq!(&buf.get_ref()[8..11], b"ID3");
+    /// assert_eq!(buf.position(), 8 + written as u64);
+    /// ```
+    pub fn write_at<W: Write + Seek>(&self, writer: &mut W, offset: u64) -> io::Result<u32> {
+        try!(writer.seek(SeekFrom::Start(offset)));
+        self.write_to(writer, self.flags.get(TagFlag::Unsynchronization))
+    }
+
+    /// Serializes the tag to a buffer, reads it back, and checks that the
+    /// frames read match the originals. Returns an error if serialization and
+    /// parsing are not inverses for some frame of this tag.
+    ///
+    /// # Example
+    /// ```
+    /// use id3::id3v2;
+    /// use id3::id3v2::frame::{Frame, Id};
+    ///
+    /// let mut tag = id3v2::Tag::new();
+    /// tag.add_frame(Frame::new_url_frame(Id::V4(*b"WOAR"), b"http://example.com/").unwrap());
+    ///
+    /// assert!(tag.self_check().is_ok());
+    /// ```
+    pub fn self_check(&self) -> Result<(), Error> {
+        let mut buf = vec![];
+        try!(self.write_to(&mut buf, self.flags.get(TagFlag::Unsynchronization)));
+        let reread = match try!(read_tag(&mut &*buf)) {
+            Some(tag) => tag,
+            None => return Err(Error::new(ErrorKind::InvalidTag, "serialized tag was not found when re-read")),
+        };
+        if reread.frames.len() != self.frames.len() {
+            return Err(Error::new(ErrorKind::InvalidTag, "re-read tag has a different number of frames"));
+        }
+        for (original, parsed) in self.frames.iter().zip(reread.frames.iter()) {
+            if original.id != parsed.id || original.fields != parsed.fields {
+                debug!("frame {:?} did not survive a round trip", original.id);
+                return Err(Error::new(ErrorKind::InvalidTag, "re-read frame differs from the original"));
+            }
+        }
+        Ok(())
+    }
+
+    /// Converts the tag to the specified version, dropping any data that
+    /// cannot be represented in the new version.
+    ///
+    /// Since this is a lossy conversion, converting a tag from version A to
+    /// version B and then back to its original version is unlikely to preserve
+    /// all tag data. Frames whose IDs cannot be converted are removed, as are
+    /// frames whose converted fields do not fit the field layout of their new
+    /// ID.
+    ///
+    /// # Example
+    /// ```
+    /// use id3::id3v2;
+    /// use id3::id3v2::Version::{V3, V4};
+    ///
+    /// let mut tag = id3v2::Tag::with_version(V4);
+    /// assert_eq!(tag.version(), V4);
+    ///
+    /// tag.convert_version(V3);
+    /// assert_eq!(tag.version(), V3);
+    /// ```
+    pub fn convert_version(&mut self, version: Version) {
+        if self.version == version {
+            return;
+        }
+
+        let old_version = self.version;
+        self.version = version;
+
+        // only the CRC flag exists in both versions with an extended header,
+        // though its payload is laid out differently
+        if let Some(mut extended) = self.extended_header.take() {
+            extended.flag_data.retain(|&(ref flag, _)| *flag == ExtendedFlag::Crc);
+            for &mut (_, ref mut data) in extended.flag_data.iter_mut() {
+                *data = crc_payload(crc_value(&*data, old_version), version);
+            }
+            if version == Version::V2 || extended.flag_data.is_empty() {
+                self.flags.set(TagFlag::ExtendedHeader, false);
+            } else {
+                self.extended_header = Some(extended);
+            }
+        }
+
+        let mut remove = Vec::new();
+        for frame in self.frames.iter_mut() {
+            if !frame.convert_version(version) {
+                remove.push(frame as *mut _ as *const _);
+            } else if !frame.fields_match_format() {
+                debug!("{:?} cannot be represented in {:?}, removing", frame.id, version);
+                remove.push(frame as *mut _ as *const _);
+            }
+        }
+
+        self.frames.retain(|frame: &Frame| !remove.contains(&(frame as *const _)));
+    }
+
+    /// Re-encodes the text of every frame using UTF-8 or UTF-16BE as UTF-16,
+    /// the only Unicode encoding available in ID3v2.2 and ID3v2.3. Returns the
+    /// IDs of the frames which were changed.
+    ///
+    /// # Example
+    /// ```
+    /// use id3::id3v2;
+    /// use id3::id3v2::frame::{Id, Encoding};
+    ///
+    /// let mut tag = id3v2::Tag::new();
+    /// tag.add_text_frame_enc(Id::V4(*b"TALB"), "album", Encoding::UTF8);
+    /// tag.add_text_frame_enc(Id::V4(*b"TIT2"), "title", Encoding::Latin1);
+    ///
+    /// assert_eq!(tag.downgrade_encodings_for_v3(), vec![Id::V4(*b"TALB")]);
+    /// assert_eq!(tag.get_frame_by_id(Id::V4(*b"TALB")).unwrap().encoding(), Some(Encoding::UTF16));
+    /// ```
+    pub fn downgrade_encodings_for_v3(&mut self) -> Vec<frame::Id> {
+        let mut changed = Vec::new();
+        for frame in self.frames.iter_mut() {
+            match frame.encoding() {
+                Some(Encoding::UTF8) | Some(Encoding::UTF16BE) => {
+                    if frame.set_encoding(Encoding::UTF16) {
+                        changed.push(frame.id);
+                    }
+                },
+                _ => (),
+            }
+        }
+        changed
+    }
+
+    /// Returns a vector of references to all frames in the tag.
+    ///
+    /// # Example
+    /// ```
+    /// use id3::id3v2;
+    /// use id3::id3v2::frame::{Frame, Id};
+    ///
+    /// let mut tag = id3v2::Tag::new();
+    ///
+    /// tag.add_frame(Frame::new(Id::V4(*b"TPE1")));
+    /// tag.add_frame(Frame::new(Id::V4(*b"APIC")));
+    ///
+    /// assert_eq!(tag.get_frames().len(), 2);
+    /// ```
+    #[inline]
+    pub fn get_frames<'a>(&'a self) -> &'a Vec<Frame> {
+        &self.frames
+    }
+
+    /// Returns an iterator over the frames in the tag. This is preferred to
+    /// accessing `frames` directly.
+    ///
+    /// # Example
+    /// ```
+    /// use id3::id3v2;
+    /// use id3::id3v2::frame::{Frame, Id};
+    ///
+    /// let mut tag = id3v2::Tag::new();
+    /// tag.add_frame(Frame::new(Id::V4(*b"TPE1")));
+    /// tag.add_frame(Frame::new(Id::V4(*b"APIC")));
+    ///
+    /// let ids: Vec<_> = tag.iter().map(|frame| frame.id).collect();
+    /// assert_eq!(ids, vec![Id::V4(*b"TPE1"), Id::V4(*b"APIC")]);
+    ///
+    /// for frame in &tag {
+    ///     assert!(frame.fields.is_empty());
+    /// }
+    /// ```
+    #[inline]
+    pub fn iter<'a>(&'a self) -> slice::Iter<'a, Frame> {
+        self.frames.iter()
+    }
+
+    /// Returns an iterator over mutable references to the frames in the tag.
+    #[inline]
+    pub fn iter_mut<'a>(&'a mut self) -> slice::IterMut<'a, Frame> {
+        self.frames.iter_mut()
+    }
+
+    /// Get a tag's flags.
+    #[inline]
+    pub fn flags(&self) -> TagFlags {
+        self.flags
+    }
+
+    /// Returns a reference to the first frame with the specified identifier.
+    ///
+    /// # Example
+    /// ```
+    /// use id3::id3v2;
+    /// use id3::id3v2::frame::{Frame, Id};
+    ///
+    /// let mut tag = id3v2::Tag::new();
+    ///
+    /// tag.add_frame(Frame::new(Id::V4(*b"TIT2")));
+    ///
+    /// assert!(tag.get_frame_by_id(Id::V4(*b"TIT2")).is_some());
+    /// assert!(tag.get_frame_by_id(Id::V4(*b"TCON")).is_none());
+    /// ```
+    pub fn get_frame_by_id<'a>(&'a self, id: frame::Id) -> Option<&'a Frame> {
+        for frame in self.frames.iter() {
+            if frame.id == id {
+                return Some(frame);
+            }
+        }
+
+        None
+    }
+
+    /// Returns the text encoding of the first frame with the specified
+    /// identifier, so that an edited frame can keep the encoding it was stored
+    /// in. Returns `None` if there is no such frame or it has no text encoding.
+    ///
+    /// # Example
+    /// ```
+    /// use id3::id3v2;
+    /// use id3::id3v2::frame::{Id, Encoding};
+    ///
+    /// let mut tag = id3v2::Tag::with_version(id3v2::Version::V3);
+    /// tag.add_text_frame_enc(Id::V3(*b"TIT2"), "title", Encoding::UTF16);
+    ///
+    /// assert_eq!(tag.frame_encoding(Id::V3(*b"TIT2")), Some(Encoding::UTF16));
+    /// assert_eq!(tag.frame_encoding(Id::V3(*b"TALB")), None);
+    /// ```
+    pub fn frame_encoding(&self, id: frame::Id) -> Option<Encoding> {
+        self.get_frame_by_id(id).and_then(|frame| frame.encoding())
+    }
+
+    /// Returns a vector of references to frames with the specified identifier.
+    ///
+    /// # Example
+    /// ```
     /// use id3::id3v2;
     /// use id3::id3v2::frame::{Frame, Id};
     ///
@@ -779,20 +1513,946 @@ impl Tag {
     ///
     /// tag.add_frame(Frame::new(Id::V4(*b"TXXX")));
     /// tag.add_frame(Frame::new(Id::V4(*b"TXXX")));
-    /// tag.add_frame(Frame::new(Id::V4(*b"USLT")));
+    /// tag.add_frame(Frame::new(Id::V4(*b"TALB")));
     ///
-    /// assert_eq!(tag.get_frames().len(), 3);
+    /// assert_eq!(tag.get_frames_by_id(Id::V4(*b"TXXX")).len(), 2);
+    /// assert_eq!(tag.get_frames_by_id(Id::V4(*b"TALB")).len(), 1);
+    /// ```
+    pub fn get_frames_by_id<'a>(&'a self, id: frame::Id) -> Vec<&'a Frame> {
+        let mut matches = Vec::new();
+        for frame in self.frames.iter() {
+            if frame.id == id {
+                matches.push(frame);
+            }
+        }
+
+        matches
+    }
+
+    /// Returns a mutable reference to the first frame with the specified identifier.
     ///
-    /// tag.remove_frames_by_id(Id::V4(*b"TXXX"));
-    /// assert_eq!(tag.get_frames().len(), 1);
+    /// # Example
+    /// ```
+    /// use id3::id3v2;
+    /// use id3::id3v2::frame::{Frame, Id};
+    ///
+    /// let mut tag = id3v2::Tag::new();
+    ///
+    /// tag.add_frame(Frame::new(Id::V4(*b"TIT2")));
+    ///
+    /// tag.get_frame_by_id_mut(Id::V4(*b"TIT2")).unwrap().set_read_only(true);
+    /// assert!(tag.get_frame_by_id(Id::V4(*b"TIT2")).unwrap().read_only());
+    /// assert!(tag.get_frame_by_id_mut(Id::V4(*b"TCON")).is_none());
+    /// ```
+    pub fn get_frame_by_id_mut<'a>(&'a mut self, id: frame::Id) -> Option<&'a mut Frame> {
+        for frame in self.frames.iter_mut() {
+            if frame.id == id {
+                return Some(frame);
+            }
+        }
+
+        None
+    }
+
+    /// Returns a vector of mutable references to frames with the specified identifier.
+    ///
+    /// # Example
+    /// ```
+    /// use id3::id3v2;
+    /// use id3::id3v2::frame::{Frame, Id};
+    ///
+    /// let mut tag = id3v2::Tag::new();
+    ///
+    /// tag.add_frame(Frame::new(Id::V4(*b"TXXX")));
+    /// tag.add_frame(Frame::new(Id::V4(*b"TXXX")));
+    /// tag.add_frame(Frame::new(Id::V4(*b"TALB")));
+    ///
+    /// for frame in tag.get_frames_by_id_mut(Id::V4(*b"TXXX")) {
+    ///     frame.set_compression(true);
+    /// }
+    /// assert!(tag.get_frames_by_id(Id::V4(*b"TXXX")).iter().all(|frame| frame.compression()));
+    /// assert!(!tag.get_frame_by_id(Id::V4(*b"TALB")).unwrap().compression());
+    /// ```
+    pub fn get_frames_by_id_mut<'a>(&'a mut self, id: frame::Id) -> Vec<&'a mut Frame> {
+        let mut matches = Vec::new();
+        for frame in self.frames.iter_mut() {
+            if frame.id == id {
+                matches.push(frame);
+            }
+        }
+
+        matches
+    }
+
+    /// Adds a frame to the tag. The versions of the tag and frame must match,
+    /// and the frame's ID must be valid.
+    ///
+    /// Returns TRUE after adding the frame if the versions matched and the ID
+    /// was valid, and returns FALSE and does nothing if not.
+    ///
+    /// # Example
+    /// ```
+    /// use id3::id3v2;
+    /// use id3::id3v2::frame::{Frame, Id};
+    ///
+    /// let id = Id::V4(*b"TALB");
+    /// let mut tag = id3v2::Tag::new();
+    /// tag.add_frame(Frame::new(id));
+    /// assert_eq!(tag.get_frames()[0].id, id);
+    /// ```
+    pub fn add_frame(&mut self, frame: Frame) -> bool {
+        if frame.version() != self.version() || !frame.id.is_valid() {
+            return false;
+        }
+        self.frames.push(frame);
+        true
+    }
+
+    /// Adds a text frame with the given ID and a UTF-8 string as content.
+    /// Returns whether the frame successfully created.
+    ///
+    /// # Example
+    /// ```
+    /// use id3::id3v2;
+    /// use id3::id3v2::frame::Id;
+    ///
+    /// let id = Id::V4(*b"TCON");
+    /// let mut tag = id3v2::Tag::new();
+    /// tag.add_text_frame(id, "Metal");
+    /// assert_eq!(tag.text_frame_text(id).unwrap(), "Metal");
+    /// ```
+    #[inline]
+    pub fn add_text_frame(&mut self, id: frame::Id, text: &str) -> bool {
+        match Frame::new_text_frame(id, text, Encoding::UTF8) {
+            Some(frame) => {
+                self.remove_frames_by_id(id);
+                self.frames.push(frame);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Adds a text frame with the given contents, which will be transcoded from
+    /// UTF-8 to the specified encoding. Characters which the encoding cannot
+    /// represent are replaced.
+    ///
+    /// Returns false without modifying the tag if the ID is not that of a
+    /// text frame or if the encoding is not supported by its version; use
+    /// `try_add_text_frame_enc` to find out why.
+    ///
+    /// # Example
+    /// ```
+    /// use id3::id3v2;
+    /// use id3::id3v2::frame::Id;
+    /// use id3::id3v2::frame::Encoding::UTF16;
+    ///
+    /// let id = Id::V4(*b"TRCK");
+    /// let mut tag = id3v2::Tag::new();
+    /// assert!(tag.add_text_frame_enc(id, "1/13", UTF16));
+    /// assert_eq!(tag.text_frame_text(id).unwrap(), "1/13");
+    /// assert!(!tag.add_text_frame_enc(Id::V4(*b"APIC"), "1/13", UTF16));
+    /// ```
+
+    /* TODO(sp3d): find a more type-safe way to encode this
+    as formulated, there are lots of errors that can be made:
+    incompatible version+encoding, lossy transcoding into Latin-1, non-text IDs
+    some of these should be preventable in the typesystem
+    or handled explicitly as behavior option arguments for encoding*/
+    pub fn add_text_frame_enc(&mut self, id: frame::Id, text: &str, encoding: Encoding) -> bool {
+        self.try_add_text_frame_enc(id, text, encoding, TextEncodingPolicy::Lossy).is_ok()
+    }
+
+    /// Adds a text frame with the given contents like `add_text_frame_enc`,
+    /// but returns an error instead of panicking if the ID is not a text frame
+    /// ID or the encoding is not supported by its version, and, if the policy
+    /// is `Strict`, if the text cannot be represented in the encoding. Existing
+    /// frames with the ID are only removed if the frame is added.
+    pub fn try_add_text_frame_enc(&mut self, id: frame::Id, text: &str, encoding: Encoding, policy: TextEncodingPolicy) -> Result<(), Error> {
+        let frame = try!(Frame::try_new_text_frame(id, text, encoding, policy));
+        self.remove_frames_by_id(id);
+        self.frames.push(frame);
+        Ok(())
+    }
+
+    /// Removes all frames with the specified identifier.
+    ///
+    /// # Example
+    /// ```
+    /// use id3::id3v2;
+    /// use id3::id3v2::frame::{Frame, Id};
+    ///
+    /// let mut tag = id3v2::Tag::new();
+    ///
+    /// tag.add_frame(Frame::new(Id::V4(*b"TXXX")));
+    /// tag.add_frame(Frame::new(Id::V4(*b"TXXX")));
+    /// tag.add_frame(Frame::new(Id::V4(*b"USLT")));
+    ///
+    /// assert_eq!(tag.get_frames().len(), 3);
+    ///
+    /// tag.remove_frames_by_id(Id::V4(*b"TXXX"));
+    /// assert_eq!(tag.get_frames().len(), 1);
+    ///
+    /// tag.remove_frames_by_id(Id::V4(*b"USLT"));
+    /// assert_eq!(tag.get_frames().len(), 0);
+    /// ```
+    pub fn remove_frames_by_id(&mut self, id: frame::Id) {
+        self.frames.retain(|frame| {
+            frame.id != id
+        });
+    }
+
+    /// Retains only the frames for which the predicate returns `true`,
+    /// preserving their order.
+    ///
+    /// # Example
+    /// ```
+    /// use id3::id3v2;
+    /// use id3::id3v2::frame::{Frame, Id};
+    ///
+    /// let mut tag = id3v2::Tag::new();
+    /// tag.add_frame(Frame::new(Id::V4(*b"TIT2")));
+    /// tag.add_frame(Frame::new(Id::V4(*b"APIC")));
+    ///
+    /// tag.retain_frames(|frame| frame.id.is_text());
+    /// assert_eq!(tag.get_frames().len(), 1);
+    /// ```
+    #[inline]
+    pub fn retain_frames<F: FnMut(&Frame) -> bool>(&mut self, f: F) {
+        self.frames.retain(f);
+    }
+
+    /// Removes the frames for which the predicate returns `true`.
+    ///
+    /// # Example
+    /// ```
+    /// use id3::id3v2;
+    /// use id3::id3v2::frame::{Frame, Id};
+    ///
+    /// let mut tag = id3v2::Tag::new();
+    /// tag.add_frame(Frame::new(Id::V4(*b"TIT2")));
+    /// tag.add_frame(Frame::new(Id::V4(*b"APIC")));
+    ///
+    /// tag.remove_frames(|frame| frame.id == Id::V4(*b"APIC"));
+    /// assert_eq!(tag.get_frames().len(), 1);
+    /// ```
+    #[inline]
+    pub fn remove_frames<F: FnMut(&Frame) -> bool>(&mut self, mut f: F) {
+        self.frames.retain(|frame| !f(frame));
+    }
+
+    /// Sorts the frames into a canonical order: text frames first, then URL
+    /// frames, then other frames, and finally attached pictures, so that
+    /// players which only read the start of a tag find the basic metadata
+    /// before any large images. Frames of the same class keep their relative
+    /// order.
+    ///
+    /// # Example
+    /// ```
+    /// use id3::id3v2;
+    /// use id3::id3v2::frame::{Frame, Id};
+    ///
+    /// let mut tag = id3v2::Tag::new();
+    /// tag.add_frame(Frame::new(Id::V4(*b"APIC")));
+    /// tag.add_frame(Frame::new(Id::V4(*b"WOAR")));
+    /// tag.add_frame(Frame::new(Id::V4(*b"TIT2")));
+    ///
+    /// tag.sort_frames();
+    /// let ids: Vec<_> = tag.iter().map(|frame| frame.id).collect();
+    /// assert_eq!(ids, vec![Id::V4(*b"TIT2"), Id::V4(*b"WOAR"), Id::V4(*b"APIC")]);
+    /// ```
+    pub fn sort_frames(&mut self) {
+        self.frames.sort_by_key(|frame| frame_order_class(frame.id));
+    }
+
+    /// Removes duplicates of frames which should be unique within a tag,
+    /// keeping the last occurrence of each. Text frames and play counters
+    /// are unique by ID; TXXX and WXXX frames by description; COMM and USLT
+    /// frames by language and description; attached pictures by picture type;
+    /// and UFID and POPM frames by owner or email address.
+    ///
+    /// # Example
+    /// ```
+    /// use id3::id3v2;
+    /// use id3::id3v2::frame::{Frame, Id, Encoding};
+    ///
+    /// let mut tag = id3v2::Tag::new();
+    /// tag.add_text_frame(Id::V4(*b"TIT2"), "old title");
+    /// tag.add_text_frame(Id::V4(*b"TALB"), "album");
+    /// tag.add_frame(Frame::new_text_frame(Id::V4(*b"TIT2"), "new title", Encoding::UTF8).unwrap());
+    ///
+    /// tag.dedup();
+    /// let ids: Vec<_> = tag.iter().map(|frame| frame.id).collect();
+    /// assert_eq!(ids, vec![Id::V4(*b"TALB"), Id::V4(*b"TIT2")]);
+    /// ```
+    pub fn dedup(&mut self) {
+        self.dedup_impl(true);
+    }
+
+    /// Removes duplicates of frames which should be unique within a tag, as
+    /// `dedup` does, but keeps the first occurrence of each.
+    pub fn dedup_keep_first(&mut self) {
+        self.dedup_impl(false);
+    }
+
+    fn dedup_impl(&mut self, keep_last: bool) {
+        if keep_last {
+            self.frames.reverse();
+        }
+        let mut seen: Vec<(frame::Id, Vec<u8>)> = Vec::new();
+        self.frames.retain(|frame| match uniqueness_key(frame) {
+            Some(ref key) if seen.contains(key) => false,
+            Some(key) => {
+                seen.push(key);
+                true
+            },
+            None => true
+        });
+        if keep_last {
+            self.frames.reverse();
+        }
+    }
+
+    /// Adds the frames of another tag to this one, converting them to this
+    /// tag's version first if needed. Frames which conflict with existing ones
+    /// are handled according to the policy; other frames are always added.
+    ///
+    /// # Example
+    /// ```
+    /// use id3::id3v2::{self, MergePolicy};
+    /// use id3::id3v2::frame::Id;
+    ///
+    /// let mut tag = id3v2::Tag::new();
+    /// tag.add_text_frame(Id::V4(*b"TIT2"), "title");
+    /// let mut other = id3v2::Tag::new();
+    /// other.add_text_frame(Id::V4(*b"TIT2"), "other title");
+    /// other.add_text_frame(Id::V4(*b"TALB"), "album");
+    ///
+    /// tag.merge(other, MergePolicy::KeepExisting);
+    /// assert_eq!(tag.frames.len(), 2);
+    /// ```
+    pub fn merge(&mut self, other: Tag, policy: MergePolicy) {
+        let mut other = other;
+        other.convert_version(self.version);
+
+        // IDs are compared by name, as ID3v2.3 and ID3v2.4 frames may be mixed
+        let key = |frame: &Frame| uniqueness_key(frame).map(|(id, key)| (id.name().to_vec(), key));
+        match policy {
+            MergePolicy::KeepExisting => {
+                let existing: Vec<_> = self.frames.iter().filter_map(|frame| key(frame)).collect();
+                self.frames.extend(other.frames.into_iter().filter(|frame| match key(frame) {
+                    Some(ref key) => !existing.contains(key),
+                    None => true,
+                }));
+            },
+            MergePolicy::PreferOther => {
+                let replacing: Vec<_> = other.frames.iter().filter_map(|frame| key(frame)).collect();
+                self.frames.retain(|frame| match key(frame) {
+                    Some(ref key) => !replacing.contains(key),
+                    None => true,
+                });
+                self.frames.extend(other.frames);
+            },
+            MergePolicy::AppendAll => self.frames.extend(other.frames),
+        }
+    }
+
+    /// Sets whether the frames will be written in the canonical order of
+    /// `sort_frames`, without changing their order in the tag itself.
+    pub fn set_sort_frames_on_write(&mut self, sort: bool) {
+        self.sort_frames_on_write = sort;
+    }
+
+    /// Returns the decoded contents of the first EQU2 (equalisation) frame, or
+    /// `None` if there is no such frame or it is malformed.
+    ///
+    /// # Example
+    /// ```
+    /// use id3::id3v2;
+    /// use id3::id3v2::frame::{Frame, Field, Id, Interpolation};
+    ///
+    /// let mut tag = id3v2::Tag::new();
+    /// let mut frame = Frame::new(Id::V4(*b"EQU2"));
+    /// frame.fields = vec![Field::Int8(1), Field::Latin1(b"rock".to_vec()),
+    ///     Field::BinaryData(vec![0x03, 0xE8, 0xFE, 0x00])];
+    /// tag.add_frame(frame);
+    ///
+    /// let eq = tag.equalization().unwrap();
+    /// assert_eq!(eq.interpolation, Interpolation::Linear);
+    /// assert_eq!(eq.points[0].frequency_hz(), 500.0);
+    /// assert_eq!(eq.points[0].adjustment_db(), -1.0);
+    /// ```
+    pub fn equalization(&self) -> Option<frame::Equalization> {
+        let frame = match self.get_frame_by_id(frame::Id::V4(*b"EQU2")) {
+            Some(frame) => frame,
+            None => return None
+        };
+        match &*frame.fields {
+            &[Field::Int8(interpolation), Field::Latin1(ref identification), Field::BinaryData(ref data)] => {
+                let interpolation = match frame::Interpolation::from_u8(interpolation) {
+                    Some(interpolation) => interpolation,
+                    None => return None
+                };
+                frame::Equalization::parse_points(&*data).map(|points| frame::Equalization {
+                    interpolation: interpolation,
+                    identification: identification.clone(),
+                    points: points,
+                })
+            },
+            _ => None
+        }
+    }
+
+    /// Returns the play counter (PCNT), the number of times the file has been
+    /// played, if the tag has one.
+    ///
+    /// # Example
+    /// ```
+    /// use id3::id3v2;
+    ///
+    /// let mut tag = id3v2::Tag::new();
+    /// assert!(tag.play_count().is_none());
+    ///
+    /// tag.increment_play_count();
+    /// assert_eq!(tag.play_count().unwrap().to_string(), "1");
+    /// ```
+    pub fn play_count(&self) -> Option<BigNum> {
+        match self.get_frame_by_id(self.version().play_count_id()) {
+            Some(frame) => match &*frame.fields {
+                &[Field::Int32Plus(ref count)] => Some(count.clone()),
+                _ => None
+            },
+            None => None
+        }
+    }
+
+    /// Increments the play counter (PCNT), creating it with a count of one if
+    /// the tag does not have a valid one.
+    pub fn increment_play_count(&mut self) {
+        let id = self.version().play_count_id();
+        let incremented = match self.get_frame_by_id_mut(id) {
+            Some(frame) => match &mut *frame.fields {
+                &mut [Field::Int32Plus(ref mut count)] => {
+                    count.incr();
+                    true
+                },
+                _ => false
+            },
+            None => false
+        };
+        if incremented {
+            return;
+        }
+
+        self.remove_frames_by_id(id);
+        let mut count = BigNum::new(vec![]);
+        count.incr();
+        let mut frame = Frame::new(id);
+        frame.fields = vec![Field::Int32Plus(count)];
+        self.frames.push(frame);
+    }
+
+    /// Returns the popularimeter (POPM) belonging to the given email address,
+    /// if the tag has one.
+    ///
+    /// # Example
+    /// ```
+    /// use id3::id3v2;
+    /// use id3::id3v2::frame::Popularimeter;
+    /// use id3::id3v2::frame::field::BigNum;
+    ///
+    /// let mut tag = id3v2::Tag::new();
+    /// tag.set_popularimeter(Popularimeter {
+    ///     email: "user@example.com".to_owned(),
+    ///     rating: 255,
+    ///     counter: BigNum::new(vec![]),
+    /// });
+    ///
+    /// assert_eq!(tag.popularimeter("user@example.com").unwrap().rating, 255);
+    /// assert!(tag.popularimeter("other@example.com").is_none());
+    /// ```
+    pub fn popularimeter(&self, email: &str) -> Option<frame::Popularimeter> {
+        let id = self.version().popularimeter_id();
+        self.get_frames_by_id(id).into_iter()
+            .filter_map(|frame| frame::Popularimeter::from_fields(&*frame.fields))
+            .find(|popm| popm.email == email)
+    }
+
+    /// Adds a popularimeter (POPM) frame, replacing any existing one with the
+    /// same email address.
+    pub fn set_popularimeter(&mut self, popularimeter: frame::Popularimeter) {
+        let id = self.version().popularimeter_id();
+        self.frames.retain(|frame| {
+            frame.id != id || match frame::Popularimeter::from_fields(&*frame.fields) {
+                Some(popm) => popm.email != popularimeter.email,
+                None => true
+            }
+        });
+
+        let mut frame = Frame::new(id);
+        frame.fields = popularimeter.to_fields();
+        self.frames.push(frame);
+    }
+
+    /// Returns the ownership (OWNE) frame's purchase information, if the tag
+    /// has one. ID3v2.2 has no ownership frame.
+    ///
+    /// # Example
+    /// ```
+    /// use id3::id3v2;
+    /// use id3::id3v2::frame::{Ownership, Encoding};
+    ///
+    /// let mut tag = id3v2::Tag::new();
+    /// let ownership = Ownership {
+    ///     price: "USD0.99".to_owned(),
+    ///     date: "20140321".to_owned(),
+    ///     seller: "Record Store".to_owned(),
+    /// };
+    /// assert!(tag.set_ownership(ownership.clone(), Encoding::UTF8));
+    /// assert_eq!(tag.ownership(), Some(ownership));
+    /// ```
+    pub fn ownership(&self) -> Option<frame::Ownership> {
+        self.v34_only_id(*b"OWNE")
+            .and_then(|id| self.get_frame_by_id(id))
+            .and_then(|frame| frame::Ownership::from_fields(&*frame.fields))
+    }
+
+    /// Sets the ownership (OWNE) frame, with the seller in the specified
+    /// encoding, replacing any existing one. Returns `false`, leaving the tag
+    /// unchanged, if the date of purchase is not eight digits (`YYYYMMDD`) or
+    /// the tag is ID3v2.2, which has no ownership frame.
+    pub fn set_ownership(&mut self, ownership: frame::Ownership, encoding: Encoding) -> bool {
+        let id = match self.v34_only_id(*b"OWNE") {
+            Some(id) => id,
+            None => return false
+        };
+        if !ownership.is_valid_date() {
+            return false;
+        }
+        self.remove_frames_by_id(id);
+
+        let mut frame = Frame::new(id);
+        frame.fields = ownership.to_fields(encoding);
+        self.frames.push(frame);
+        true
+    }
+
+    /// Returns the decoded contents of each commercial (COMR) frame in the
+    /// tag. ID3v2.2 has no commercial frame.
+    ///
+    /// # Example
+    /// ```
+    /// use id3::id3v2;
+    /// use id3::id3v2::frame::{Commercial, Encoding};
+    ///
+    /// let mut tag = id3v2::Tag::new();
+    /// tag.add_commercial_frame(Commercial {
+    ///     price: "USD9.99".to_owned(),
+    ///     valid_until: "20151231".to_owned(),
+    ///     contact_url: "http://example.com/".to_owned(),
+    ///     received_as: 2,
+    ///     seller: "Record Store".to_owned(),
+    ///     description: "The album".to_owned(),
+    ///     mime_type: String::new(),
+    ///     logo: vec![],
+    /// }, Encoding::UTF8);
+    ///
+    /// assert_eq!(tag.commercial_frames()[0].price, "USD9.99");
+    /// ```
+    pub fn commercial_frames(&self) -> Vec<frame::Commercial> {
+        let id = match self.v34_only_id(*b"COMR") {
+            Some(id) => id,
+            None => return Vec::new()
+        };
+        self.get_frames_by_id(id).into_iter()
+            .filter_map(|frame| frame::Commercial::from_fields(&*frame.fields))
+            .collect()
+    }
+
+    /// Adds a commercial (COMR) frame holding the offer, with the seller and
+    /// description in the specified encoding. A tag may hold several offers,
+    /// but not identical ones, so an identical existing offer is replaced.
+    /// Returns `false`, leaving the tag unchanged, if the tag is ID3v2.2,
+    /// which has no commercial frame.
+    pub fn add_commercial_frame(&mut self, commercial: frame::Commercial, encoding: Encoding) -> bool {
+        let id = match self.v34_only_id(*b"COMR") {
+            Some(id) => id,
+            None => return false
+        };
+        self.frames.retain(|frame| {
+            frame.id != id || frame::Commercial::from_fields(&*frame.fields).as_ref() != Some(&commercial)
+        });
+
+        let mut frame = Frame::new(id);
+        frame.fields = commercial.to_fields(encoding);
+        self.frames.push(frame);
+        true
+    }
+
+    /// Returns the recording time (TDRC) of an ID3v2.4 tag, or `None` if there
+    /// is no such frame or its timestamp is malformed.
+    ///
+    /// # Example
+    /// ```
+    /// use id3::id3v2;
+    /// use id3::id3v2::frame::Id;
+    ///
+    /// let mut tag = id3v2::Tag::new();
+    /// tag.add_text_frame(Id::V4(*b"TDRC"), "2014-03-21");
+    /// assert_eq!(tag.recording_time().unwrap().day, Some(21));
+    /// ```
+    pub fn recording_time(&self) -> Option<Timestamp> {
+        if self.version != Version::V4 {
+            return None;
+        }
+        self.text_frame_str(frame::Id::V4(*b"TDRC")).and_then(|text| Timestamp::parse(text.trim()))
+    }
+
+    /// Returns the decoded contents of each synchronised lyrics (SYLT) frame
+    /// in the tag.
+    ///
+    /// # Example
+    /// ```
+    /// use id3::id3v2;
+    /// use id3::id3v2::frame::{SyncedLyrics, TimestampFormat, Encoding};
+    ///
+    /// let mut tag = id3v2::Tag::new();
+    /// tag.set_synced_lyrics(SyncedLyrics {
+    ///     lang: "eng".to_owned(),
+    ///     timestamp_format: TimestampFormat::Milliseconds,
+    ///     content_type: 1,
+    ///     description: String::new(),
+    ///     content: vec![("first line".to_owned(), 0), ("second line".to_owned(), 2500)],
+    /// }, Encoding::UTF8);
+    ///
+    /// assert_eq!(tag.synced_lyrics()[0].content[1], ("second line".to_owned(), 2500));
+    /// ```
+    pub fn synced_lyrics(&self) -> Vec<frame::SyncedLyrics> {
+        let id = self.version().synced_lyrics_id();
+        self.get_frames_by_id(id).into_iter()
+            .filter_map(|frame| frame::SyncedLyrics::from_fields(&*frame.fields))
+            .collect()
+    }
+
+    /// Adds a synchronised lyrics (SYLT) frame holding the given lyrics, with
+    /// text in the specified encoding, replacing any existing one with the
+    /// same language and description.
+    pub fn set_synced_lyrics(&mut self, lyrics: frame::SyncedLyrics, encoding: Encoding) {
+        let id = self.version().synced_lyrics_id();
+        self.frames.retain(|frame| {
+            frame.id != id || match frame::SyncedLyrics::from_fields(&*frame.fields) {
+                Some(sylt) => sylt.lang != lyrics.lang || sylt.description != lyrics.description,
+                None => true
+            }
+        });
+
+        let mut frame = Frame::new(id);
+        frame.fields = lyrics.to_fields(encoding);
+        self.frames.push(frame);
+    }
+
+    /// Returns the decoded contents of the event timing codes (ETCO) frame, if
+    /// the tag has one.
+    ///
+    /// # Example
+    /// ```
+    /// use id3::id3v2;
+    /// use id3::id3v2::frame::{EventTimingCodes, TimestampFormat};
+    ///
+    /// let mut tag = id3v2::Tag::new();
+    /// tag.set_event_timing_codes(EventTimingCodes {
+    ///     timestamp_format: TimestampFormat::Milliseconds,
+    ///     events: vec![(0x02, 1500)],
+    /// });
+    ///
+    /// assert_eq!(tag.event_timing_codes().unwrap().events, vec![(0x02, 1500)]);
+    /// ```
+    pub fn event_timing_codes(&self) -> Option<frame::EventTimingCodes> {
+        let id = self.version().event_timing_codes_id();
+        self.get_frame_by_id(id).and_then(|frame| frame::EventTimingCodes::from_fields(&*frame.fields))
+    }
+
+    /// Sets the event timing codes (ETCO), replacing any existing ETCO frame.
+    pub fn set_event_timing_codes(&mut self, codes: frame::EventTimingCodes) {
+        let id = self.version().event_timing_codes_id();
+        self.remove_frames_by_id(id);
+        let mut frame = Frame::new(id);
+        frame.fields = codes.to_fields();
+        self.frames.push(frame);
+    }
+
+    /// Returns the decoded contents of each chapter (CHAP) frame in the tag.
+    ///
+    /// # Example
+    /// ```
+    /// use id3::id3v2;
+    /// use id3::id3v2::frame::{Chapter, Frame, Id, Encoding};
+    ///
+    /// let mut tag = id3v2::Tag::new();
+    /// tag.set_chapter(Chapter {
+    ///     element_id: "chp0".to_owned(),
+    ///     start_time: 0,
+    ///     end_time: 60000,
+    ///     start_offset: 0xFFFFFFFF,
+    ///     end_offset: 0xFFFFFFFF,
+    ///     frames: vec![Frame::new_text_frame(Id::V4(*b"TIT2"), "Introduction", Encoding::UTF8).unwrap()],
+    /// });
+    ///
+    /// assert_eq!(tag.chapters()[0].element_id, "chp0");
+    /// ```
+    pub fn chapters(&self) -> Vec<frame::Chapter> {
+        let version = self.version();
+        match self.v34_only_id(*b"CHAP") {
+            Some(id) => self.get_frames_by_id(id).into_iter()
+                .filter_map(|frame| frame::Chapter::from_fields(&*frame.fields, version))
+                .collect(),
+            None => vec![]
+        }
+    }
+
+    /// Adds a chapter (CHAP) frame, replacing any existing chapter with the
+    /// same element ID. The chapter's frames must have the tag's version.
+    /// Returns `false` if the tag's version does not support chapters
+    /// (ID3v2.2).
+    pub fn set_chapter(&mut self, chapter: frame::Chapter) -> bool {
+        let id = match self.v34_only_id(*b"CHAP") {
+            Some(id) => id,
+            None => return false
+        };
+        self.frames.retain(|frame| {
+            frame.id != id || match frame.fields.first() {
+                Some(&Field::Latin1(ref element_id)) => *element_id != util::string_to_latin1(&*chapter.element_id),
+                _ => true
+            }
+        });
+
+        let mut frame = Frame::new(id);
+        frame.fields = chapter.to_fields();
+        self.frames.push(frame);
+        true
+    }
+
+    /// Returns the decoded contents of each table of contents (CTOC) frame in
+    /// the tag.
+    pub fn tables_of_contents(&self) -> Vec<frame::TableOfContents> {
+        let version = self.version();
+        match self.v34_only_id(*b"CTOC") {
+            Some(id) => self.get_frames_by_id(id).into_iter()
+                .filter_map(|frame| frame::TableOfContents::from_fields(&*frame.fields, version))
+                .collect(),
+            None => vec![]
+        }
+    }
+
+    /// Adds a table of contents (CTOC) frame, replacing any existing table of
+    /// contents with the same element ID. The table's frames must have the
+    /// tag's version. Returns `false` if the tag's version does not support
+    /// tables of contents (ID3v2.2).
+    pub fn set_table_of_contents(&mut self, toc: frame::TableOfContents) -> bool {
+        let id = match self.v34_only_id(*b"CTOC") {
+            Some(id) => id,
+            None => return false
+        };
+        self.frames.retain(|frame| {
+            frame.id != id || match frame.fields.first() {
+                Some(&Field::Latin1(ref element_id)) => *element_id != util::string_to_latin1(&*toc.element_id),
+                _ => true
+            }
+        });
+
+        let mut frame = Frame::new(id);
+        frame.fields = toc.to_fields();
+        self.frames.push(frame);
+        true
+    }
+
+    /// Returns the ID with the given name for the tag's version, if the frame
+    /// exists only in ID3v2.3 and ID3v2.4.
+    fn v34_only_id(&self, name: [u8; 4]) -> Option<frame::Id> {
+        match self.version() {
+            Version::V2 => None,
+            Version::V3 => Some(Id::V3(name)),
+            Version::V4 => Some(Id::V4(name)),
+        }
+    }
+
+    /// Returns the owner and identifier of each unique file identifier (UFID)
+    /// frame in the tag.
+    ///
+    /// # Example
+    /// ```
+    /// use id3::id3v2;
+    ///
+    /// let mut tag = id3v2::Tag::new();
+    /// tag.set_unique_file_id("http://musicbrainz.org", b"0123-4567");
+    ///
+    /// assert_eq!(tag.unique_file_ids(), vec![("http://musicbrainz.org".to_owned(), b"0123-4567".to_vec())]);
+    /// ```
+    pub fn unique_file_ids(&self) -> Vec<(String, Vec<u8>)> {
+        let mut ids = Vec::new();
+        for frame in self.get_frames_by_id(self.version().unique_file_id_id()) {
+            if let &[Field::Latin1(ref owner), Field::BinaryData(ref id)] = &*frame.fields {
+                if let Some(owner) = util::string_from_latin1(&*owner) {
+                    ids.push((owner, id.clone()));
+                }
+            }
+        }
+        ids
+    }
+
+    /// Adds a unique file identifier (UFID) frame, replacing any existing one
+    /// with the same owner.
+    pub fn set_unique_file_id(&mut self, owner: &str, id: &[u8]) {
+        let frame_id = self.version().unique_file_id_id();
+        let owner_bytes = util::string_to_latin1(owner);
+        self.frames.retain(|frame| {
+            frame.id != frame_id || match &*frame.fields {
+                &[Field::Latin1(ref frame_owner), _] => *frame_owner != owner_bytes,
+                _ => true
+            }
+        });
+
+        let mut frame = Frame::new(frame_id);
+        frame.fields = vec![Field::Latin1(owner_bytes), Field::BinaryData(id.to_vec())];
+        self.frames.push(frame);
+    }
+
+    /// Returns the symbol, owner, and group dependent data of each group
+    /// identification registration (GRID) frame in the tag, which declare the
+    /// owners of the group symbols used by grouped frames.
+    ///
+    /// # Example
+    /// ```
+    /// use id3::id3v2;
+    ///
+    /// let mut tag = id3v2::Tag::new();
+    /// assert!(tag.register_group(0x80, "http://example.com", &[]));
+    ///
+    /// assert_eq!(tag.group_registrations(), vec![(0x80, "http://example.com".to_owned(), vec![])]);
+    /// ```
+    pub fn group_registrations(&self) -> Vec<(u8, String, Vec<u8>)> {
+        let id = match self.v34_only_id(*b"GRID") {
+            Some(id) => id,
+            None => return Vec::new()
+        };
+
+        let mut registrations = Vec::new();
+        for frame in self.get_frames_by_id(id) {
+            if let &[Field::Latin1(ref owner), Field::Int8(symbol), Field::BinaryData(ref data)] = &*frame.fields {
+                if let Some(owner) = util::string_from_latin1(&*owner) {
+                    registrations.push((symbol, owner, data.clone()));
+                }
+            }
+        }
+        registrations
+    }
+
+    /// Adds a group identification registration (GRID) frame declaring the
+    /// owner of a group symbol, replacing any existing registration of the
+    /// same symbol. Returns `false` if the tag's version does not support
+    /// grouping (ID3v2.2).
+    pub fn register_group(&mut self, symbol: u8, owner: &str, data: &[u8]) -> bool {
+        let id = match self.v34_only_id(*b"GRID") {
+            Some(id) => id,
+            None => return false
+        };
+
+        self.frames.retain(|frame| {
+            frame.id != id || match &*frame.fields {
+                &[_, Field::Int8(frame_symbol), _] => frame_symbol != symbol,
+                _ => true
+            }
+        });
+
+        let mut frame = Frame::new(id);
+        frame.fields = vec![Field::Latin1(util::string_to_latin1(owner)), Field::Int8(symbol), Field::BinaryData(data.to_vec())];
+        self.frames.push(frame);
+        true
+    }
+
+    /// Returns the restrictions recorded in the tag's extended header, if any.
+    ///
+    /// # Example
+    /// ```
+    /// use id3::id3v2::{self, TagRestrictions};
+    ///
+    /// let mut tag = id3v2::Tag::new();
+    /// assert!(tag.restrictions().is_none());
+    ///
+    /// let restrictions = TagRestrictions::from_byte(0x24);
+    /// assert!(tag.set_restrictions(Some(restrictions)));
+    /// assert_eq!(tag.restrictions(), Some(restrictions));
+    /// ```
+    pub fn restrictions(&self) -> Option<TagRestrictions> {
+        let extended = match self.extended_header {
+            Some(ref extended) => extended,
+            None => return None
+        };
+        for &(ref flag, ref data) in &extended.flag_data {
+            if let (&ExtendedFlag::TagRestrictions, &[byte]) = (flag, &**data) {
+                return Some(TagRestrictions::from_byte(byte));
+            }
+        }
+        None
+    }
+
+    /// Sets or, given `None`, removes the restrictions recorded in the tag's
+    /// extended header, creating or dropping the extended header as needed.
+    ///
+    /// Returns FALSE and does nothing if the tag's version (before ID3v2.4)
+    /// cannot record restrictions.
+    pub fn set_restrictions(&mut self, restrictions: Option<TagRestrictions>) -> bool {
+        if self.version() < Version::V4 {
+            return false;
+        }
+
+        let mut extended = self.extended_header.take().unwrap_or(ExtendedHeader { flag_data: vec![], padding_size: 0 });
+        extended.flag_data.retain(|&(ref flag, _)| *flag != ExtendedFlag::TagRestrictions);
+        if let Some(restrictions) = restrictions {
+            extended.flag_data.push((ExtendedFlag::TagRestrictions, vec![restrictions.to_byte()]));
+        }
+
+        let present = !extended.flag_data.is_empty();
+        self.flags.set(TagFlag::ExtendedHeader, present);
+        if present {
+            self.extended_header = Some(extended);
+        }
+        true
+    }
+
+    /// Returns the content of the first text frame with the specified identifier
+    /// like `text_frame_text`, but borrowed from the frame rather than copied
+    /// when it is stored as UTF-8 or as ASCII-only Latin-1. Several values of an
+    /// ID3v2.4 frame are joined with slashes.
+    ///
+    /// # Example
+    /// ```
+    /// use std::borrow::Cow;
+    /// use id3::id3v2;
+    /// use id3::id3v2::frame::{Id, Encoding};
+    ///
+    /// let mut tag = id3v2::Tag::new();
+    /// tag.add_text_frame_enc(Id::V4(*b"TALB"), "album", Encoding::UTF8);
+    /// tag.add_text_frame_enc(Id::V4(*b"TIT2"), "title", Encoding::UTF16);
     ///
-    /// tag.remove_frames_by_id(Id::V4(*b"USLT"));
-    /// assert_eq!(tag.get_frames().len(), 0);
+    /// assert_eq!(tag.text_frame_str(Id::V4(*b"TALB")), Some(Cow::Borrowed("album")));
+    /// assert_eq!(tag.text_frame_str(Id::V4(*b"TIT2")), Some(Cow::Owned("title".to_owned())));
     /// ```
-    pub fn remove_frames_by_id(&mut self, id: frame::Id) {
-        self.frames.retain(|frame| {
-            frame.id != id
-        });
+    pub fn text_frame_str(&self, id: frame::Id) -> Option<Cow<str>> {
+        match self.get_frame_by_id(id) {
+            Some(frame) => match &*frame.fields {
+                &[Field::TextEncoding(encoding), Field::String(ref text)] => text_str(encoding, &*text),
+                &[Field::TextEncoding(encoding), Field::StringList(ref texts)] => match &**texts {
+                    &[ref text] => text_str(encoding, &*text),
+                    _ => texts.iter()
+                        .map(|text| text_str(encoding, &*text))
+                        .collect::<Option<Vec<_>>>()
+                        .map(|texts| Cow::Owned(texts.join("/"))),
+                },
+                _ => None
+            },
+            None => None
+        }
     }
 
     /// Returns the content of the first text frame with the specified identifier,
@@ -801,10 +2461,1346 @@ impl Tag {
     pub fn text_frame_text(&self, id: frame::Id) -> Option<String> {
         match self.get_frame_by_id(id) {
             Some(frame) => match &*frame.fields {
-                &[Field::TextEncoding(encoding), Field::String(ref text)] => util::string_from_encoding(encoding, &text),
+                &[Field::TextEncoding(encoding), Field::String(ref text)] => util::string_from_encoding(encoding, &text).map(util::clean_decoded),
                 _ => None
             },
             None => None
         }
     }
+
+    /// Returns every value of the first text frame with the specified
+    /// identifier, converted to UTF8, or `None` if the frame with the
+    /// specified ID does not exist or does not have textual content. ID3v2.4
+    /// text frames may hold several null-separated values.
+    ///
+    /// # Example
+    /// ```
+    /// use id3::id3v2;
+    /// use id3::id3v2::frame::{Id, Encoding};
+    ///
+    /// let id = Id::V4(*b"TPE1");
+    /// let mut tag = id3v2::Tag::new();
+    /// tag.set_text_frame_values(id, &["first", "second"], Encoding::UTF8);
+    /// assert_eq!(tag.text_frame_values(id).unwrap(), vec!["first", "second"]);
+    /// ```
+    pub fn text_frame_values(&self, id: frame::Id) -> Option<Vec<String>> {
+        let frame = match self.get_frame_by_id(id) {
+            Some(frame) => frame,
+            None => return None
+        };
+        let decoded = match &*frame.fields {
+            &[Field::TextEncoding(encoding), Field::String(ref text)] => util::string_from_encoding(encoding, &text).map(|text| vec![text]),
+            &[Field::TextEncoding(encoding), Field::StringList(ref texts)] => texts.iter()
+                .map(|text| util::string_from_encoding(encoding, &text))
+                .collect(),
+            _ => None
+        };
+        decoded.map(|texts| texts.iter()
+            .flat_map(|text| text.split('\0'))
+            .map(|value| util::clean_decoded(value.to_owned()))
+            .filter(|value| !value.is_empty())
+            .collect())
+    }
+
+    /// Sets the values of the text frame with the specified identifier,
+    /// replacing any frames with that ID. ID3v2.4 frames store the values
+    /// separately; earlier versions have no way to do so and store them
+    /// separated by slashes.
+    ///
+    /// Returns false without modifying the tag if the ID is not that of a
+    /// text frame or if the encoding is not supported by its version.
+    pub fn set_text_frame_values(&mut self, id: frame::Id, values: &[&str], encoding: Encoding) -> bool {
+        if !id.is_text() || !id.version().encoding_compatible(encoding) {
+            return false
+        }
+        let encoded = match id.version() {
+            Version::V4 => values.iter().map(|value| util::encode_string(value, encoding)).collect(),
+            _ => vec![util::encode_string(&*values.join("/"), encoding)],
+        };
+        self.remove_frames_by_id(id);
+        let mut frame = Frame::new(id);
+        frame.fields = vec![Field::TextEncoding(encoding), Field::StringList(encoded)];
+        self.frames.push(frame);
+        true
+    }
+
+    /// Returns the names of the genres listed in the genre frame (TCO/TCON).
+    ///
+    /// References to ID3v1 genre numbers, whether in the legacy `(13)` syntax
+    /// or as the plain numbers used by ID3v2.4, are expanded to the genre names,
+    /// as are the special `RX` (remix) and `CR` (cover) tokens. Refinement text
+    /// following the references is listed after them, unless it repeats the
+    /// last genre name. A leading `((` is an escaped parenthesis, so the text
+    /// following it is taken literally. Null-separated genres (ID3v2.4) are
+    /// each expanded in turn.
+    ///
+    /// # Example
+    /// ```
+    /// use id3::id3v2;
+    ///
+    /// let mut tag = id3v2::Tag::new();
+    /// let id = tag.version().genre_id();
+    /// tag.add_text_frame(id, "(9)(RX)Nu Metal");
+    /// assert_eq!(tag.genre_names(), vec!["Metal", "Remix", "Nu Metal"]);
+    /// ```
+    pub fn genre_names(&self) -> Vec<String> {
+        let frame = match self.get_frame_by_id(self.version().genre_id()) {
+            Some(frame) => frame,
+            None => return vec![]
+        };
+        let encoded: Vec<&Vec<u8>> = match &*frame.fields {
+            &[Field::TextEncoding(_), Field::String(ref text)] => vec![text],
+            &[Field::TextEncoding(_), Field::StringList(ref texts)] => texts.iter().collect(),
+            _ => return vec![]
+        };
+        let encoding = frame.encoding().unwrap();
+
+        let mut names = vec![];
+        for text in encoded.into_iter().filter_map(|text| util::string_from_encoding(encoding, &*text)) {
+            for value in text.split('\0').filter(|value| !value.is_empty()) {
+                expand_genre(value.trim_matches('\u{FEFF}'), &mut names);
+            }
+        }
+        names
+    }
+
+    /// Sets the genre (TCON) to a reference to one of the ID3v1 genres: `(n)`
+    /// for ID3v2.2 and ID3v2.3, or the bare number `n` for ID3v2.4, which
+    /// drops the parentheses.
+    ///
+    /// # Example
+    /// ```
+    /// use id3::{id3v2, Genre};
+    ///
+    /// let mut tag = id3v2::Tag::with_version(id3v2::Version::V3);
+    /// tag.set_genre_code(Genre::Metal);
+    /// assert_eq!(tag.genre_names(), vec!["Metal"]);
+    /// ```
+    pub fn set_genre_code(&mut self, genre: Genre) {
+        let text = match self.version() {
+            Version::V4 => genre.to_code().to_string(),
+            _ => format!("({})", genre.to_code()),
+        };
+        let id = self.version().genre_id();
+        self.add_text_frame_enc(id, &*text, Encoding::Latin1);
+    }
+}
+
+/// Returns the ID of a frame along with the content which distinguishes it
+/// from other frames with the same ID, or `None` if duplicates are allowed.
+fn uniqueness_key(frame: &Frame) -> Option<(frame::Id, Vec<u8>)> {
+    let key = match frame.id.name() {
+        b"TXX" | b"TXXX" | b"WXX" | b"WXXX" => match frame.description_text() {
+            Some(description) => description.into_bytes(),
+            None => return None
+        },
+        b"COM" | b"COMM" | b"ULT" | b"USLT" => match (frame.fields.get(1), frame.description_text()) {
+            (Some(&Field::Language(lang)), Some(description)) => {
+                let mut key = lang.to_vec();
+                key.extend(description.into_bytes());
+                key
+            },
+            _ => return None
+        },
+        b"PIC" | b"APIC" => match frame.fields.get(2) {
+            Some(&Field::Int8(picture_type)) => vec![picture_type],
+            _ => return None
+        },
+        b"UFI" | b"UFID" | b"POP" | b"POPM" => match frame.fields.get(0) {
+            Some(&Field::Latin1(ref owner)) => owner.clone(),
+            _ => return None
+        },
+        b"CNT" | b"PCNT" => vec![],
+        _ if frame.id.is_text() => vec![],
+        _ => return None
+    };
+    Some((frame.id, key))
+}
+
+/// Decodes text of the given encoding, borrowing it if it is already valid
+/// UTF-8 which needs no cleaning up.
+fn text_str(encoding: Encoding, text: &[u8]) -> Option<Cow<str>> {
+    let borrowed = match encoding {
+        Encoding::UTF8 => str::from_utf8(text).ok(),
+        Encoding::Latin1 if text.iter().all(|&b| b < 0x80) => str::from_utf8(text).ok(),
+        _ => None,
+    };
+    match borrowed {
+        Some(text) if !text.contains('\u{FEFF}') => Some(Cow::Borrowed(text.trim_right_matches('\0'))),
+        _ => util::string_from_encoding(encoding, text).map(util::clean_decoded).map(Cow::Owned),
+    }
+}
+
+/// Returns the position of a frame's class in the canonical frame order.
+fn frame_order_class(id: frame::Id) -> u8 {
+    let name = id.name();
+    if name == b"APIC" || name == b"PIC" {
+        3
+    } else if name[0] == b'T' {
+        0
+    } else if name[0] == b'W' {
+        1
+    } else {
+        2
+    }
+}
+
+/// Lists the tag's frames one per line, each as its ID, a description of its
+/// type, and a best-effort rendering of its content.
+///
+/// # Example
+/// ```
+/// use id3::id3v2;
+/// use id3::id3v2::frame::Id;
+///
+/// let mut tag = id3v2::Tag::new();
+/// tag.add_text_frame(Id::V4(*b"TIT2"), "title");
+/// assert_eq!(tag.to_string(), "TIT2 (Title/songname/content description): title\n");
+/// ```
+impl fmt::Display for Tag {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for frame in &self.frames {
+            let encoding = frame.encoding();
+            let values: Vec<String> = frame.fields.iter().filter_map(|field| field_summary(field, encoding)).collect();
+            try!(writeln!(f, "{} ({}): {}", String::from_utf8_lossy(frame.id.name()), frame.description(), values.join(", ")));
+        }
+        Ok(())
+    }
+}
+
+/// Renders a field's content for display, decoding strings with the frame's
+/// encoding and summarizing binary data. Returns `None` for the text encoding
+/// field, whose effect is already seen in the decoded strings.
+fn field_summary(field: &Field, encoding: Option<Encoding>) -> Option<String> {
+    let decode = |text: &Vec<u8>| util::string_from_encoding(encoding.unwrap_or(Encoding::Latin1), &*text)
+        .map(util::clean_decoded)
+        .unwrap_or_else(|| "<undecodable>".to_owned());
+    let summary = match *field {
+        Field::TextEncoding(_) => return None,
+        Field::String(ref text) | Field::StringFull(ref text) => decode(text),
+        Field::StringList(ref texts) => texts.iter().map(decode).collect::<Vec<_>>().join("/"),
+        Field::Latin1(ref text) | Field::Latin1Full(ref text) => util::string_from_latin1(&*text).unwrap(),
+        Field::Latin1List(ref texts) => texts.iter().filter_map(|text| util::string_from_latin1(&*text)).collect::<Vec<_>>().join("/"),
+        Field::Language(ref lang) => String::from_utf8_lossy(lang).into_owned(),
+        Field::FrameIdV2(ref id) => String::from_utf8_lossy(id).into_owned(),
+        Field::FrameIdV34(ref id) => String::from_utf8_lossy(id).into_owned(),
+        Field::Int8(..) | Field::Int16(..) | Field::Int24(..) | Field::Int32(..) => field.as_u32().unwrap().to_string(),
+        Field::Int32Plus(ref n) => n.to_string(),
+        Field::BinaryData(ref data) => {
+            let hex: Vec<String> = data.iter().take(16).map(|byte| format!("{:02X}", byte)).collect();
+            format!("<{} bytes: {}{}>", data.len(), hex.join(" "), if data.len() > 16 { " ..." } else { "" })
+        },
+    };
+    Some(summary)
+}
+
+impl IntoIterator for Tag {
+    type Item = Frame;
+    type IntoIter = vec::IntoIter<Frame>;
+
+    fn into_iter(self) -> vec::IntoIter<Frame> {
+        self.frames.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Tag {
+    type Item = &'a Frame;
+    type IntoIter = slice::Iter<'a, Frame>;
+
+    fn into_iter(self) -> slice::Iter<'a, Frame> {
+        self.frames.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a mut Tag {
+    type Item = &'a mut Frame;
+    type IntoIter = slice::IterMut<'a, Frame>;
+
+    fn into_iter(self) -> slice::IterMut<'a, Frame> {
+        self.frames.iter_mut()
+    }
+}
+
+// Tests {{{
+#[cfg(test)]
+mod tests {
+    use id3v2::{Tag, TagFlag, TagRestrictions, ExtendedHeader, ExtendedFlag, Version, ParseOptions, read_tag, read_tag_with};
+    use id3v2::Version::*;
+    use id3v2::frame::{Frame, Field, Encoding, Id, Popularimeter, Ownership, Commercial};
+    use id3v2::frame::field::BigNum;
+    use id3v2::byteorder::{BigEndian, ByteOrder};
+    use std::io::{self, Read};
+    use util;
+
+    #[test]
+    fn test_extended_header_round_trip_v3() {
+        let bytes = [0, 0, 0, 10, 0x80, 0x00, 0, 0, 0x08, 0x00, 0xDE, 0xAD, 0xBE, 0xEF];
+        let (header, len) = ExtendedHeader::parse(&mut &bytes[..], V3).unwrap();
+        assert_eq!(len, bytes.len());
+        assert_eq!(header.flag_data, vec![(ExtendedFlag::Crc, vec![0xDE, 0xAD, 0xBE, 0xEF])]);
+        assert_eq!(header.padding_size, 0x800);
+
+        let mut written = vec![];
+        assert_eq!(header.write_to(&mut written, V3).unwrap() as usize, bytes.len());
+        assert_eq!(&*written, &bytes[..]);
+        assert_eq!(ExtendedHeader::parse(&mut &*written, V3).unwrap(), (header, len));
+    }
+
+    #[test]
+    fn test_extended_header_round_trip_v4() {
+        let bytes = [0, 0, 0, 15, 0x01, 0x70, 0x00, 0x05, 0x01, 0x02, 0x03, 0x04, 0x05, 0x01, 0x42];
+        let (header, len) = ExtendedHeader::parse(&mut &bytes[..], V4).unwrap();
+        assert_eq!(len, bytes.len());
+        assert_eq!(header.flag_data, vec![
+            (ExtendedFlag::Update, vec![]),
+            (ExtendedFlag::Crc, vec![0x01, 0x02, 0x03, 0x04, 0x05]),
+            (ExtendedFlag::TagRestrictions, vec![0x42]),
+        ]);
+
+        let mut written = vec![];
+        assert_eq!(header.write_to(&mut written, V4).unwrap() as usize, bytes.len());
+        assert_eq!(&*written, &bytes[..]);
+        assert_eq!(ExtendedHeader::parse(&mut &*written, V4).unwrap(), (header, len));
+    }
+
+    /// Writes the tag without unsynchronization and reads it back. Shared
+    /// with the tests of the submodules.
+    pub fn round_trip(tag: &Tag) -> Tag {
+        let mut written = vec![];
+        tag.write_to(&mut written, false).unwrap();
+        read_tag(&mut &*written).unwrap().unwrap()
+    }
+
+    /// Returns a title frame laid out as text frames are when read.
+    fn title_frame(version: Version, encoding: Encoding, text: &[u8]) -> Frame {
+        let mut frame = Frame::new(version.title_id());
+        frame.fields = vec![Field::TextEncoding(encoding), Field::StringList(vec![text.to_vec()])];
+        frame
+    }
+
+    /// A reader which yields at most one byte per call, like a slow pipe.
+    struct OneByteReader<'a>(&'a [u8]);
+
+    impl<'a> Read for OneByteReader<'a> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if buf.is_empty() || self.0.is_empty() {
+                return Ok(0)
+            }
+            buf[0] = self.0[0];
+            self.0 = &self.0[1..];
+            Ok(1)
+        }
+    }
+
+    #[test]
+    fn test_one_byte_reads() {
+        for &version in &[V2, V3, V4] {
+            let mut tag = Tag::with_version(version);
+            tag.add_frame(title_frame(version, Encoding::UTF16, &*util::string_to_utf16("title")));
+            tag.add_frame(title_frame(version, Encoding::Latin1, b"another title"));
+            if version == V4 {
+                tag.set_restrictions(Some(TagRestrictions::from_byte(0)));
+                tag.frames[1].flags.compression = true;
+                tag.frames[1].flags.data_length_indicator = true;
+            }
+
+            let mut written = vec![];
+            tag.write_to(&mut written, false).unwrap();
+            let reread = read_tag(&mut OneByteReader(&*written)).unwrap().unwrap();
+            assert_eq!(reread.frames.len(), 2);
+            for (reread, frame) in reread.frames.iter().zip(tag.frames.iter()) {
+                assert_eq!(reread.fields_to_bytes(), frame.fields_to_bytes());
+            }
+        }
+
+        let mut written = vec![];
+        let mut tag = Tag::with_version(V4);
+        tag.add_frame(title_frame(V4, Encoding::UTF8, b"title"));
+        tag.write_to(&mut written, false).unwrap();
+        written.truncate(written.len() - 1);
+        assert!(read_tag(&mut OneByteReader(&*written)).is_err());
+    }
+
+    #[test]
+    fn test_play_count() {
+        for &version in &[V2, V3, V4] {
+            let mut tag = Tag::with_version(version);
+            assert!(tag.play_count().is_none());
+            tag.increment_play_count();
+            assert_eq!(tag.play_count().unwrap().to_string(), "1");
+            tag.increment_play_count();
+            assert_eq!(tag.play_count().unwrap().to_string(), "2");
+
+            let mut reread = round_trip(&tag);
+            assert_eq!(reread.play_count().unwrap().to_string(), "2");
+            reread.increment_play_count();
+            assert_eq!(reread.play_count().unwrap().to_string(), "3");
+        }
+    }
+
+    #[test]
+    fn test_popularimeter() {
+        for &version in &[V2, V3, V4] {
+            let mut tag = Tag::with_version(version);
+            let mut counter = BigNum::new(vec![]);
+            counter.incr();
+            tag.set_popularimeter(Popularimeter {
+                email: "user@example.com".to_owned(),
+                rating: 255,
+                counter: counter,
+            });
+            tag.set_popularimeter(Popularimeter {
+                email: "other@example.com".to_owned(),
+                rating: 1,
+                counter: BigNum::new(vec![]),
+            });
+
+            let mut reread = round_trip(&tag);
+            let popm = reread.popularimeter("user@example.com").unwrap();
+            assert_eq!(popm.rating, 255);
+            assert_eq!(popm.counter.to_string(), "1");
+            assert_eq!(reread.popularimeter("other@example.com").unwrap().rating, 1);
+            assert!(reread.popularimeter("nobody@example.com").is_none());
+
+            reread.set_popularimeter(Popularimeter { rating: 128, .. popm });
+            assert_eq!(reread.get_frames_by_id(version.popularimeter_id()).len(), 2);
+            assert_eq!(reread.popularimeter("user@example.com").unwrap().rating, 128);
+        }
+    }
+
+    #[test]
+    fn test_popularimeter_without_counter() {
+        let mut frame = Frame::new(Version::V4.popularimeter_id());
+        frame.fields = frame.parse_fields(b"user@example.com\0\xFF").unwrap();
+        let mut tag = Tag::with_version(V4);
+        tag.add_frame(frame);
+        let popm = tag.popularimeter("user@example.com").unwrap();
+        assert_eq!(popm.rating, 255);
+        assert_eq!(popm.counter.to_string(), "0");
+
+        let mut frame = Frame::new(Version::V4.popularimeter_id());
+        frame.fields = vec![Field::Latin1(b"user@example.com".to_vec()), Field::Int8(255)];
+        let mut tag = Tag::with_version(V4);
+        tag.add_frame(frame);
+        assert_eq!(tag.popularimeter("user@example.com").unwrap().counter.to_string(), "0");
+    }
+
+    #[test]
+    fn test_iter() {
+        let mut tag = Tag::with_version(V4);
+        tag.add_frame(title_frame(V4, Encoding::UTF8, b"title"));
+        tag.add_frame(Frame::new(V4.artist_id()));
+
+        let ids: Vec<_> = tag.iter().map(|frame| frame.id).collect();
+        assert_eq!(ids, vec![V4.title_id(), V4.artist_id()]);
+
+        for frame in &mut tag {
+            frame.fields.clear();
+        }
+        for frame in tag.iter_mut() {
+            frame.fields.push(Field::Int8(1));
+        }
+        for frame in &tag {
+            assert_eq!(frame.fields, vec![Field::Int8(1)]);
+        }
+
+        let frames: Vec<Frame> = tag.into_iter().collect();
+        assert_eq!(frames.len(), 2);
+    }
+
+    #[test]
+    fn test_clone() {
+        let mut tag = Tag::with_version(V4);
+        tag.add_frame(title_frame(V4, Encoding::UTF8, b"title"));
+        tag.set_restrictions(Some(TagRestrictions::from_byte(0)));
+
+        let mut copy = tag.clone();
+        copy.frames[0].fields[1] = Field::StringList(vec![b"changed".to_vec()]);
+        copy.add_frame(title_frame(V4, Encoding::UTF8, b"another"));
+        copy.set_restrictions(None);
+
+        assert_eq!(tag.frames.len(), 1);
+        assert_eq!(tag.frames[0].fields[1], Field::StringList(vec![b"title".to_vec()]));
+        assert!(tag.restrictions().is_some());
+        assert_eq!(copy.frames.len(), 2);
+        assert!(copy.restrictions().is_none());
+    }
+
+    #[test]
+    fn test_bytes_round_trip() {
+        for &version in &[V2, V3, V4] {
+            let mut tag = Tag::with_version(version);
+            tag.add_frame(title_frame(version, Encoding::UTF8, b"title"));
+            tag.add_frame(title_frame(version, Encoding::Latin1, b"\xFF\xE0 sync"));
+            tag.increment_play_count();
+            tag.flags.set(TagFlag::Unsynchronization, true);
+
+            let bytes = tag.to_bytes().unwrap();
+            let mut written = vec![];
+            tag.write_to(&mut written, true).unwrap();
+            assert_eq!(bytes, written);
+
+            let reread = Tag::from_bytes(&*bytes).unwrap().unwrap();
+            assert_eq!(reread.frames.len(), tag.frames.len());
+            for (reread, frame) in reread.iter().zip(tag.iter()) {
+                assert_eq!(reread.id, frame.id);
+                assert_eq!(reread.fields_to_bytes(), frame.fields_to_bytes());
+            }
+        }
+
+        assert!(Tag::from_bytes(b"not a tag").unwrap().is_none());
+        assert!(Tag::from_bytes(b"ID3\x04").is_err());
+    }
+
+    #[test]
+    fn test_unique_file_ids() {
+        for &version in &[V2, V3, V4] {
+            let mut tag = Tag::with_version(version);
+            tag.set_unique_file_id("http://musicbrainz.org", b"old id");
+            tag.set_unique_file_id("http://example.com", &[0, 1, 2, 0xFF]);
+            tag.set_unique_file_id("http://musicbrainz.org", b"new id");
+
+            let reread = round_trip(&tag);
+            assert_eq!(reread.unique_file_ids(), vec![
+                ("http://example.com".to_owned(), vec![0, 1, 2, 0xFF]),
+                ("http://musicbrainz.org".to_owned(), b"new id".to_vec()),
+            ]);
+        }
+    }
+
+    #[test]
+    fn test_group_registrations() {
+        assert!(!Tag::with_version(V2).register_group(0x80, "http://example.com", &[]));
+
+        for &version in &[V3, V4] {
+            let mut tag = Tag::with_version(version);
+            assert!(tag.register_group(0x80, "http://example.com/old", &[]));
+            assert!(tag.register_group(0x81, "http://example.org", &[1, 2, 3]));
+            assert!(tag.register_group(0x80, "http://example.com", &[]));
+
+            let mut frame = title_frame(version, Encoding::Latin1, b"title");
+            frame.set_group(Some(0x81));
+            tag.add_frame(frame);
+
+            let reread = round_trip(&tag);
+            assert_eq!(reread.group_registrations(), vec![
+                (0x81, "http://example.org".to_owned(), vec![1, 2, 3]),
+                (0x80, "http://example.com".to_owned(), vec![]),
+            ]);
+            assert_eq!(reread.get_frame_by_id(version.title_id()).unwrap().group(), Some(0x81));
+        }
+    }
+
+    #[test]
+    fn test_retain_frames() {
+        let mut tag = Tag::with_version(V4);
+        tag.add_frame(title_frame(V4, Encoding::UTF8, b"title"));
+        tag.increment_play_count();
+        tag.add_text_frame(V4.artist_id(), "artist");
+        tag.set_unique_file_id("http://example.com", b"id");
+
+        tag.retain_frames(|frame| frame.id.is_text());
+        let ids: Vec<_> = tag.iter().map(|frame| frame.id).collect();
+        assert_eq!(ids, vec![V4.title_id(), V4.artist_id()]);
+
+        tag.remove_frames(|frame| frame.id == V4.title_id());
+        let ids: Vec<_> = tag.iter().map(|frame| frame.id).collect();
+        assert_eq!(ids, vec![V4.artist_id()]);
+    }
+
+    #[test]
+    fn test_sort_frames() {
+        let mut tag = Tag::with_version(V3);
+        tag.add_frame(Frame::new(V3.picture_id()));
+        tag.add_frame(title_frame(V3, Encoding::Latin1, b"title"));
+        tag.increment_play_count();
+        tag.add_frame(Frame::new_url_frame(Id::V3(*b"WOAR"), b"http://example.com/").unwrap());
+        tag.add_text_frame_enc(V3.artist_id(), "artist", Encoding::Latin1);
+        tag.frames[0].fields = vec![Field::TextEncoding(Encoding::Latin1), Field::Latin1(b"image/png".to_vec()),
+            Field::Int8(3), Field::String(vec![]), Field::BinaryData(vec![1, 2, 3])];
+        let expected = vec![V3.title_id(), V3.artist_id(), Id::V3(*b"WOAR"), V3.play_count_id(), V3.picture_id()];
+
+        let mut sorted = tag.clone();
+        sorted.sort_frames();
+        let ids: Vec<_> = sorted.iter().map(|frame| frame.id).collect();
+        assert_eq!(ids, expected);
+
+        tag.set_sort_frames_on_write(true);
+        let mut written = vec![];
+        tag.write_to(&mut written, false).unwrap();
+        assert_eq!(tag.frames[0].id, V3.picture_id());
+        let reread = read_tag(&mut &*written).unwrap().unwrap();
+        let ids: Vec<_> = reread.iter().map(|frame| frame.id).collect();
+        assert_eq!(ids, expected);
+    }
+
+    fn picture_frame(version: Version, picture_type: u8, data: &[u8]) -> Frame {
+        let mut frame = Frame::new(version.picture_id());
+        frame.fields = vec![Field::TextEncoding(Encoding::Latin1), Field::Latin1(b"image/png".to_vec()),
+            Field::Int8(picture_type), Field::String(vec![]), Field::BinaryData(data.to_vec())];
+        frame
+    }
+
+    #[test]
+    fn test_dedup() {
+        let mut tag = Tag::with_version(V3);
+        tag.add_frame(title_frame(V3, Encoding::Latin1, b"first title"));
+        tag.add_frame(picture_frame(V3, 3, b"first front"));
+        tag.add_frame(picture_frame(V3, 4, b"back"));
+        tag.add_frame(title_frame(V3, Encoding::Latin1, b"second title"));
+        tag.add_frame(picture_frame(V3, 3, b"second front"));
+        tag.add_frame(Frame::new_url_frame(Id::V3(*b"WCOM"), b"http://example.com/").unwrap());
+        tag.add_frame(Frame::new_url_frame(Id::V3(*b"WCOM"), b"http://example.org/").unwrap());
+
+        let mut first = tag.clone();
+        first.dedup_keep_first();
+        tag.dedup();
+
+        let fields: Vec<_> = tag.iter().map(|frame| frame.fields.last().unwrap().clone()).collect();
+        assert_eq!(fields, vec![
+            Field::BinaryData(b"back".to_vec()),
+            Field::StringList(vec![b"second title".to_vec()]),
+            Field::BinaryData(b"second front".to_vec()),
+            Field::Latin1(b"http://example.com/".to_vec()),
+            Field::Latin1(b"http://example.org/".to_vec()),
+        ]);
+
+        let fields: Vec<_> = first.iter().map(|frame| frame.fields.last().unwrap().clone()).collect();
+        assert_eq!(fields, vec![
+            Field::StringList(vec![b"first title".to_vec()]),
+            Field::BinaryData(b"first front".to_vec()),
+            Field::BinaryData(b"back".to_vec()),
+            Field::Latin1(b"http://example.com/".to_vec()),
+            Field::Latin1(b"http://example.org/".to_vec()),
+        ]);
+    }
+
+    #[test]
+    fn test_from_id3v1() {
+        use id3v1;
+        use id3v2::simple::Simple;
+
+        let mut v1 = id3v1::Tag::new();
+        v1.title = b"caf\xE9\0\0".to_vec();
+        v1.artist = b"artist  ".to_vec();
+        v1.album = b"album".to_vec();
+        v1.year = id3v1::Year::new(1999).unwrap();
+        v1.comment = b"comment".to_vec();
+        v1.track = 7;
+        v1.genre = 9;
+
+        let tag = Tag::from_id3v1(&v1, V3);
+        assert_eq!(tag.text_frame_text(Id::V3(*b"TIT2")).unwrap(), "café");
+        assert_eq!(tag.text_frame_text(Id::V3(*b"TPE1")).unwrap(), "artist");
+        assert_eq!(tag.text_frame_text(Id::V3(*b"TALB")).unwrap(), "album");
+        assert_eq!(tag.text_frame_text(Id::V3(*b"TYER")).unwrap(), "1999");
+        assert_eq!(tag.comment("eng", "").unwrap(), "comment");
+        assert_eq!(tag.text_frame_text(Id::V3(*b"TRCK")).unwrap(), "7");
+        assert_eq!(tag.text_frame_text(Id::V3(*b"TCON")).unwrap(), "(9)");
+        assert_eq!(tag.frames.len(), 7);
+
+        v1.genre_str = b"Nu Metal".to_vec();
+        let tag = Tag::from_id3v1(&v1, V4);
+        assert_eq!(tag.text_frame_text(Id::V4(*b"TDRC")).unwrap(), "1999");
+        assert_eq!(tag.genre_names(), vec!["Metal", "Nu Metal"]);
+        let tag = Tag::from_id3v1(&v1, V2);
+        assert_eq!(tag.text_frame_text(Id::V2(*b"TCO")).unwrap(), "(9)Nu Metal");
+
+        v1.genre = 255;
+        let tag = Tag::from_id3v1(&v1, V3);
+        assert_eq!(tag.genre_names(), vec!["Nu Metal"]);
+    }
+
+    #[test]
+    fn test_display() {
+        let mut tag = Tag::new();
+        tag.add_frame(title_frame(V4, Encoding::UTF16, b"\xFF\xFEt\0i\0t\0l\0e\0"));
+        tag.add_frame(picture_frame(V4, 3, &[1, 2, 3]));
+
+        let dump = tag.to_string();
+        let lines: Vec<&str> = dump.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("TIT2 ("));
+        assert!(lines[0].ends_with("): title"));
+        assert!(lines[1].starts_with("APIC ("));
+        assert!(lines[1].contains("<3 bytes: 01 02 03>"));
+    }
+
+    #[test]
+    fn test_text_frame_values() {
+        let id = Id::V4(*b"TPE1");
+        let mut tag = Tag::new();
+        assert!(tag.set_text_frame_values(id, &["first", "second"], Encoding::UTF8));
+        assert_eq!(tag.text_frame_values(id).unwrap(), vec!["first", "second"]);
+
+        let mut tag = read_tag(&mut io::Cursor::new(tag.to_bytes().unwrap())).unwrap().unwrap();
+        assert_eq!(tag.text_frame_values(id).unwrap(), vec!["first", "second"]);
+
+        tag.add_frame(title_frame(V4, Encoding::Latin1, b"one\0two\0"));
+        assert_eq!(tag.text_frame_values(Id::V4(*b"TIT2")).unwrap(), vec!["one", "two"]);
+        tag.remove_frames_by_id(Id::V4(*b"TIT2"));
+        tag.add_frame(title_frame(V4, Encoding::UTF8, b"caf\xC3\xA9\0bar"));
+        assert_eq!(tag.text_frame_values(Id::V4(*b"TIT2")).unwrap(), vec!["café", "bar"]);
+
+        let id = Id::V3(*b"TPE1");
+        let mut tag = Tag::with_version(V3);
+        assert!(!tag.set_text_frame_values(id, &["first"], Encoding::UTF8));
+        assert!(tag.set_text_frame_values(id, &["first", "second"], Encoding::Latin1));
+        assert_eq!(tag.text_frame_values(id).unwrap(), vec!["first/second"]);
+        assert!(tag.text_frame_values(Id::V3(*b"TIT2")).is_none());
+    }
+
+    #[test]
+    fn test_padding_len() {
+        let mut tag = Tag::new();
+        assert_eq!(tag.padding_len(), 1024);
+        tag.add_frame(title_frame(V4, Encoding::UTF8, b"title"));
+        tag.set_padding_len(100);
+
+        let mut written = vec![];
+        let len = tag.write_to(&mut written, false).unwrap();
+        assert_eq!(len as usize, written.len());
+        let frames_len = tag.frames[0].size(false) as usize;
+        assert_eq!(written.len(), 10 + frames_len + 100);
+        assert_eq!(util::unsynchsafe(BigEndian::read_u32(&written[6..10])) as usize, frames_len + 100);
+        assert!(written[10 + frames_len..].iter().all(|&b| b == 0));
+
+        let reread = read_tag(&mut &*written).unwrap().unwrap();
+        assert_eq!(reread.frames, tag.frames);
+        assert_eq!(reread.padding_len(), 100);
+    }
+
+    #[test]
+    fn test_padding_preserved() {
+        for &version in &[V2, V3, V4] {
+            let mut tag = Tag::with_version(version);
+            tag.add_frame(title_frame(version, Encoding::Latin1, b"title"));
+            tag.set_padding_len(300);
+            let mut written = vec![];
+            tag.write_to(&mut written, false).unwrap();
+
+            let mut reread = read_tag(&mut &*written).unwrap().unwrap();
+            assert_eq!(reread.padding_len(), 300);
+            reread.add_frame(title_frame(version, Encoding::Latin1, b"another title"));
+            let mut rewritten = vec![];
+            reread.write_to(&mut rewritten, false).unwrap();
+            let frames_len: u32 = reread.iter().map(|frame| frame.size(false)).sum();
+            assert_eq!(rewritten.len(), 10 + frames_len as usize + 300);
+
+            let reread = read_tag(&mut &*rewritten).unwrap().unwrap();
+            assert_eq!(reread.frames.len(), 2);
+            assert_eq!(reread.padding_len(), 300);
+        }
+    }
+
+    #[test]
+    fn test_synced_lyrics() {
+        use id3v2::frame::{SyncedLyrics, TimestampFormat};
+
+        for &(version, encoding) in &[(V2, Encoding::UTF16), (V3, Encoding::Latin1), (V4, Encoding::UTF8)] {
+            let lyrics = SyncedLyrics {
+                lang: "eng".to_owned(),
+                timestamp_format: TimestampFormat::MpegFrames,
+                content_type: 1,
+                description: "lyrics".to_owned(),
+                content: vec![("first line".to_owned(), 10), ("second line".to_owned(), 250)],
+            };
+            let mut tag = Tag::with_version(version);
+            tag.set_synced_lyrics(SyncedLyrics { content: vec![], ..lyrics.clone() }, encoding);
+            tag.set_synced_lyrics(lyrics.clone(), encoding);
+            assert_eq!(tag.frames.len(), 1);
+
+            let reread = round_trip(&tag);
+            assert_eq!(reread.synced_lyrics(), vec![lyrics]);
+        }
+    }
+
+    #[test]
+    fn test_event_timing_codes() {
+        use id3v2::frame::{EventTimingCodes, TimestampFormat};
+
+        for &version in &[V2, V3, V4] {
+            let codes = EventTimingCodes {
+                timestamp_format: TimestampFormat::MpegFrames,
+                events: vec![(0x02, 40), (0x03, 9000)],
+            };
+            let mut tag = Tag::with_version(version);
+            assert!(tag.event_timing_codes().is_none());
+            tag.set_event_timing_codes(EventTimingCodes { events: vec![], ..codes.clone() });
+            tag.set_event_timing_codes(codes.clone());
+            assert_eq!(tag.frames.len(), 1);
+
+            let reread = round_trip(&tag);
+            assert_eq!(reread.event_timing_codes(), Some(codes));
+        }
+    }
+
+    #[test]
+    fn test_chapters() {
+        use id3v2::frame::{Chapter, TableOfContents};
+
+        assert!(!Tag::with_version(V2).set_chapter(Chapter {
+            element_id: "chp0".to_owned(), start_time: 0, end_time: 0, start_offset: 0, end_offset: 0, frames: vec![],
+        }));
+
+        for &version in &[V3, V4] {
+            let chapter = Chapter {
+                element_id: "chp0".to_owned(),
+                start_time: 0,
+                end_time: 60000,
+                start_offset: 0xFFFFFFFF,
+                end_offset: 0xFFFFFFFF,
+                frames: vec![title_frame(version, Encoding::Latin1, b"Introduction")],
+            };
+            let toc = TableOfContents {
+                element_id: "toc".to_owned(),
+                top_level: true,
+                ordered: true,
+                children: vec!["chp0".to_owned()],
+                frames: vec![],
+            };
+            let mut tag = Tag::with_version(version);
+            assert!(tag.set_chapter(Chapter { end_time: 1, ..chapter.clone() }));
+            assert!(tag.set_chapter(chapter.clone()));
+            assert!(tag.set_table_of_contents(toc.clone()));
+            assert_eq!(tag.frames.len(), 2);
+
+            let reread = round_trip(&tag);
+            let chapters = reread.chapters();
+            assert_eq!(chapters.len(), 1);
+            assert_eq!(chapters[0].element_id, "chp0");
+            assert_eq!(chapters[0].end_time, 60000);
+            assert_eq!(chapters[0].frames.len(), 1);
+            assert_eq!(chapters[0].frames[0].id, version.title_id());
+            assert_eq!(chapters[0].frames[0].fields_to_bytes(), chapter.frames[0].fields_to_bytes());
+            assert_eq!(reread.tables_of_contents(), vec![toc]);
+        }
+    }
+
+    #[test]
+    fn test_invalid_ids() {
+        let mut tag = Tag::new();
+        assert!(!tag.add_frame(Frame::new(Id::V4(*b"TAL "))));
+        assert!(!tag.add_frame(Frame::new(Id::V4(*b"TAL\0"))));
+        assert!(!tag.add_frame(Frame::new(Id::V4(*b"tit2"))));
+        assert!(!tag.add_text_frame(Id::V4(*b"TIT "), "title"));
+        assert!(tag.is_empty());
+
+        assert!(Frame::new_text_frame(Id::V3(*b"TIT "), "title", Encoding::Latin1).is_none());
+        assert!(Frame::new_url_frame(Id::V3(*b"WOA "), b"http://example.com").is_none());
+        assert!(tag.add_frame(Frame::new(Id::V4(*b"TIT2"))));
+    }
+
+    #[test]
+    fn test_clear() {
+        let mut tag = Tag::with_version(V3);
+        tag.flags.set(TagFlag::Unsynchronization, true);
+        tag.add_frame(title_frame(V3, Encoding::Latin1, b"title"));
+        tag.increment_play_count();
+        assert!(!tag.is_empty());
+
+        tag.clear();
+        assert!(tag.is_empty());
+        assert_eq!(tag.version(), V3);
+        assert!(tag.flags().get(TagFlag::Unsynchronization));
+    }
+
+    #[test]
+    fn test_try_add_text_frame_enc() {
+        use id3v2::frame::TextEncodingPolicy::*;
+
+        let mut tag = Tag::with_version(V3);
+        tag.add_text_frame_enc(V3.title_id(), "title", Encoding::Latin1);
+        assert!(tag.try_add_text_frame_enc(V3.title_id(), "東京", Encoding::Latin1, Strict).is_err());
+        assert_eq!(tag.text_frame_text(V3.title_id()).unwrap(), "title");
+
+        tag.try_add_text_frame_enc(V3.title_id(), "東京", Encoding::Latin1, Lossy).unwrap();
+        assert_eq!(tag.text_frame_text(V3.title_id()).unwrap(), "??");
+        assert_eq!(tag.frames.len(), 1);
+
+        assert!(tag.try_add_text_frame_enc(V3.comment_id(), "text", Encoding::Latin1, Lossy).is_err());
+        assert!(tag.try_add_text_frame_enc(V3.title_id(), "text", Encoding::UTF8, Lossy).is_err());
+
+        assert!(!tag.add_text_frame_enc(V3.comment_id(), "text", Encoding::Latin1));
+        assert!(!tag.add_text_frame_enc(V3.title_id(), "text", Encoding::UTF8));
+        assert_eq!(tag.text_frame_text(V3.title_id()).unwrap(), "??");
+        assert_eq!(tag.frames.len(), 1);
+    }
+
+    #[test]
+    fn test_convert_version_fields() {
+        let mut tag = Tag::with_version(V2);
+        let mut picture = Frame::new(V2.picture_id());
+        picture.fields = vec![Field::TextEncoding(Encoding::Latin1), Field::Int24(b'P', b'N', b'G'),
+            Field::Int8(3), Field::String(b"cover".to_vec()), Field::BinaryData(vec![1, 2, 3])];
+        tag.add_frame(picture);
+        tag.add_frame(title_frame(V2, Encoding::Latin1, b"title"));
+
+        tag.convert_version(V4);
+        assert_eq!(tag.frames.len(), 2);
+        let picture = tag.get_frame_by_id(V4.picture_id()).unwrap();
+        assert_eq!(picture.fields[1], Field::Latin1(b"image/png".to_vec()));
+        assert!(tag.frames.iter().all(|frame| frame.fields_match_format()));
+
+        let mut data = vec![];
+        tag.write_to(&mut data, false).unwrap();
+        let reread = read_tag(&mut &*data).unwrap().unwrap();
+        let picture = reread.get_frame_by_id(V4.picture_id()).unwrap();
+        assert_eq!(picture.fields[1], Field::Latin1(b"image/png".to_vec()));
+        assert_eq!(picture.fields[4], Field::BinaryData(vec![1, 2, 3]));
+
+        // a frame whose fields do not fit its layout is dropped
+        let mut malformed = Frame::new(V4.title_id());
+        malformed.fields = vec![Field::Int8(9)];
+        tag.frames.push(malformed);
+        tag.convert_version(V3);
+        assert_eq!(tag.frames.len(), 2);
+    }
+
+    #[test]
+    fn test_merge() {
+        use id3v2::MergePolicy;
+        use id3v2::simple::Simple;
+
+        let txxx = |tag: &Tag| -> Vec<_> {
+            tag.frames.iter().filter_map(|frame| frame.description_text()).collect()
+        };
+        let tags = || {
+            let mut tag = Tag::with_version(V4);
+            tag.add_frame(title_frame(V4, Encoding::UTF8, b"title"));
+            tag.add_txxx_enc("first", "1", Encoding::UTF8);
+            let mut other = Tag::with_version(V3);
+            other.add_frame(title_frame(V3, Encoding::Latin1, b"other title"));
+            other.add_txxx_enc("second", "2", Encoding::Latin1);
+            (tag, other)
+        };
+
+        let (mut tag, other) = tags();
+        tag.merge(other, MergePolicy::KeepExisting);
+        assert_eq!(tag.frames.len(), 3);
+        assert!(tag.frames.iter().all(|frame| frame.version() == V4));
+        assert_eq!(tag.text_frame_values(V4.title_id()), Some(vec!["title".to_owned()]));
+        assert_eq!(txxx(&tag), vec!["first", "second"]);
+
+        let (mut tag, other) = tags();
+        tag.merge(other, MergePolicy::PreferOther);
+        assert_eq!(tag.frames.len(), 3);
+        assert_eq!(tag.get_frames_by_id(V4.title_id()).len(), 1);
+        assert_eq!(tag.text_frame_values(V4.title_id()), Some(vec!["other title".to_owned()]));
+        assert_eq!(txxx(&tag), vec!["first", "second"]);
+
+        let reread = read_tag(&mut &*tag.to_bytes().unwrap()).unwrap().unwrap();
+        assert_eq!(reread.frames, tag.frames);
+        assert_eq!(reread.text_frame_values(V4.title_id()), Some(vec!["other title".to_owned()]));
+
+        let (mut tag, other) = tags();
+        tag.merge(other, MergePolicy::AppendAll);
+        assert_eq!(tag.frames.len(), 4);
+        assert_eq!(tag.get_frames_by_id(V4.title_id()).len(), 2);
+    }
+
+    #[test]
+    fn test_text_frame_str() {
+        use std::borrow::Cow;
+
+        let mut tag = Tag::with_version(V4);
+        tag.add_frame(title_frame(V4, Encoding::UTF8, b"title\0"));
+        match tag.text_frame_str(V4.title_id()) {
+            Some(Cow::Borrowed(text)) => assert_eq!(text, "title"),
+            other => panic!("expected borrowed text, got {:?}", other),
+        }
+
+        tag.remove_frames_by_id(V4.title_id());
+        tag.add_frame(title_frame(V4, Encoding::Latin1, b"caf\xE9"));
+        assert_eq!(tag.text_frame_str(V4.title_id()), Some(Cow::Owned("café".to_owned())));
+
+        let mut frame = Frame::new(V4.album_id());
+        frame.fields = vec![Field::TextEncoding(Encoding::UTF8), Field::StringList(vec![b"a".to_vec(), b"b".to_vec()])];
+        tag.add_frame(frame);
+        assert_eq!(tag.text_frame_str(V4.album_id()).unwrap(), "a/b");
+        assert_eq!(tag.text_frame_str(V4.artist_id()), None);
+    }
+
+    #[test]
+    fn test_recording_time() {
+        use id3v2::simple::Simple;
+
+        let mut tag = Tag::with_version(V4);
+        assert!(tag.recording_time().is_none());
+        for &(text, month, second) in &[("2014", None, None), ("2014-03", Some(3), None),
+                ("2014-03-21T18:30:05", Some(3), Some(5))] {
+            tag.add_text_frame(Id::V4(*b"TDRC"), text);
+            let time = tag.recording_time().unwrap();
+            assert_eq!((time.year, time.month, time.second), (2014, month, second));
+            assert_eq!(tag.year(), Some(2014));
+        }
+
+        // TYER takes precedence, and TDRC is only read from ID3v2.4 tags
+        tag.set_year(1999);
+        assert_eq!(tag.year(), Some(1999));
+        tag.remove_frames_by_id(V4.year_id());
+        tag.add_text_frame(Id::V4(*b"TDRC"), "recently");
+        assert!(tag.recording_time().is_none());
+        assert_eq!(tag.year(), None);
+    }
+
+    #[test]
+    fn test_unknown_frames() {
+        for &(version, id) in &[(V2, Id::V2(*b"XYZ")), (V3, Id::V3(*b"XYZZ")), (V4, Id::V4(*b"XYZZ"))] {
+            let mut tag = Tag::with_version(version);
+            let mut frame = Frame::new(id);
+            frame.fields = vec![Field::BinaryData(vec![0x01, 0x00, 0xFF, 0x42])];
+            assert!(tag.add_frame(frame));
+            tag.add_frame(title_frame(version, Encoding::Latin1, b"title"));
+
+            let mut data = vec![];
+            tag.write_to(&mut data, false).unwrap();
+            let reread = read_tag(&mut &*data).unwrap().unwrap();
+            assert_eq!(reread.frames.len(), 2);
+            assert_eq!(reread.get_frame_by_id(id).unwrap().fields, vec![Field::BinaryData(vec![0x01, 0x00, 0xFF, 0x42])]);
+
+            let mut rewritten = vec![];
+            reread.write_to(&mut rewritten, false).unwrap();
+            assert_eq!(rewritten, data);
+        }
+    }
+
+    #[test]
+    fn test_read_tag_skipping_bad_frames() {
+        let mut body = vec![];
+        body.extend(b"TIT2\x00\x00\x00\x06\x00\x00\x00title".iter());
+        // an invalid encoding byte
+        body.extend(b"TALB\x00\x00\x00\x06\x00\x00\x07album".iter());
+        body.extend(b"TPE1\x00\x00\x00\x07\x00\x00\x00artist".iter());
+        body.extend([0u8; 8].iter());
+
+        let mut data = b"ID3\x03\x00\x00".to_vec();
+        let mut size = [0; 4];
+        BigEndian::write_u32(&mut size, util::synchsafe(body.len() as u32));
+        data.extend(size.iter());
+        data.extend(body);
+
+        assert!(read_tag(&mut &data[..]).is_err());
+        assert!(read_tag_with(&mut &data[..], ParseOptions::default()).is_err());
+
+        let (tag, errors) = read_tag_with(&mut &data[..], ParseOptions { skip_bad_frames: true }).unwrap().unwrap();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(tag.frames.len(), 2);
+        assert_eq!(tag.text_frame_str(Id::V3(*b"TIT2")).unwrap(), "title");
+        assert_eq!(tag.text_frame_str(Id::V3(*b"TPE1")).unwrap(), "artist");
+        assert!(tag.get_frame_by_id(Id::V3(*b"TALB")).is_none());
+        assert_eq!(tag.padding_len(), 8);
+    }
+
+    #[test]
+    fn test_with_capacity() {
+        let mut tag = Tag::with_capacity(V3, 16);
+        assert_eq!(tag.version(), V3);
+        assert!(tag.frames.is_empty());
+        assert!(tag.frames.capacity() >= 16);
+
+        tag.reserve(40);
+        assert!(tag.frames.capacity() >= 40);
+    }
+
+    #[test]
+    fn test_ownership_round_trip() {
+        let ownership = Ownership {
+            price: "USD0.99".to_owned(),
+            date: "20140321".to_owned(),
+            seller: "Record Store".to_owned(),
+        };
+        for &(version, encoding) in &[(V3, Encoding::UTF16), (V4, Encoding::UTF8)] {
+            let mut tag = Tag::with_version(version);
+            assert!(!tag.set_ownership(Ownership { date: "2014-03-21".to_owned(), .. ownership.clone() }, encoding));
+            assert!(tag.ownership().is_none());
+            assert!(tag.set_ownership(ownership.clone(), encoding));
+
+            let reread = round_trip(&tag);
+            assert_eq!(reread.ownership(), Some(ownership.clone()));
+        }
+
+        let mut tag = Tag::with_version(V2);
+        assert!(!tag.set_ownership(ownership, Encoding::UTF16));
+        assert!(tag.frames.is_empty());
+    }
+
+    #[test]
+    fn test_commercial_round_trip() {
+        let commercial = Commercial {
+            price: "USD9.99/EUR8.99".to_owned(),
+            valid_until: "20151231".to_owned(),
+            contact_url: "mailto:store@example.com".to_owned(),
+            received_as: 2,
+            seller: "Record Store".to_owned(),
+            description: "The album, compressed".to_owned(),
+            mime_type: "image/png".to_owned(),
+            logo: vec![0x89, b'P', b'N', b'G', 0x00, 0xFF, 0xE0, 0x00],
+        };
+        for &(version, encoding) in &[(V3, Encoding::UTF16), (V4, Encoding::UTF8)] {
+            let mut tag = Tag::with_version(version);
+            assert!(tag.add_commercial_frame(commercial.clone(), encoding));
+            assert!(tag.add_commercial_frame(Commercial { received_as: 1, .. commercial.clone() }, encoding));
+            assert!(tag.add_commercial_frame(commercial.clone(), encoding));
+            assert_eq!(tag.frames.len(), 2);
+
+            let reread = round_trip(&tag);
+            let offers = reread.commercial_frames();
+            assert_eq!(offers.len(), 2);
+            assert!(offers.contains(&commercial));
+            assert!(offers.iter().any(|offer| offer.received_as == 1));
+        }
+
+        assert!(!Tag::with_version(V2).add_commercial_frame(commercial, Encoding::UTF16));
+    }
+
+    #[test]
+    fn test_frame_encoding() {
+        for &(version, encoding) in &[(V2, Encoding::UTF16), (V3, Encoding::UTF16), (V4, Encoding::UTF16BE)] {
+            let mut tag = Tag::with_version(version);
+            tag.add_text_frame_enc(version.title_id(), "title", encoding);
+
+            let reread = round_trip(&tag);
+            assert_eq!(reread.frame_encoding(version.title_id()), Some(encoding));
+            assert_eq!(reread.frame_encoding(version.album_id()), None);
+        }
+
+        let mut tag = Tag::with_version(V4);
+        tag.add_frame(Frame::new(Id::V4(*b"MCDI")));
+        assert_eq!(tag.frame_encoding(Id::V4(*b"MCDI")), None);
+    }
+
+    #[test]
+    fn test_on_disk_size() {
+        let frames = |version: Version| vec![
+            title_frame(version, Encoding::UTF16, &util::string_to_utf16("title")),
+            picture_frame(version, 3, &[0xFF, 0xE0, 0x00, 0xFF]),
+        ];
+        for &version in &[V3, V4] {
+            for &unsynchronization in &[false, true] {
+                for &footer in &[false, true] {
+                    let mut tag = Tag::with_version(version);
+                    tag.frames = frames(version);
+                    tag.set_padding_len(37);
+                    tag.flags.set(TagFlag::Unsynchronization, unsynchronization);
+                    tag.flags.set(TagFlag::Footer, footer && version == V4);
+                    assert_eq!(tag.on_disk_size() as usize, tag.to_bytes().unwrap().len(),
+                               "{:?} unsync {} footer {}", version, unsynchronization, footer);
+                    // size takes unsynchronization as given rather than from the flags
+                    let mut written = vec![];
+                    let len = tag.write_to(&mut written, !unsynchronization).unwrap();
+                    assert_eq!(tag.size(!unsynchronization), len);
+
+                    // the tag ends in 0xFF, which unsynchronization guards
+                    tag.set_padding_len(0);
+                    assert_eq!(tag.on_disk_size() as usize, tag.to_bytes().unwrap().len());
+                }
+            }
+        }
+
+        let bytes = [0, 0, 0, 10, 0x80, 0x00, 0, 0, 0x08, 0x00, 0xDE, 0xAD, 0xBE, 0xEF];
+        let mut tag = Tag::with_version(V3);
+        tag.frames = frames(V3);
+        tag.extended_header = Some(ExtendedHeader::parse(&mut &bytes[..], V3).unwrap().0);
+        tag.flags.set(TagFlag::ExtendedHeader, true);
+        assert_eq!(tag.on_disk_size() as usize, tag.to_bytes().unwrap().len());
+
+        let mut tag = Tag::with_version(V2);
+        tag.add_text_frame_enc(V2.title_id(), "title", Encoding::Latin1);
+        assert_eq!(tag.on_disk_size() as usize, tag.to_bytes().unwrap().len());
+    }
+
+    #[test]
+    fn test_get_frames_by_id_mut() {
+        let mut tag = Tag::with_version(V4);
+        tag.add_frame(title_frame(V4, Encoding::Latin1, b"first"));
+        tag.add_frame(title_frame(V4, Encoding::Latin1, b"second"));
+
+        tag.get_frame_by_id_mut(V4.title_id()).unwrap().fields[1] = Field::StringList(vec![b"changed".to_vec()]);
+        assert_eq!(tag.frames[0], title_frame(V4, Encoding::Latin1, b"changed"));
+        assert_eq!(tag.frames[1], title_frame(V4, Encoding::Latin1, b"second"));
+
+        for frame in tag.get_frames_by_id_mut(V4.title_id()) {
+            frame.fields[0] = Field::TextEncoding(Encoding::UTF8);
+        }
+        assert_eq!(tag.frames[0], title_frame(V4, Encoding::UTF8, b"changed"));
+        assert_eq!(tag.frames[1], title_frame(V4, Encoding::UTF8, b"second"));
+        assert!(tag.get_frames_by_id_mut(V4.album_id()).is_empty());
+    }
+
+    #[test]
+    fn test_truncated_extended_header() {
+        // the tag restrictions flag is set, but its payload is missing
+        let header = [0, 0, 0, 6, 0x01, 0x10, 0x01];
+        assert!(ExtendedHeader::parse(&mut &header[..], V4).is_err());
+        assert!(ExtendedHeader::parse(&mut &header[..5], V4).is_err());
+        assert!(ExtendedHeader::parse(&mut &header[..], V2).is_err());
+
+        let mut tag = b"ID3\x04\x00\x40\x00\x00\x00\x07".to_vec();
+        tag.extend(&header);
+        assert!(read_tag(&mut &*tag).is_err());
+    }
+
+    #[test]
+    fn test_v2_compression() {
+        let tag = b"ID3\x02\x00\x40\x00\x00\x00\x00";
+        assert!(read_tag(&mut &tag[..]).is_err());
+    }
+
+    #[test]
+    fn test_extended_header_frame_offset() {
+        for &version in &[V3, V4] {
+            // the CRC is 4 bytes in ID3v2.3 and a 5-byte synchsafe integer in ID3v2.4
+            let crc = match version {
+                V4 => vec![0x01, 0x02, 0x03, 0x04, 0x05],
+                _ => vec![0x01, 0x02, 0x03, 0x04],
+            };
+            let mut tag = Tag::with_version(version);
+            tag.flags.set(TagFlag::ExtendedHeader, true);
+            tag.extended_header = Some(ExtendedHeader {
+                flag_data: vec![(ExtendedFlag::Crc, crc)],
+                padding_size: 0,
+            });
+            tag.add_frame(title_frame(version, Encoding::Latin1, b"title"));
+
+            let mut written = vec![];
+            let len = tag.write_to(&mut written, false).unwrap();
+            assert_eq!(len as usize, written.len());
+            assert_eq!(tag.size(false), len);
+            assert_eq!(util::unsynchsafe(BigEndian::read_u32(&written[6..10])) as usize, written.len() - 10);
+
+            // the CRC and the ID3v2.3 padding size describe what was written
+            let mut frame_bytes = vec![];
+            tag.frames[0].write_to(&mut frame_bytes, false).unwrap();
+            let expected = tag.extended_header.as_ref().unwrap().describing(&*frame_bytes, tag.padding_len(), version);
+            assert!(expected.flag_data[0].1 != tag.extended_header.as_ref().unwrap().flag_data[0].1);
+
+            let reread = read_tag(&mut &*written).unwrap().unwrap();
+            assert_eq!(reread.extended_header, Some(expected));
+            assert_eq!(reread.frames, tag.frames);
+        }
+    }
+
+    #[test]
+    fn test_extended_header_padding_size() {
+        let mut tag = Tag::with_version(V3);
+        tag.flags.set(TagFlag::ExtendedHeader, true);
+        tag.extended_header = Some(ExtendedHeader { flag_data: vec![], padding_size: 0x800 });
+        tag.add_frame(title_frame(V3, Encoding::Latin1, b"title"));
+        tag.set_padding_len(300);
+
+        let reread = round_trip(&tag);
+        assert_eq!(reread.extended_header.unwrap().padding_size, 300);
+
+        let mut written = vec![];
+        let total_size = tag.size(false) + 50;
+        assert!(tag.write_to_fit(&mut written, false, total_size).unwrap());
+        assert_eq!(written.len(), total_size as usize);
+        let reread = read_tag(&mut &*written).unwrap().unwrap();
+        assert_eq!(reread.extended_header.unwrap().padding_size, 350);
+        assert_eq!(reread.padding_len(), 350);
+
+        tag.set_padding_len(0);
+        assert_eq!(round_trip(&tag).extended_header.unwrap().padding_size, 0);
+    }
+
+    #[test]
+    fn test_read_from_end() {
+        use std::io::Cursor;
+        use id3v1;
+
+        let mut tag = Tag::with_version(V4);
+        tag.flags.set(TagFlag::Footer, true);
+        tag.add_frame(title_frame(V4, Encoding::UTF8, b"streamed"));
+
+        let mut stream = vec![0xFFu8, 0xFB, 0x90, 0x64];
+        stream.extend(vec![0u8; 413]);
+        let tag_start = stream.len();
+        tag.write_to(&mut stream, false).unwrap();
+
+        let reread = Tag::read_from_end(&mut Cursor::new(&*stream)).unwrap().unwrap();
+        assert_eq!(reread.frames, tag.frames);
+
+        id3v1::Tag::new().write(&mut stream, false).unwrap();
+        let reread = Tag::read_from_end(&mut Cursor::new(&*stream)).unwrap().unwrap();
+        assert_eq!(reread.frames, tag.frames);
+
+        assert!(Tag::read_from_end(&mut Cursor::new(&stream[..tag_start])).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_footer() {
+        let mut tag = Tag::with_version(V4);
+        tag.flags.set(TagFlag::Footer, true);
+        tag.padding_len = 512;
+        tag.add_frame(title_frame(V4, Encoding::UTF8, b"title"));
+
+        let mut written = vec![];
+        let len = tag.write_to(&mut written, false).unwrap();
+        assert_eq!(len as usize, written.len());
+        assert_eq!(tag.size(false), len);
+
+        // no padding precedes the footer
+        let frames_len = tag.frames[0].size(false) as usize;
+        assert_eq!(written.len(), 10 + frames_len + 10);
+        let (header, footer) = (&written[..10], &written[written.len() - 10..]);
+        assert_eq!(&footer[..3], b"3DI");
+        assert_eq!(&footer[3..], &header[3..]);
+
+        let reread = read_tag(&mut &*written).unwrap().unwrap();
+        assert!(reread.flags().get(TagFlag::Footer));
+        assert_eq!(reread.frames, tag.frames);
+    }
+
+    #[test]
+    fn test_convert_version_extended_header() {
+        // an update flag, a CRC and restrictions
+        let bytes = [0, 0, 0, 15, 0x01, 0x70, 0x00, 0x05, 0x01, 0x02, 0x03, 0x04, 0x05, 0x01, 0x42];
+        let extended = ExtendedHeader::parse(&mut &bytes[..], V4).unwrap().0;
+        let mut written = vec![];
+        assert_eq!(extended.write_to(&mut written, V3).unwrap() as usize, extended.size(V3));
+        assert_eq!(written.len(), 4 + 2 + 4 + 5);
+        assert_eq!(extended.size(V2), 0);
+
+        for &version in &[V3, V2] {
+            let mut tag = Tag::with_version(V4);
+            tag.flags.set(TagFlag::ExtendedHeader, true);
+            tag.extended_header = Some(extended.clone());
+            tag.add_frame(title_frame(V4, Encoding::Latin1, b"title"));
+            let mut tag = round_trip(&tag);
+            assert!(tag.restrictions().is_some());
+
+            tag.convert_version(version);
+            assert!(tag.restrictions().is_none());
+            assert_eq!(tag.on_disk_size() as usize, tag.to_bytes().unwrap().len());
+
+            let reread = round_trip(&tag);
+            assert_eq!(reread.version(), version);
+            assert_eq!(reread.frames.len(), 1);
+            assert_eq!(reread.extended_header.is_some(), version == V3);
+            if version == V3 {
+                let mut frame_bytes = vec![];
+                tag.frames[0].write_to(&mut frame_bytes, false).unwrap();
+                let crc = util::u32_to_bytes(util::crc32(&*frame_bytes)).to_vec();
+                assert_eq!(reread.extended_header.unwrap().flag_data, vec![(ExtendedFlag::Crc, crc)]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_restrictions_round_trip() {
+        let restrictions = TagRestrictions::from_byte(0x42);
+        let mut tag = Tag::with_version(V4);
+        assert!(tag.set_restrictions(Some(restrictions)));
+
+        let mut reread = round_trip(&tag);
+        assert_eq!(reread.restrictions(), Some(restrictions));
+
+        assert!(reread.set_restrictions(None));
+        assert!(reread.restrictions().is_none());
+        assert!(!reread.flags().get(TagFlag::ExtendedHeader));
+
+        assert!(!Tag::with_version(V3).set_restrictions(Some(restrictions)));
+    }
 }
+// }}}
@@ -0,0 +1,105 @@
+/// Restrictions on the number of frames and total size of a tag.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum TagSizeRestriction {
+    /// No more than 128 frames and 1 MB total tag size.
+    Frames128Size1MB,
+    /// No more than 64 frames and 128 KB total tag size.
+    Frames64Size128KB,
+    /// No more than 32 frames and 40 KB total tag size.
+    Frames32Size40KB,
+    /// No more than 32 frames and 4 KB total tag size.
+    Frames32Size4KB,
+}
+
+/// Restrictions on the length of text fields.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum TextSizeRestriction {
+    /// No restrictions.
+    Unrestricted,
+    /// No string is longer than 1024 characters.
+    Chars1024,
+    /// No string is longer than 128 characters.
+    Chars128,
+    /// No string is longer than 30 characters.
+    Chars30,
+}
+
+/// Restrictions on the dimensions of images.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum ImageSizeRestriction {
+    /// No restrictions.
+    Unrestricted,
+    /// All images are 256x256 pixels or smaller.
+    Max256x256,
+    /// All images are 64x64 pixels or smaller.
+    Max64x64,
+    /// All images are exactly 64x64 pixels, unless required otherwise.
+    Exactly64x64,
+}
+
+/// The restrictions an ID3v2.4 tag places on its contents, as stored in the
+/// one-byte payload of the extended header's tag restrictions flag.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct TagRestrictions {
+    /// Restrictions on the number of frames and tag size.
+    pub tag_size: TagSizeRestriction,
+    /// Whether text is restricted to the ISO-8859-1 and UTF-8 encodings.
+    pub latin1_or_utf8_only: bool,
+    /// Restrictions on the length of text fields.
+    pub text_size: TextSizeRestriction,
+    /// Whether images are restricted to the PNG and JPEG formats.
+    pub png_or_jpeg_only: bool,
+    /// Restrictions on the dimensions of images.
+    pub image_size: ImageSizeRestriction,
+}
+
+impl TagRestrictions {
+    /// Parses restrictions from the payload byte, which is laid out as
+    /// `%ppqrrstt`: tag size (p), text encoding (q), text size (r), image
+    /// encoding (s), and image size (t).
+    pub fn from_byte(byte: u8) -> TagRestrictions {
+        use self::TagSizeRestriction::*;
+        use self::TextSizeRestriction::*;
+        use self::ImageSizeRestriction::*;
+        TagRestrictions {
+            tag_size: [Frames128Size1MB, Frames64Size128KB, Frames32Size40KB, Frames32Size4KB][(byte >> 6) as usize],
+            latin1_or_utf8_only: byte & 0x20 != 0,
+            text_size: [TextSizeRestriction::Unrestricted, Chars1024, Chars128, Chars30][((byte >> 3) & 0x3) as usize],
+            png_or_jpeg_only: byte & 0x04 != 0,
+            image_size: [ImageSizeRestriction::Unrestricted, Max256x256, Max64x64, Exactly64x64][(byte & 0x3) as usize],
+        }
+    }
+
+    /// Returns the payload byte representing the restrictions.
+    pub fn to_byte(&self) -> u8 {
+        (self.tag_size as u8) << 6
+            | (self.latin1_or_utf8_only as u8) << 5
+            | (self.text_size as u8) << 3
+            | (self.png_or_jpeg_only as u8) << 2
+            | self.image_size as u8
+    }
+}
+
+// Tests {{{
+#[cfg(test)]
+mod tests {
+    use super::{TagRestrictions, TagSizeRestriction, TextSizeRestriction, ImageSizeRestriction};
+
+    #[test]
+    fn test_restrictions_byte() {
+        let restrictions = TagRestrictions::from_byte(0b01_1_10_0_11);
+        assert_eq!(restrictions, TagRestrictions {
+            tag_size: TagSizeRestriction::Frames64Size128KB,
+            latin1_or_utf8_only: true,
+            text_size: TextSizeRestriction::Chars128,
+            png_or_jpeg_only: false,
+            image_size: ImageSizeRestriction::Exactly64x64,
+        });
+        assert_eq!(restrictions.to_byte(), 0b01_1_10_0_11);
+
+        for byte in 0..256u32 {
+            assert_eq!(TagRestrictions::from_byte(byte as u8).to_byte(), byte as u8);
+        }
+    }
+}
+// }}}
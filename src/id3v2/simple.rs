@@ -1,7 +1,9 @@
 #![allow(missing_docs, unused, unused_variables)]
 
-use id3v2::Tag;
+use id3v2::{Tag, Version};
+use id3v2::byteorder::{BigEndian, ByteOrder};
 use id3v2::frame::{PictureType, Id, Field, Frame, Encoding};
+use util;
 
 #[derive(Debug, Clone, PartialEq)]
 #[allow(missing_docs)]
@@ -50,6 +52,99 @@ pub struct Picture {
     pub data: Vec<u8>
 }
 
+impl Picture {
+    /// Returns the MIME type of the image data as detected from its magic
+    /// bytes, regardless of the MIME type stored in the frame, or `None` if
+    /// the format is not recognized.
+    ///
+    /// # Example
+    /// ```
+    /// use id3::id3v2::simple::Picture;
+    /// use id3::id3v2::frame::PictureType;
+    ///
+    /// let picture = Picture {
+    ///     mime_type: String::new(),
+    ///     picture_type: PictureType::CoverFront,
+    ///     description: String::new(),
+    ///     data: vec![0xFF, 0xD8, 0xFF, 0xE0],
+    /// };
+    /// assert_eq!(picture.sniff_mime(), Some("image/jpeg"));
+    /// ```
+    pub fn sniff_mime(&self) -> Option<&'static str> {
+        let data = &*self.data;
+        if data.starts_with(b"\x89PNG\r\n\x1A\n") {
+            Some("image/png")
+        } else if data.starts_with(&[0xFF, 0xD8, 0xFF]) {
+            Some("image/jpeg")
+        } else if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") {
+            Some("image/gif")
+        } else if data.starts_with(b"BM") {
+            Some("image/bmp")
+        } else {
+            None
+        }
+    }
+
+    /// Returns the width and height of the image in pixels, as read from the
+    /// header of PNG or JPEG data. Returns `None` for other formats or if the
+    /// data is truncated before the dimensions.
+    pub fn dimensions(&self) -> Option<(u32, u32)> {
+        match self.sniff_mime() {
+            Some("image/png") => png_dimensions(&*self.data),
+            Some("image/jpeg") => jpeg_dimensions(&*self.data),
+            _ => None
+        }
+    }
+}
+
+/// Reads the dimensions from the IHDR chunk, which must immediately follow
+/// the PNG signature.
+fn png_dimensions(data: &[u8]) -> Option<(u32, u32)> {
+    if data.len() < 24 || &data[12..16] != b"IHDR" {
+        return None;
+    }
+    Some((BigEndian::read_u32(&data[16..20]), BigEndian::read_u32(&data[20..24])))
+}
+
+/// Reads the dimensions from the first start of frame (SOFn) segment of JPEG
+/// data.
+fn jpeg_dimensions(data: &[u8]) -> Option<(u32, u32)> {
+    let mut pos = 2;
+    loop {
+        // markers may be preceded by any number of 0xFF fill bytes
+        while data.get(pos) == Some(&0xFF) && data.get(pos + 1) == Some(&0xFF) {
+            pos += 1;
+        }
+        let marker = match (data.get(pos), data.get(pos + 1)) {
+            (Some(&0xFF), Some(&marker)) => marker,
+            _ => return None
+        };
+        pos += 2;
+        match marker {
+            // standalone markers without a length
+            0x01 | 0xD0...0xD7 => continue,
+            // the image data starts or ends before any frame header
+            0xD9 | 0xDA => return None,
+            _ => ()
+        }
+
+        if data.len() < pos + 2 {
+            return None;
+        }
+        let length = BigEndian::read_u16(&data[pos..pos + 2]) as usize;
+        // SOF0-SOF15, other than DHT, JPG, and DAC, which share the range
+        if marker >= 0xC0 && marker <= 0xCF && marker != 0xC4 && marker != 0xC8 && marker != 0xCC {
+            if length < 7 || data.len() < pos + 7 {
+                return None;
+            }
+            let height = BigEndian::read_u16(&data[pos + 3..pos + 5]) as u32;
+            let width = BigEndian::read_u16(&data[pos + 5..pos + 7]) as u32;
+            return Some((width, height));
+        }
+        pos += length;
+    }
+}
+
 
 /// Simple and wrong accessors for simple interpretations of common frames
 pub trait Simple
@@ -58,14 +153,20 @@ pub trait Simple
     fn add_txxx(&mut self, key: &str, value: &str);
     fn add_txxx_enc(&mut self, key: &str, value: &str, encoding: Encoding);
     fn remove_txxx(&mut self, key: Option<&str>, val: Option<&str>);
-    fn pictures(&self) -> Vec<&Picture>;
+    fn remove_wxxx(&mut self, description: Option<&str>, url: Option<&str>);
+    fn pictures(&self) -> Vec<Picture>;
+    fn picture(&self, picture_type: PictureType) -> Option<Picture>;
     fn add_picture(&mut self, mime_type: &str, picture_type: PictureType, data: Vec<u8>);
     fn add_picture_enc(&mut self, mime_type: &str, picture_type: PictureType, description: &str, data: Vec<u8>, encoding: Encoding);
     fn remove_picture_type(&mut self, picture_type: PictureType);
+    fn add_picture_keyed(&mut self, mime_type: &str, picture_type: PictureType, description: &str, data: Vec<u8>, encoding: Encoding);
+    fn remove_picture_keyed(&mut self, picture_type: PictureType, description: &str);
     fn comments(&self) -> Vec<(String, String)>;
     fn add_comment(&mut self, description: &str, text: &str);
     fn add_comment_enc(&mut self, lang: &str, description: &str, text: &str, encoding: Encoding);
     fn remove_comment(&mut self, description: Option<&str>, text: Option<&str>);
+    fn comment(&self, lang: &str, description: &str) -> Option<String>;
+    fn set_comment(&mut self, lang: &str, description: &str, text: &str, encoding: Encoding);
     fn set_artist_enc(&mut self, artist: &str, encoding: Encoding);
     fn set_album_artist_enc(&mut self, album_artist: &str, encoding: Encoding);
     fn set_album_enc(&mut self, album: &str, encoding: Encoding);
@@ -77,7 +178,184 @@ pub trait Simple
     fn track_pair(&self) -> Option<(u32, Option<u32>)>;
     fn set_track_enc(&mut self, track: u32, encoding: Encoding);
     fn set_total_tracks_enc(&mut self, total_tracks: u32, encoding: Encoding);
+    fn set_track(&mut self, track: u32, total_tracks: Option<u32>);
+    fn disc_pair(&self) -> Option<(u32, Option<u32>)>;
+    fn set_disc(&mut self, disc: u32, total_discs: Option<u32>);
+    fn set_disc_enc(&mut self, disc: u32, encoding: Encoding);
+    fn set_total_discs_enc(&mut self, total_discs: u32, encoding: Encoding);
+    fn lyrics(&self) -> Vec<Lyrics>;
     fn set_lyrics_enc(&mut self, lang: &str, description: &str, text: &str, encoding: Encoding);
+    fn lyrics_text(&self, lang: &str, description: &str) -> Option<String>;
+    fn set_lyrics_text(&mut self, lang: &str, description: &str, text: &str, encoding: Encoding);
+    fn isrc(&self) -> Option<String>;
+    fn set_isrc(&mut self, isrc: &str) -> bool;
+}
+
+/// Returns whether a string is a well-formed International Standard Recording
+/// Code: a 2-letter country code, a 3-character registrant code, a 2-digit
+/// year, and a 5-digit designation code, with no separators.
+pub fn is_valid_isrc(isrc: &str) -> bool {
+    let bytes = isrc.as_bytes();
+    bytes.len() == 12 &&
+        bytes[0..2].iter().all(|b| b'A' <= *b && *b <= b'Z') &&
+        bytes[2..5].iter().all(|b| (b'A' <= *b && *b <= b'Z') || (b'0' <= *b && *b <= b'9')) &&
+        bytes[5..12].iter().all(|b| b'0' <= *b && *b <= b'9')
+}
+
+/// Parses a number with an optional total, such as `5` or `5/10`, as stored in
+/// the TRCK and TPOS frames.
+fn number_pair(text: &str) -> Option<(u32, Option<u32>)> {
+    let split: Vec<&str> = text.splitn(2, '/').collect();
+
+    let total = if split.len() == 2 {
+        match split[1].parse() {
+            Ok(total) => Some(total),
+            _ => return None
+        }
+    } else {
+        None
+    };
+
+    match split[0].parse() {
+        Ok(number) => Some((number, total)),
+        _ => None
+    }
+}
+
+/// Formats a number with an optional total as `n/m` or `n`.
+fn number_pair_text(number: u32, total: Option<u32>) -> String {
+    match total {
+        Some(total) => format!("{}/{}", number, total),
+        None => format!("{}", number),
+    }
+}
+
+/// Returns the decoded key and value of a user defined text frame (TXXX).
+fn txxx_pair(frame: &Frame) -> Option<(String, String)> {
+    match &*frame.fields {
+        &[Field::TextEncoding(encoding), Field::String(ref key), Field::String(ref value)] => {
+            match (util::string_from_encoding(encoding, &*key), util::string_from_encoding(encoding, &*value)) {
+                (Some(key), Some(value)) => Some((util::clean_decoded(key), util::clean_decoded(value))),
+                _ => None,
+            }
+        },
+        _ => None,
+    }
+}
+
+/// Creates a picture frame (APIC/PIC) of the given version.
+fn picture_frame(version: Version, mime_type: &str, picture_type: PictureType, description: &str, data: Vec<u8>, encoding: Encoding) -> Frame {
+    let format = match version {
+        Version::V2 => {
+            let format = util::mime_type_format(mime_type);
+            Field::Int24(format[0], format[1], format[2])
+        },
+        Version::V3 | Version::V4 => Field::Latin1(mime_type.as_bytes().to_vec()),
+    };
+    let mut frame = Frame::new(version.picture_id());
+    frame.fields = vec![
+        Field::TextEncoding(encoding),
+        format,
+        Field::Int8(picture_type as u8),
+        Field::String(util::encode_string(description, encoding)),
+        Field::BinaryData(data),
+    ];
+    frame
+}
+
+/// Returns whether a picture frame has the given type and, unless the type is
+/// one of the file icons, which a tag may only hold one of each, the given
+/// decoded description. Frames which cannot be parsed match nothing, so that
+/// keyed changes leave them alone.
+fn picture_matches(frame: &Frame, picture_type: PictureType, description: &str) -> bool {
+    match &*frame.fields {
+        &[Field::TextEncoding(encoding), _, Field::Int8(n), Field::String(ref f_description), _] => {
+            if n != picture_type as u8 {
+                return false;
+            }
+            if picture_type == PictureType::Icon || picture_type == PictureType::OtherIcon {
+                return true;
+            }
+            match util::string_from_encoding(encoding, &*f_description) {
+                Some(f_description) => util::clean_decoded(f_description) == description,
+                None => false,
+            }
+        },
+        _ => false,
+    }
+}
+
+/// Returns the decoded description and URL of a user defined URL link frame
+/// (WXXX).
+fn wxxx_pair(frame: &Frame) -> Option<(String, String)> {
+    match &*frame.fields {
+        &[Field::TextEncoding(encoding), Field::String(ref description), Field::Latin1(ref url)] => {
+            match (util::string_from_encoding(encoding, &*description), util::string_from_latin1(&*url)) {
+                (Some(description), Some(url)) => Some((util::clean_decoded(description), util::clean_decoded(url))),
+                _ => None,
+            }
+        },
+        _ => None,
+    }
+}
+
+/// Returns the language code, decoded description, and decoded text of a
+/// frame keyed by language and description, such as COMM and USLT.
+fn language_keyed_text(frame: &Frame) -> Option<([u8; 3], String, String)> {
+    match &*frame.fields {
+        &[Field::TextEncoding(encoding), Field::Language(lang), Field::String(ref description), Field::StringFull(ref text)] => {
+            match (util::string_from_encoding(encoding, &*description), util::string_from_encoding(encoding, &*text)) {
+                (Some(description), Some(text)) => Some((lang, util::clean_decoded(description), util::clean_decoded(text))),
+                _ => None,
+            }
+        },
+        _ => None,
+    }
+}
+
+/// Returns the text of the first frame with the given ID, language, and
+/// description.
+fn find_language_keyed_text(tag: &Tag, id: Id, lang: &str, description: &str) -> Option<String> {
+    let lang = util::language_code(lang);
+    tag.get_frames_by_id(id).into_iter()
+        .filter_map(|frame| language_keyed_text(frame))
+        .find(|&(frame_lang, ref frame_description, _)| frame_lang == lang && &**frame_description == description)
+        .map(|(_, _, text)| text)
+}
+
+/// Adds a frame with the given ID keyed by language and description,
+/// replacing any frames with the same key.
+fn set_language_keyed_text(tag: &mut Tag, id: Id, lang: &str, description: &str, text: &str, encoding: Encoding) {
+    let lang = util::language_code(lang);
+    tag.frames.retain(|frame| {
+        frame.id != id || match language_keyed_text(frame) {
+            Some((frame_lang, ref frame_description, _)) => frame_lang != lang || &**frame_description != description,
+            None => true
+        }
+    });
+
+    let mut frame = Frame::new(id);
+    frame.fields = vec![
+        Field::TextEncoding(encoding),
+        Field::Language(lang),
+        Field::String(util::encode_string(description, encoding)),
+        Field::StringFull(util::encode_string(text, encoding)),
+    ];
+    tag.frames.push(frame);
+}
+
+/// Returns the decoded contents of an unsynchronized lyrics frame (USLT).
+fn lyrics_content(frame: &Frame) -> Option<Lyrics> {
+    language_keyed_text(frame).map(|(lang, description, text)| Lyrics {
+        lang: String::from_utf8_lossy(&lang).into_owned(),
+        description: description,
+        text: text,
+    })
+}
+
+/// Returns the decoded description and text of a comment frame (COMM).
+fn comment_pair(frame: &Frame) -> Option<(String, String)> {
+    language_keyed_text(frame).map(|(_, description, text)| (description, text))
 }
 
 impl Simple for Tag {
@@ -86,8 +364,7 @@ impl Simple for Tag {
     /// # Example
     /// ```
     /// use id3::id3v2;
-    /// use id3::id3v2::frame::{Frame, Id};
-    /// use id3::id3v2::frame;
+    /// use id3::id3v2::simple::Simple;
     ///
     /// let mut tag = id3v2::Tag::new();
     ///
@@ -99,19 +376,9 @@ impl Simple for Tag {
     /// assert!(tag.txxx().contains(&("key2".to_owned(), "value2".to_owned())));
     /// ```
     fn txxx(&self) -> Vec<(String, String)> {
-        //use std::collections::string::String;
-        let mut out = Vec::new();
-        for frame in self.get_frames_by_id(self.version().txxx_id()).iter() {
-            match &*frame.fields {
-                &[Field::TextEncoding(_encoding), Field::String(ref k), Field::String(ref v)] => {
-                    //TODO(sp3d): convert encoding?
-                    out.push((String::from_utf8(k.clone()).unwrap(), String::from_utf8(v.clone()).unwrap()));
-                },
-                _ => {},
-            }
-        }
-
-        out
+        self.get_frames_by_id(self.version().txxx_id()).iter()
+            .filter_map(|frame| txxx_pair(frame))
+            .collect()
     }
 
     /// Adds a user defined text frame (TXXX).
@@ -119,6 +386,7 @@ impl Simple for Tag {
     /// # Example
     /// ```
     /// use id3::id3v2;
+    /// use id3::id3v2::simple::Simple;
     ///
     /// let mut tag = id3v2::Tag::new();
     ///
@@ -130,7 +398,6 @@ impl Simple for Tag {
     /// assert!(tag.txxx().contains(&("key2".to_owned(), "value2".to_owned())));
     /// ```
     #[inline]
-    //fn add_txxx<E: Encoding>(&mut self, key: EncodedString<E>, value: EncodedString<E>) {
     fn add_txxx(&mut self, key: &str, value: &str) {
         let encoding = self.version().default_encoding();
         self.add_txxx_enc(key, value, encoding);
@@ -141,6 +408,7 @@ impl Simple for Tag {
     /// # Example
     /// ```
     /// use id3::id3v2;
+    /// use id3::id3v2::simple::Simple;
     /// use id3::id3v2::frame::Encoding::UTF16;
     ///
     /// let mut tag = id3v2::Tag::new();
@@ -152,19 +420,15 @@ impl Simple for Tag {
     /// assert!(tag.txxx().contains(&("key1".to_owned(), "value1".to_owned())));
     /// assert!(tag.txxx().contains(&("key2".to_owned(), "value2".to_owned())));
     /// ```
-    //TODO(sp3d): there has to be a better way of dealing with encoded strings!
     fn add_txxx_enc(&mut self, key: &str, value: &str, encoding: Encoding) {
-        let key = key.to_owned();
-
-        self.remove_txxx(Some(&key), None);
+        self.remove_txxx(Some(key), None);
 
         let mut frame = Frame::new(self.version().txxx_id());
-        frame.set_encoding(encoding);
-        //TODO(sp3d): rebuild this on top of fields
-        /*frame.fields = ExtendedTextContent(frame::ExtendedText {
-            key: key,
-            value: value.to_owned()
-        });*/
+        frame.fields = vec![
+            Field::TextEncoding(encoding),
+            Field::String(util::encode_string(key, encoding)),
+            Field::String(util::encode_string(value, encoding)),
+        ];
 
         self.frames.push(frame);
     }
@@ -175,6 +439,7 @@ impl Simple for Tag {
     /// # Example
     /// ```
     /// use id3::id3v2;
+    /// use id3::id3v2::simple::Simple;
     ///
     /// let mut tag = id3v2::Tag::new();
     ///
@@ -204,13 +469,12 @@ impl Simple for Tag {
             let mut val_match = false;
 
             if frame.id == id {
-                match &*frame.fields {
-                    &[Field::TextEncoding(_), Field::String(ref f_key), Field::String(ref f_val)] => {
-                        //TODO(sp3d): checking byte equality is wrong; encodings need to be considered
-                        key_match = key.unwrap_or("").as_bytes() == &**f_key;
-                        val_match = val.unwrap_or("").as_bytes() == &**f_val;
+                match txxx_pair(frame) {
+                    Some((ref f_key, ref f_val)) => {
+                        key_match = key.map_or(true, |s| s == *f_key);
+                        val_match = val.map_or(true, |s| s == *f_val);
                     },
-                    _ => {
+                    None => {
                         // remove frames that we can't parse
                         key_match = true;
                         val_match = true;
@@ -222,55 +486,126 @@ impl Simple for Tag {
         });
     }
 
-    /// Returns a vector of references to the pictures in the tag.
+    /// Removes the user defined URL link frames (WXXX) with the specified
+    /// description and URL. A description or URL may be `None` to specify a
+    /// wildcard value. Descriptions are compared after decoding, so they match
+    /// whatever encoding the frame uses.
     ///
     /// # Example
     /// ```
     /// use id3::id3v2;
-    /// use id3::id3v2::frame::{Frame, Id};
-    /// use id3::id3v2::frame::Picture;
-    /// use id3::Content::PictureContent;
+    /// use id3::id3v2::simple::Simple;
+    /// use id3::id3v2::frame::{Frame, Field, Encoding};
+    ///
+    /// let mut tag = id3v2::Tag::with_version(id3v2::Version::V4);
+    /// for &(description, url) in &[("home", "http://example.com/"), ("shop", "http://example.com/shop")] {
+    ///     let mut frame = Frame::new(tag.version().wxxx_id());
+    ///     frame.fields = vec![Field::TextEncoding(Encoding::UTF8), Field::String(description.as_bytes().to_vec()),
+    ///         Field::Latin1(url.as_bytes().to_vec())];
+    ///     tag.add_frame(frame);
+    /// }
+    ///
+    /// tag.remove_wxxx(Some("home"), None);
+    /// assert_eq!(tag.frames.len(), 1);
+    ///
+    /// tag.remove_wxxx(None, Some("http://example.com/shop"));
+    /// assert!(tag.frames.is_empty());
+    /// ```
+    fn remove_wxxx(&mut self, description: Option<&str>, url: Option<&str>) {
+        let id = self.version().wxxx_id();
+        self.frames.retain(|frame| {
+            if frame.id != id {
+                return true;
+            }
+            match wxxx_pair(frame) {
+                Some((ref f_description, ref f_url)) => {
+                    !(description.map_or(true, |s| s == *f_description) && url.map_or(true, |s| s == *f_url))
+                },
+                // remove frames that we can't parse
+                None => false,
+            }
+        });
+    }
+
+    /// Returns a vector of the pictures in the tag. For ID3v2.2 picture frames
+    /// (PIC), the MIME type is derived from the 3-character image format.
+    ///
+    /// # Example
+    /// ```
+    /// use id3::id3v2;
+    /// use id3::id3v2::simple::Simple;
+    /// use id3::id3v2::frame::{Frame, Field, Id, Encoding, PictureType};
     ///
     /// let mut tag = id3v2::Tag::new();
     ///
     /// let mut frame = Frame::new(Id::V4(*b"APIC"));
-    /// let picture = Picture {
-    ///     mime_type: String::new(),
-    ///     picture_type: PictureType::Other,
-    ///     description: String::new(),
-    ///     data: Vec::new()
-    /// };
-    ////
-    /// let picture = Picture {
-    ///     mime_type: String::new(),
-    ///     picture_type: PictureType::Other,
-    ///     description: String::new(),
-    ///     data: Vec::new()
-    /// };
-    /// 
-    /// let mut frame = Frame::new(Id::V4(*b"APIC"));
-    /// frame.fields = PictureContent(picture);
+    /// frame.fields = vec![
+    ///     Field::TextEncoding(Encoding::Latin1),
+    ///     Field::Latin1(b"image/png".to_vec()),
+    ///     Field::Int8(PictureType::CoverFront as u8),
+    ///     Field::String(b"front".to_vec()),
+    ///     Field::BinaryData(vec![0x89, b'P', b'N', b'G']),
+    /// ];
     /// tag.add_frame(frame);
     ///
-    /// assert_eq!(tag.pictures().len(), 2);
+    /// let pictures = tag.pictures();
+    /// assert_eq!(pictures.len(), 1);
+    /// assert_eq!(&pictures[0].mime_type, "image/png");
+    /// assert_eq!(pictures[0].picture_type, PictureType::CoverFront);
+    /// assert_eq!(&pictures[0].description, "front");
     /// ```
-    fn pictures(&self) -> Vec<&Picture> {
-        //TODO(sp3d): rebuild this on top of fields
+    fn pictures(&self) -> Vec<Picture> {
         let mut pictures = Vec::new();
         for frame in self.get_frames_by_id(self.version().picture_id()).iter() {
-            match &frame.fields {
-                _ => { }
-            }
+            let (encoding, mime_type, picture_type, description, data) = match &*frame.fields {
+                &[Field::TextEncoding(encoding), ref format, Field::Int8(picture_type), Field::String(ref description), Field::BinaryData(ref data)] => {
+                    let mime_type = match format {
+                        &Field::Latin1(ref mime_type) => util::string_from_encoding(Encoding::Latin1, &*mime_type),
+                        &Field::Int24(a, b, c) => Some(util::format_mime_type(&[a, b, c])),
+                        _ => None,
+                    };
+                    (encoding, mime_type, picture_type, description, data)
+                },
+                _ => continue,
+            };
+            let (mime_type, description) = match (mime_type, util::string_from_encoding(encoding, &*description)) {
+                (Some(mime_type), Some(description)) => (mime_type, description),
+                _ => continue,
+            };
+            pictures.push(Picture {
+                mime_type: mime_type,
+                picture_type: PictureType::from_u8(picture_type).unwrap_or(PictureType::Other),
+                description: description,
+                data: data.clone(),
+            });
         }
         pictures
     }
 
+    /// Returns the first picture (APIC) of the given type, if any.
+    ///
+    /// # Example
+    /// ```
+    /// use id3::id3v2;
+    /// use id3::id3v2::simple::Simple;
+    /// use id3::id3v2::frame::PictureType::{CoverFront, CoverBack};
+    ///
+    /// let mut tag = id3v2::Tag::new();
+    /// tag.add_picture("image/jpeg", CoverFront, vec![0xFF, 0xD8]);
+    /// assert_eq!(tag.picture(CoverFront).unwrap().mime_type, "image/jpeg");
+    /// assert!(tag.picture(CoverBack).is_none());
+    /// ```
+    fn picture(&self, picture_type: PictureType) -> Option<Picture> {
+        self.pictures().into_iter().find(|picture| picture.picture_type == picture_type)
+    }
+
     /// Adds a picture frame (APIC).
     /// Any other pictures with the same type will be removed from the tag.
     ///
     /// # Example
     /// ```
     /// use id3::id3v2;
+    /// use id3::id3v2::simple::Simple;
     /// use id3::id3v2::frame::PictureType::Other;
     ///
     /// let mut tag = id3v2::Tag::new();
@@ -290,6 +625,7 @@ impl Simple for Tag {
     /// # Example
     /// ```
     /// use id3::id3v2;
+    /// use id3::id3v2::simple::Simple;
     /// use id3::id3v2::frame::PictureType::Other;
     /// use id3::id3v2::frame::Encoding::UTF16;
     ///
@@ -300,22 +636,42 @@ impl Simple for Tag {
     /// assert_eq!(&tag.pictures()[0].mime_type, "image/png");
     /// ```
     fn add_picture_enc(&mut self, mime_type: &str, picture_type: PictureType, description: &str, data: Vec<u8>, encoding: Encoding) {
-        //TODO(sp3d): rebuild this on top of fields
-        /*
         self.remove_picture_type(picture_type);
+        let frame = picture_frame(self.version(), mime_type, picture_type, description, data, encoding);
+        self.frames.push(frame);
+    }
 
-        let mut frame = Frame::new(self.version().picture_id());
-
-        frame.set_encoding(encoding);
-        frame.fields = PictureContent(Picture {
-            mime_type: mime_type.to_owned(),
-            picture_type: picture_type,
-            description: description.to_owned(),
-            data: data
-        });
-
+    /// Adds a picture frame (APIC) using the specified text encoding,
+    /// replacing only a picture with the same type and description. The
+    /// standard allows several pictures of a type as long as their
+    /// descriptions differ, except for the file icons, of which there may only
+    /// be one each; an icon replaces any other icon of its type.
+    ///
+    /// # Example
+    /// ```
+    /// use id3::id3v2;
+    /// use id3::id3v2::simple::Simple;
+    /// use id3::id3v2::frame::PictureType::CoverFront;
+    /// use id3::id3v2::frame::Encoding::UTF8;
+    ///
+    /// let mut tag = id3v2::Tag::new();
+    /// tag.add_picture_keyed("image/jpeg", CoverFront, "large", vec![1], UTF8);
+    /// tag.add_picture_keyed("image/jpeg", CoverFront, "small", vec![2], UTF8);
+    /// tag.add_picture_keyed("image/png", CoverFront, "small", vec![3], UTF8);
+    /// assert_eq!(tag.pictures().len(), 2);
+    /// ```
+    fn add_picture_keyed(&mut self, mime_type: &str, picture_type: PictureType, description: &str, data: Vec<u8>, encoding: Encoding) {
+        self.remove_picture_keyed(picture_type, description);
+        let frame = picture_frame(self.version(), mime_type, picture_type, description, data, encoding);
         self.frames.push(frame);
-        */
+    }
+
+    /// Removes the pictures with the specified type and description, or for
+    /// the file icon types, all pictures of the type. Descriptions are
+    /// compared after decoding.
+    fn remove_picture_keyed(&mut self, picture_type: PictureType, description: &str) {
+        let id = self.version().picture_id();
+        self.frames.retain(|frame| frame.id != id || !picture_matches(frame, picture_type, description));
     }
 
     /// Removes all pictures of the specified type.
@@ -323,6 +679,7 @@ impl Simple for Tag {
     /// # Example
     /// ```
     /// use id3::id3v2;
+    /// use id3::id3v2::simple::Simple;
     /// use id3::id3v2::frame::PictureType::{CoverFront, Other};
     ///
     /// let mut tag = id3v2::Tag::new();
@@ -338,13 +695,11 @@ impl Simple for Tag {
         let id = self.version().picture_id();
         self.frames.retain(|frame| {
             if frame.id == id {
-                match &frame.fields {
-                    //TODO(sp3d): rebuild this on top of fields
-                    //PictureContent(ref picture) => picture,
-                    _ => return false
+                return match frame.fields.get(2) {
+                    Some(&Field::Int8(n)) => n != picture_type as u8,
+                    // remove frames that we can't parse
+                    _ => false
                 };
-
-                return false/*pic.picture_type != picture_type*/
             }
 
             true
@@ -356,26 +711,27 @@ impl Simple for Tag {
     /// # Example
     /// ```
     /// use id3::id3v2;
-    /// use id3::id3v2::frame::{Frame, Id};
-    /// use id3::id3v2::frame;
-    /// use id3::Content::CommentContent;
+    /// use id3::id3v2::simple::Simple;
+    /// use id3::id3v2::frame::{Frame, Field, Id, Encoding};
     ///
     /// let mut tag = id3v2::Tag::new();
     ///
     /// let mut frame = Frame::new(Id::V4(*b"COMM"));
-    /// frame.fields = CommentContent(frame::Comment {
-    ///     lang: "eng".to_owned(),
-    ///     description: "key1".to_owned(),
-    ///     text: "value1".to_owned()
-    /// });
+    /// frame.fields = vec![
+    ///     Field::TextEncoding(Encoding::UTF8),
+    ///     Field::Language(*b"eng"),
+    ///     Field::String(b"key1".to_vec()),
+    ///     Field::StringFull(b"value1".to_vec()),
+    /// ];
     /// tag.add_frame(frame);
     ///
     /// let mut frame = Frame::new(Id::V4(*b"COMM"));
-    /// frame.fields = CommentContent(frame::Comment {
-    ///     lang: "eng".to_owned(),
-    ///     description: "key2".to_owned(),
-    ///     text: "value2".to_owned()
-    /// });
+    /// frame.fields = vec![
+    ///     Field::TextEncoding(Encoding::UTF8),
+    ///     Field::Language(*b"eng"),
+    ///     Field::String(b"key2".to_vec()),
+    ///     Field::StringFull(b"value2".to_vec()),
+    /// ];
     /// tag.add_frame(frame);
     ///
     /// assert_eq!(tag.comments().len(), 2);
@@ -383,24 +739,18 @@ impl Simple for Tag {
     /// assert!(tag.comments().contains(&("key2".to_owned(), "value2".to_owned())));
     /// ```
     fn comments(&self) -> Vec<(String, String)> {
-        let mut out = Vec::new();
-        for frame in self.get_frames_by_id(self.version().comment_id()).iter() {
-            match &frame.fields {
-                //TODO(sp3d): rebuild this on top of fields
-                /*CommentContent(ref comment) => out.push((comment.description.clone(),
-                                                         comment.text.clone())),*/
-                _ => { }
-            }
-        }
-
-        out
+        self.get_frames_by_id(self.version().comment_id()).iter()
+            .filter_map(|frame| comment_pair(frame))
+            .collect()
     }
 
-    /// Adds a user comment frame (COMM).
+    /// Adds an English user comment frame (COMM), replacing any English
+    /// comment with the same description.
     ///
     /// # Example
     /// ```
     /// use id3::id3v2;
+    /// use id3::id3v2::simple::Simple;
     ///
     /// let mut tag = id3v2::Tag::new();
     ///
@@ -417,11 +767,13 @@ impl Simple for Tag {
         self.add_comment_enc("eng", description, text, encoding);
     }
 
-    /// Adds a user comment frame (COMM) using the specified text encoding.
+    /// Adds a user comment frame (COMM) using the specified text encoding,
+    /// replacing any comment with the same language and description.
     ///
     /// # Example
     /// ```
     /// use id3::id3v2;
+    /// use id3::id3v2::simple::Simple;
     /// use id3::id3v2::frame::Encoding::UTF16;
     ///
     /// let mut tag = id3v2::Tag::new();
@@ -433,22 +785,9 @@ impl Simple for Tag {
     /// assert!(tag.comments().contains(&("key1".to_owned(), "value1".to_owned())));
     /// assert!(tag.comments().contains(&("key2".to_owned(), "value2".to_owned())));
     /// ```
+    #[inline]
     fn add_comment_enc(&mut self, lang: &str, description: &str, text: &str, encoding: Encoding) {
-        let description = description.to_owned();
-
-        self.remove_comment(Some(&description), None);
-
-        let mut frame = Frame::new(self.version().comment_id());
-
-        //TODO(sp3d): rebuild this on top of fields
-        /*frame.set_encoding(encoding);
-        frame.fields = CommentContent(frame::Comment {
-            lang: lang.to_owned(),
-            description: description,
-            text: text.to_owned()
-        });*/
-
-        self.frames.push(frame);
+        self.set_comment(lang, description, text, encoding);
     }
 
     /// Removes the user comment frame (COMM) with the specified key and value.
@@ -457,6 +796,7 @@ impl Simple for Tag {
     /// # Example
     /// ```
     /// use id3::id3v2;
+    /// use id3::id3v2::simple::Simple;
     ///
     /// let mut tag = id3v2::Tag::new();
     ///
@@ -486,21 +826,12 @@ impl Simple for Tag {
             let mut text_match = false;
 
             if frame.id == id {
-                match &frame.fields {
-                    //TODO(sp3d): rebuild this on top of fields
-                    /*
-                    CommentContent(ref comment) =>  {
-                        match description {
-                            Some(s) => description_match = s == &comment.description(),
-                            None => description_match = true
-                        }
-
-                        match text {
-                            Some(s) => text_match = s == &comment.text,
-                            None => text_match = true,
-                        }
-                    },*/
-                    _ => { // remove frames that we can't parse
+                match comment_pair(frame) {
+                    Some((ref comment_description, ref comment_text)) => {
+                        description_match = description.map_or(true, |s| s == *comment_description);
+                        text_match = text.map_or(true, |s| s == *comment_text);
+                    },
+                    None => { // remove frames that we can't parse
                         description_match = true;
                         text_match = true;
                     }
@@ -511,6 +842,34 @@ impl Simple for Tag {
         });
     }
 
+    /// Returns the text of the user comment frame (COMM) with the specified
+    /// language and description, if present.
+    ///
+    /// # Example
+    /// ```
+    /// use id3::id3v2;
+    /// use id3::id3v2::simple::Simple;
+    /// use id3::id3v2::frame::Encoding::UTF8;
+    ///
+    /// let mut tag = id3v2::Tag::new();
+    /// tag.set_comment("eng", "note", "in English", UTF8);
+    /// tag.set_comment("deu", "note", "auf Deutsch", UTF8);
+    ///
+    /// assert_eq!(tag.comment("deu", "note"), Some("auf Deutsch".to_owned()));
+    /// assert_eq!(tag.comment("fra", "note"), None);
+    /// ```
+    fn comment(&self, lang: &str, description: &str) -> Option<String> {
+        find_language_keyed_text(self, self.version().comment_id(), lang, description)
+    }
+
+    /// Sets the text of the user comment frame (COMM) with the specified
+    /// language and description, leaving comments with other languages or
+    /// descriptions in place.
+    fn set_comment(&mut self, lang: &str, description: &str, text: &str, encoding: Encoding) {
+        let id = self.version().comment_id();
+        set_language_keyed_text(self, id, lang, description, text, encoding);
+    }
+
     /// Sets the artist (TPE1) using the specified text encoding.
     ///
     /// # Example
@@ -605,7 +964,8 @@ impl Simple for Tag {
         self.add_text_frame_enc(id, genre, encoding);
     }
 
-    /// Returns the year (TYER).
+    /// Returns the year (TYER), or in ID3v2.4 tags without one, the year of the
+    /// recording time (TDRC).
     /// Returns `None` if the year frame could not be found or if it could not be parsed.
     ///
     /// # Example
@@ -629,9 +989,9 @@ impl Simple for Tag {
     /// ```
     fn year(&self) -> Option<usize> {
         let id = self.version().year_id();
-        match self.text_frame_text(id) {
-            Some(ref text) => text.parse().ok(),
-            _ => None,
+        match self.text_frame_str(id) {
+            Some(text) => text.parse().ok(),
+            _ => self.recording_time().map(|time| time.year as usize),
         }
     }
 
@@ -670,26 +1030,7 @@ impl Simple for Tag {
 
     /// Returns the (track, total_tracks) tuple.
     fn track_pair(&self) -> Option<(u32, Option<u32>)> {
-        match self.text_frame_text(self.version().track_id()) {
-            Some(ref text) => {
-                let split: Vec<&str> = text.splitn(2, '/').collect();
-
-                let total_tracks = if split.len() == 2 {
-                    match split[1].parse() {
-                        Ok(total_tracks) => Some(total_tracks),
-                        _ => return None
-                    }
-                } else {
-                    None
-                };
-
-                match split[0].parse() {
-                    Ok(track) => Some((track, total_tracks)),
-                    _ => None
-                }
-            },
-            None => None
-        }
+        self.text_frame_str(self.version().track_id()).and_then(|text| number_pair(&*text))
     }
 
     /// Sets the track number (TRCK) using the specified text encoding.
@@ -736,33 +1077,488 @@ impl Simple for Tag {
         self.add_text_frame_enc(id, &text, encoding);
     }
 
+    /// Sets the track number and, if given, the total number of tracks (TRCK),
+    /// replacing both. Uses the default encoding of the tag's version.
+    ///
+    /// # Example
+    /// ```
+    /// use id3::id3v2;
+    /// use id3::id3v2::simple::Simple;
+    ///
+    /// let mut tag = id3v2::Tag::new();
+    /// tag.set_track(5, Some(10));
+    /// assert_eq!(tag.track_pair(), Some((5, Some(10))));
+    /// tag.set_track(6, None);
+    /// assert_eq!(tag.track_pair(), Some((6, None)));
+    /// ```
+    fn set_track(&mut self, track: u32, total_tracks: Option<u32>) {
+        let id = self.version().track_id();
+        let encoding = self.version().default_encoding();
+        self.add_text_frame_enc(id, &*number_pair_text(track, total_tracks), encoding);
+    }
+
+    /// Returns the (disc, total_discs) tuple of the part of a set (TPOS).
+    fn disc_pair(&self) -> Option<(u32, Option<u32>)> {
+        self.text_frame_str(self.version().disc_id()).and_then(|text| number_pair(&*text))
+    }
+
+    /// Sets the disc number and, if given, the total number of discs (TPOS),
+    /// replacing both. Uses the default encoding of the tag's version.
+    ///
+    /// # Example
+    /// ```
+    /// use id3::id3v2;
+    /// use id3::id3v2::simple::Simple;
+    ///
+    /// let mut tag = id3v2::Tag::new();
+    /// tag.set_disc(1, Some(2));
+    /// assert_eq!(tag.disc_pair(), Some((1, Some(2))));
+    /// ```
+    fn set_disc(&mut self, disc: u32, total_discs: Option<u32>) {
+        let id = self.version().disc_id();
+        let encoding = self.version().default_encoding();
+        self.add_text_frame_enc(id, &*number_pair_text(disc, total_discs), encoding);
+    }
+
+    /// Sets the disc number (TPOS) using the specified text encoding, keeping
+    /// any total number of discs.
+    ///
+    /// # Example
+    /// ```
+    /// use id3::id3v2;
+    /// use id3::id3v2::simple::Simple;
+    /// use id3::id3v2::frame::Encoding::UTF16;
+    ///
+    /// let mut tag = id3v2::Tag::new();
+    /// tag.set_disc_enc(2, UTF16);
+    /// assert_eq!(tag.disc_pair(), Some((2, None)));
+    /// ```
+    fn set_disc_enc(&mut self, disc: u32, encoding: Encoding) {
+        let total_discs = self.disc_pair().and_then(|(_, total_discs)| total_discs);
+        let id = self.version().disc_id();
+        self.add_text_frame_enc(id, &*number_pair_text(disc, total_discs), encoding);
+    }
 
-    /// Sets the lyrics text (USLT) using the specified text encoding.
+    /// Sets the total number of discs (TPOS) using the specified text encoding,
+    /// keeping the disc number, or setting it to 1 if there is none.
     ///
     /// # Example
     /// ```
     /// use id3::id3v2;
+    /// use id3::id3v2::simple::Simple;
     /// use id3::id3v2::frame::Encoding::UTF16;
-    /// use id3::FileTags;
     ///
-    /// let mut tag = FileTags::from_tags(None, Some(id3v2::Tag::new()));
-    /// tag.v2.as_mut().unwrap().set_lyrics_enc("eng", "description", "lyrics", UTF16);
-    /// assert_eq!(&tag.lyrics().unwrap(), "lyrics");
+    /// let mut tag = id3v2::Tag::new();
+    /// tag.set_total_discs_enc(2, UTF16);
+    /// assert_eq!(tag.disc_pair(), Some((1, Some(2))));
+    /// ```
+    fn set_total_discs_enc(&mut self, total_discs: u32, encoding: Encoding) {
+        let disc = self.disc_pair().map(|(disc, _)| disc).unwrap_or(1);
+        let id = self.version().disc_id();
+        self.add_text_frame_enc(id, &*number_pair_text(disc, Some(total_discs)), encoding);
+    }
+
+
+    /// Returns the lyrics (USLT) in the tag, one entry per language.
+    ///
+    /// # Example
+    /// ```
+    /// use id3::id3v2;
+    /// use id3::id3v2::simple::Simple;
+    /// use id3::id3v2::frame::Encoding::UTF8;
+    ///
+    /// let mut tag = id3v2::Tag::new();
+    /// tag.set_lyrics_enc("eng", "", "lyrics", UTF8);
+    /// tag.set_lyrics_enc("deu", "", "Liedtext", UTF8);
+    /// assert_eq!(tag.lyrics().len(), 2);
+    /// assert_eq!(&tag.lyrics()[1].lang, "deu");
+    /// assert_eq!(&tag.lyrics()[1].text, "Liedtext");
+    /// ```
+    fn lyrics(&self) -> Vec<Lyrics> {
+        self.get_frames_by_id(self.version().lyrics_id()).iter()
+            .filter_map(|frame| lyrics_content(frame))
+            .collect()
+    }
+
+    /// Sets the lyrics text (USLT) using the specified text encoding. Any
+    /// other lyrics in the same language will be removed from the tag.
+    ///
+    /// # Example
+    /// ```
+    /// use id3::id3v2;
+    /// use id3::id3v2::simple::Simple;
+    /// use id3::id3v2::frame::Encoding::UTF16;
+    ///
+    /// let mut tag = id3v2::Tag::new();
+    /// tag.set_lyrics_enc("eng", "description", "old lyrics", UTF16);
+    /// tag.set_lyrics_enc("eng", "description", "lyrics", UTF16);
+    /// assert_eq!(tag.lyrics().len(), 1);
+    /// assert_eq!(&tag.lyrics()[0].text, "lyrics");
     /// ```
     fn set_lyrics_enc(&mut self, lang: &str, description: &str, text: &str, encoding: Encoding) {
         let id = self.version().lyrics_id();
-        self.remove_frames_by_id(id);
+        let lang = util::language_code(lang);
+        self.frames.retain(|frame| {
+            if frame.id == id {
+                return match frame.fields.get(1) {
+                    Some(&Field::Language(frame_lang)) => frame_lang != lang,
+                    // remove frames that we can't parse
+                    _ => false
+                };
+            }
 
-        let mut frame = Frame::new(id);
+            true
+        });
 
-        frame.set_encoding(encoding);
-        //TODO(sp3d): rebuild this on top of fields
-        /*frame.fields = LyricsContent(frame::Lyrics {
-            lang: lang.to_owned(),
-            description: description.to_owned(),
-            text: text.to_owned()
-        });*/
+        let mut frame = Frame::new(id);
+        frame.fields = vec![
+            Field::TextEncoding(encoding),
+            Field::Language(lang),
+            Field::String(util::encode_string(description, encoding)),
+            Field::StringFull(util::encode_string(text, encoding)),
+        ];
 
         self.frames.push(frame);
     }
+
+    /// Returns the text of the lyrics frame (USLT) with the specified language
+    /// and description, if present.
+    ///
+    /// # Example
+    /// ```
+    /// use id3::id3v2;
+    /// use id3::id3v2::simple::Simple;
+    /// use id3::id3v2::frame::Encoding::UTF8;
+    ///
+    /// let mut tag = id3v2::Tag::new();
+    /// tag.set_lyrics_text("eng", "verse", "lyrics", UTF8);
+    /// tag.set_lyrics_text("eng", "chorus", "more lyrics", UTF8);
+    ///
+    /// assert_eq!(tag.lyrics_text("eng", "chorus"), Some("more lyrics".to_owned()));
+    /// assert_eq!(tag.lyrics().len(), 2);
+    /// ```
+    fn lyrics_text(&self, lang: &str, description: &str) -> Option<String> {
+        find_language_keyed_text(self, self.version().lyrics_id(), lang, description)
+    }
+
+    /// Sets the text of the lyrics frame (USLT) with the specified language
+    /// and description, leaving lyrics with other languages or descriptions
+    /// in place.
+    fn set_lyrics_text(&mut self, lang: &str, description: &str, text: &str, encoding: Encoding) {
+        let id = self.version().lyrics_id();
+        set_language_keyed_text(self, id, lang, description, text, encoding);
+    }
+
+    /// Returns the International Standard Recording Code (TSRC), if present
+    /// and well-formed.
+    ///
+    /// # Example
+    /// ```
+    /// use id3::id3v2;
+    /// use id3::id3v2::simple::Simple;
+    ///
+    /// let mut tag = id3v2::Tag::new();
+    /// assert!(tag.isrc().is_none());
+    ///
+    /// assert!(tag.set_isrc("USRC17607839"));
+    /// assert_eq!(tag.isrc().unwrap(), "USRC17607839");
+    /// ```
+    fn isrc(&self) -> Option<String> {
+        match self.text_frame_str(self.version().isrc_id()) {
+            Some(ref text) if is_valid_isrc(text) => Some(text.clone().into_owned()),
+            _ => None,
+        }
+    }
+
+    /// Sets the International Standard Recording Code (TSRC). Returns `false`
+    /// and leaves the tag unmodified if the code is malformed.
+    ///
+    /// # Example
+    /// ```
+    /// use id3::id3v2;
+    /// use id3::id3v2::simple::Simple;
+    ///
+    /// let mut tag = id3v2::Tag::new();
+    /// assert!(!tag.set_isrc("US-RC1-76-07839"));
+    /// assert!(!tag.set_isrc("usrc17607839"));
+    /// assert!(!tag.set_isrc("USRC1760783"));
+    /// assert!(tag.isrc().is_none());
+    /// ```
+    fn set_isrc(&mut self, isrc: &str) -> bool {
+        if !is_valid_isrc(isrc) {
+            return false;
+        }
+        let id = self.version().isrc_id();
+        self.add_text_frame_enc(id, isrc, Encoding::Latin1);
+        true
+    }
+}
+
+// Tests {{{
+#[cfg(test)]
+mod tests {
+    use id3v2::Tag;
+    use id3v2::tests::round_trip;
+    use id3v2::Version::*;
+    use id3v2::simple::{Simple, Picture};
+    use id3v2::frame::{Frame, Field, Encoding, PictureType};
+    use util;
+
+    #[test]
+    fn test_pictures_v2() {
+        let mut tag = Tag::with_version(V2);
+        let mut frame = Frame::new(V2.picture_id());
+        frame.fields = vec![
+            Field::TextEncoding(Encoding::UTF16),
+            Field::Int24(b'J', b'P', b'G'),
+            Field::Int8(PictureType::CoverBack as u8),
+            Field::String(util::string_to_utf16("back")),
+            Field::BinaryData(vec![0xFF, 0xD8]),
+        ];
+        tag.add_frame(frame);
+
+        let pictures = tag.pictures();
+        assert_eq!(pictures.len(), 1);
+        assert_eq!(&pictures[0].mime_type, "image/jpeg");
+        assert_eq!(pictures[0].picture_type, PictureType::CoverBack);
+        assert_eq!(&pictures[0].description, "back");
+        assert_eq!(pictures[0].data, vec![0xFF, 0xD8]);
+    }
+
+    fn picture(data: Vec<u8>) -> Picture {
+        Picture { mime_type: String::new(), picture_type: PictureType::Other, description: String::new(), data: data }
+    }
+
+    #[test]
+    fn test_picture_png() {
+        let mut png = b"\x89PNG\r\n\x1A\n\0\0\0\x0DIHDR".to_vec();
+        png.extend(&[0, 0, 0x01, 0x2C, 0, 0, 0, 0xC8, 8, 6, 0, 0, 0]);
+        assert_eq!(picture(png.clone()).sniff_mime(), Some("image/png"));
+        assert_eq!(picture(png.clone()).dimensions(), Some((300, 200)));
+
+        png.truncate(22);
+        assert_eq!(picture(png).dimensions(), None);
+    }
+
+    #[test]
+    fn test_picture_jpeg() {
+        let mut jpeg = vec![0xFF, 0xD8];
+        // an APP0 segment, fill bytes, and a baseline start of frame segment
+        jpeg.extend(&[0xFF, 0xE0, 0, 16, b'J', b'F', b'I', b'F', 0, 1, 1, 0, 0, 1, 0, 1, 0, 0]);
+        jpeg.extend(&[0xFF, 0xFF, 0xC0, 0, 11, 8, 0x01, 0xE0, 0x02, 0x80, 1, 1, 0x11, 0]);
+        assert_eq!(picture(jpeg.clone()).sniff_mime(), Some("image/jpeg"));
+        assert_eq!(picture(jpeg.clone()).dimensions(), Some((640, 480)));
+
+        for len in 0..jpeg.len() - 5 {
+            assert_eq!(picture(jpeg[..len].to_vec()).dimensions(), None);
+        }
+        assert_eq!(picture(vec![0xFF, 0xD8, 0xFF, 0xDA, 0, 2]).dimensions(), None);
+        assert_eq!(picture(b"GIF89a".to_vec()).sniff_mime(), Some("image/gif"));
+        assert_eq!(picture(b"GIF89a".to_vec()).dimensions(), None);
+        assert_eq!(picture(vec![1, 2, 3]).sniff_mime(), None);
+    }
+
+    #[test]
+    fn test_picture_by_type() {
+        let mut tag = Tag::with_version(V3);
+        tag.add_picture("image/jpeg", PictureType::CoverFront, vec![1]);
+        tag.add_picture("image/png", PictureType::CoverBack, vec![2]);
+
+        let front = tag.picture(PictureType::CoverFront).unwrap();
+        assert_eq!(&*front.mime_type, "image/jpeg");
+        assert_eq!(front.data, vec![1]);
+        let back = tag.picture(PictureType::CoverBack).unwrap();
+        assert_eq!(&*back.mime_type, "image/png");
+        assert_eq!(back.data, vec![2]);
+        assert!(tag.picture(PictureType::Artist).is_none());
+    }
+
+    #[test]
+    fn test_add_picture_round_trip() {
+        let png = vec![0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+        for &(version, encoding) in &[(V2, Encoding::Latin1), (V3, Encoding::UTF16), (V4, Encoding::UTF8)] {
+            let mut tag = Tag::with_version(version);
+            tag.add_picture_enc("image/png", PictureType::CoverFront, "cover", png.clone(), encoding);
+
+            let reread = round_trip(&tag);
+
+            let pictures = reread.pictures();
+            assert_eq!(pictures.len(), 1);
+            assert_eq!(&pictures[0].mime_type, "image/png");
+            assert_eq!(pictures[0].picture_type, PictureType::CoverFront);
+            assert_eq!(&pictures[0].description, "cover");
+            assert_eq!(pictures[0].data, png);
+        }
+    }
+
+    #[test]
+    fn test_comments_round_trip() {
+        for &(version, encoding) in &[(V2, Encoding::Latin1), (V3, Encoding::UTF16), (V4, Encoding::UTF16BE)] {
+            let mut tag = Tag::with_version(version);
+            tag.add_comment_enc("eng", "key1", "value1", encoding);
+            tag.add_comment_enc("deu", "key2", "value2", encoding);
+
+            let mut reread = round_trip(&tag);
+            assert_eq!(reread.comments(), vec![
+                ("key1".to_owned(), "value1".to_owned()),
+                ("key2".to_owned(), "value2".to_owned()),
+            ]);
+
+            reread.remove_comment(None, Some("value2"));
+            assert_eq!(reread.comments(), vec![("key1".to_owned(), "value1".to_owned())]);
+        }
+    }
+
+    #[test]
+    fn test_comments_by_language() {
+        for &(version, encoding) in &[(V2, Encoding::UTF16), (V3, Encoding::Latin1), (V4, Encoding::UTF8)] {
+            let mut tag = Tag::with_version(version);
+            tag.set_comment("deu", "", "Kommentar", encoding);
+            tag.add_comment("", "comment");
+            tag.add_comment("", "new comment");
+            tag.set_comment("deu", "other", "anderer Kommentar", encoding);
+
+            let mut reread = round_trip(&tag);
+            assert_eq!(reread.comments().len(), 3);
+            assert_eq!(reread.comment("eng", ""), Some("new comment".to_owned()));
+            assert_eq!(reread.comment("deu", ""), Some("Kommentar".to_owned()));
+            assert_eq!(reread.comment("deu", "other"), Some("anderer Kommentar".to_owned()));
+
+            reread.set_comment("deu", "", "neuer Kommentar", encoding);
+            assert_eq!(reread.comments().len(), 3);
+            assert_eq!(reread.comment("deu", ""), Some("neuer Kommentar".to_owned()));
+            assert_eq!(reread.comment("eng", ""), Some("new comment".to_owned()));
+
+            reread.set_lyrics_text("eng", "", "lyrics", encoding);
+            reread.set_lyrics_text("deu", "", "Liedtext", encoding);
+            assert_eq!(reread.lyrics_text("eng", ""), Some("lyrics".to_owned()));
+            assert_eq!(reread.lyrics_text("deu", ""), Some("Liedtext".to_owned()));
+        }
+    }
+
+    #[test]
+    fn test_txxx_round_trip() {
+        for &(version, encoding) in &[(V3, Encoding::Latin1), (V3, Encoding::UTF16), (V4, Encoding::UTF8)] {
+            let mut tag = Tag::with_version(version);
+            tag.add_txxx_enc("key1", "value1", encoding);
+            tag.add_txxx_enc("key2", "value2", encoding);
+
+            let mut reread = round_trip(&tag);
+            assert_eq!(reread.txxx().len(), 2);
+            assert!(reread.txxx().contains(&("key1".to_owned(), "value1".to_owned())));
+            assert!(reread.txxx().contains(&("key2".to_owned(), "value2".to_owned())));
+
+            reread.add_txxx_enc("key1", "changed", Encoding::UTF16);
+            reread.remove_txxx(None, Some("value2"));
+            assert_eq!(reread.txxx(), vec![("key1".to_owned(), "changed".to_owned())]);
+        }
+    }
+
+    #[test]
+    fn test_pictures_keyed() {
+        for &version in &[V2, V3, V4] {
+            let mut tag = Tag::with_version(version);
+            tag.add_picture_keyed("image/jpeg", PictureType::CoverFront, "front", vec![1], Encoding::UTF16);
+            tag.add_picture_keyed("image/jpeg", PictureType::CoverFront, "front, small", vec![2], Encoding::UTF16);
+            tag.add_picture_keyed("image/png", PictureType::Icon, "one", vec![3], Encoding::Latin1);
+            tag.add_picture_keyed("image/png", PictureType::Icon, "two", vec![4], Encoding::Latin1);
+
+            let mut tag = round_trip(&tag);
+            let mut data: Vec<_> = tag.pictures().into_iter().map(|picture| picture.data).collect();
+            data.sort();
+            assert_eq!(data, vec![vec![1], vec![2], vec![4]]);
+
+            tag.add_picture_keyed("image/png", PictureType::CoverFront, "front", vec![5], Encoding::Latin1);
+            tag.remove_picture_keyed(PictureType::CoverFront, "front, small");
+            let pictures = tag.pictures();
+            let covers: Vec<_> = pictures.iter().filter(|picture| picture.picture_type == PictureType::CoverFront).collect();
+            assert_eq!(covers.len(), 1);
+            assert_eq!((&*covers[0].description, &*covers[0].data), ("front", &[5u8][..]));
+
+            tag.remove_picture_type(PictureType::CoverFront);
+            assert_eq!(tag.pictures().len(), 1);
+        }
+    }
+
+    #[test]
+    fn test_pictures_keyed_keep_unparseable() {
+        let mut tag = Tag::with_version(V3);
+        let mut frame = Frame::new(V3.picture_id());
+        frame.fields = vec![Field::BinaryData(vec![1, 2, 3])];
+        tag.add_frame(frame);
+        let mut frame = Frame::new(V3.picture_id());
+        frame.fields = vec![
+            Field::TextEncoding(Encoding::UTF8),
+            Field::Latin1(b"image/png".to_vec()),
+            Field::Int8(PictureType::CoverFront as u8),
+            Field::String(vec![0xFF]),
+            Field::BinaryData(vec![4]),
+        ];
+        tag.add_frame(frame);
+
+        tag.add_picture_keyed("image/png", PictureType::CoverFront, "front", vec![5], Encoding::Latin1);
+        tag.remove_picture_keyed(PictureType::CoverFront, "front");
+        assert_eq!(tag.get_frames_by_id(V3.picture_id()).len(), 2);
+    }
+
+    #[test]
+    fn test_remove_keyed_utf16() {
+        let mut tag = Tag::with_version(V3);
+        let mut frame = Frame::new(V3.txxx_id());
+        frame.fields = vec![
+            Field::TextEncoding(Encoding::UTF16),
+            Field::String(b"\xFE\xFF\x00k\x00e\x00y".to_vec()),
+            Field::String(b"\xFE\xFF\x00v\x00a\x00l\x00u\x00e".to_vec()),
+        ];
+        tag.add_frame(frame);
+        tag.add_txxx_enc("other", "value", Encoding::Latin1);
+
+        tag.remove_txxx(Some("key"), None);
+        assert_eq!(tag.txxx(), vec![("other".to_owned(), "value".to_owned())]);
+
+        let mut frame = Frame::new(V3.wxxx_id());
+        frame.fields = vec![
+            Field::TextEncoding(Encoding::UTF16),
+            Field::String(util::string_to_utf16("home")),
+            Field::Latin1(b"http://example.com/".to_vec()),
+        ];
+        tag.add_frame(frame);
+        tag.remove_wxxx(Some("away"), None);
+        assert_eq!(tag.get_frames_by_id(V3.wxxx_id()).len(), 1);
+        tag.remove_wxxx(Some("home"), Some("http://example.com/"));
+        assert!(tag.get_frames_by_id(V3.wxxx_id()).is_empty());
+
+        tag.add_comment_enc("eng", "note", "text", Encoding::UTF16);
+        tag.remove_comment(Some("note"), None);
+        assert!(tag.comments().is_empty());
+    }
+
+    #[test]
+    fn test_isrc_round_trip() {
+        for &version in &[V2, V3, V4] {
+            let mut tag = Tag::with_version(version);
+            assert!(tag.set_isrc("USRC17607839"));
+            assert!(!tag.set_isrc("usrc17607839"));
+
+            let reread = round_trip(&tag);
+            assert_eq!(reread.isrc(), Some("USRC17607839".to_owned()));
+        }
+    }
+
+    #[test]
+    fn test_lyrics_round_trip() {
+        for &(version, encoding) in &[(V2, Encoding::UTF16), (V3, Encoding::Latin1), (V4, Encoding::UTF8)] {
+            let mut tag = Tag::with_version(version);
+            tag.set_lyrics_enc("eng", "verse", "Lyrics\nin English", encoding);
+
+            let reread = round_trip(&tag);
+
+            let lyrics = reread.lyrics();
+            assert_eq!(lyrics.len(), 1);
+            assert_eq!(&lyrics[0].lang, "eng");
+            assert_eq!(&lyrics[0].description, "verse");
+            assert_eq!(&lyrics[0].text, "Lyrics\nin English");
+        }
+    }
 }
+// }}}
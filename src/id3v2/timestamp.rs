@@ -0,0 +1,124 @@
+use std::fmt;
+
+/// A timestamp as stored in ID3v2.4 time frames such as TDRC (recording
+/// time): a subset of ISO 8601 from `yyyy` up to `yyyy-MM-ddTHH:mm:ss`, in
+/// which everything after the year may be left out, starting from any field.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct Timestamp {
+    /// The year.
+    pub year: u16,
+    /// The month, from 1 to 12, if specified.
+    pub month: Option<u8>,
+    /// The day of the month, from 1 to 31, if specified.
+    pub day: Option<u8>,
+    /// The hour, from 0 to 23, if specified.
+    pub hour: Option<u8>,
+    /// The minute, from 0 to 59, if specified.
+    pub minute: Option<u8>,
+    /// The second, from 0 to 59, if specified.
+    pub second: Option<u8>,
+}
+
+impl Timestamp {
+    /// Parses a timestamp such as `2014`, `2014-03`, or `2014-03-21T18:30:00`.
+    /// Returns `None` if the text is not in the timestamp format or a field is
+    /// out of range.
+    ///
+    /// # Example
+    /// ```
+    /// use id3::id3v2::Timestamp;
+    ///
+    /// let timestamp = Timestamp::parse("2014-03").unwrap();
+    /// assert_eq!(timestamp.year, 2014);
+    /// assert_eq!(timestamp.month, Some(3));
+    /// assert_eq!(timestamp.day, None);
+    /// ```
+    pub fn parse(text: &str) -> Option<Timestamp> {
+        let text = text.as_bytes();
+        let year = match number(text, 4) {
+            Some(year) => year as u16,
+            None => return None
+        };
+        let mut timestamp = Timestamp { year: year, month: None, day: None, hour: None, minute: None, second: None };
+
+        // each remaining field is a separator followed by two digits
+        let mut rest = &text[4..];
+        let fields: [(u8, u8, u8); 5] = [(b'-', 1, 12), (b'-', 1, 31), (b'T', 0, 23), (b':', 0, 59), (b':', 0, 59)];
+        for (i, &(separator, min, max)) in fields.iter().enumerate() {
+            if rest.is_empty() {
+                break;
+            }
+            if rest[0] != separator {
+                return None;
+            }
+            let value = match number(&rest[1..], 2) {
+                Some(value) if min as u32 <= value && value <= max as u32 => value as u8,
+                _ => return None
+            };
+            match i {
+                0 => timestamp.month = Some(value),
+                1 => timestamp.day = Some(value),
+                2 => timestamp.hour = Some(value),
+                3 => timestamp.minute = Some(value),
+                _ => timestamp.second = Some(value),
+            }
+            rest = &rest[3..];
+        }
+
+        if rest.is_empty() { Some(timestamp) } else { None }
+    }
+}
+
+/// Parses the first `len` bytes of the text as decimal digits.
+fn number(text: &[u8], len: usize) -> Option<u32> {
+    if text.len() < len || !text[..len].iter().all(|&b| b'0' <= b && b <= b'9') {
+        return None;
+    }
+    Some(text[..len].iter().fold(0, |n, &b| n * 10 + (b - b'0') as u32))
+}
+
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        try!(write!(f, "{:04}", self.year));
+        let fields = [("-", self.month), ("-", self.day), ("T", self.hour), (":", self.minute), (":", self.second)];
+        for &(separator, value) in fields.iter() {
+            match value {
+                Some(value) => try!(write!(f, "{}{:02}", separator, value)),
+                None => break
+            }
+        }
+        Ok(())
+    }
+}
+
+// Tests {{{
+#[cfg(test)]
+mod tests {
+    use super::Timestamp;
+
+    #[test]
+    fn test_parse() {
+        let year = Timestamp::parse("2014").unwrap();
+        assert_eq!(year, Timestamp { year: 2014, month: None, day: None, hour: None, minute: None, second: None });
+
+        let month = Timestamp::parse("2014-03").unwrap();
+        assert_eq!((month.year, month.month, month.day), (2014, Some(3), None));
+
+        let full = Timestamp::parse("2014-03-21T18:30:05").unwrap();
+        assert_eq!(full, Timestamp {
+            year: 2014, month: Some(3), day: Some(21), hour: Some(18), minute: Some(30), second: Some(5)
+        });
+
+        for text in ["2014", "2014-03", "2014-03-21T18", "2014-03-21T18:30:05"].iter() {
+            assert_eq!(Timestamp::parse(text).unwrap().to_string(), *text);
+        }
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        for text in ["", "14", "2014-3", "2014-13", "2014/03", "2014-03-21 18:30", "2014-03-21T24", "2014-03-", "2014 "].iter() {
+            assert!(Timestamp::parse(text).is_none(), "{}", text);
+        }
+    }
+}
+// }}}
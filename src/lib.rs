@@ -9,7 +9,7 @@
 //! let mut path = &std::path::Path::new("music.mp3");
 //! let mut tags = FileTags::from_path(path).unwrap();
 //!
-//! // print the artist
+//! // print the album
 //! println!("{}", tags.v2.as_ref().unwrap().text_frame_text(Id::V4(*b"TALB")).unwrap());
 //!
 //! ```
@@ -53,8 +53,11 @@ pub mod id3v1;
 pub mod id3v2;
 
 mod filetags;
+mod genre;
 
 /// Common functionality for handling ID3 tags in files.
-//pub use filetags::FileTags;
+pub use filetags::FileTags;
+/// The genres identified by ID3v1 genre numbers.
+pub use genre::Genre;
 
 mod parsers;
@@ -1,3 +1,5 @@
+use std::io;
+
 use id3v2::frame::field::Field;
 use id3v2::frame::{self, Frame, Id, Encoding};
 use id3v2::Version;
@@ -38,8 +40,11 @@ pub fn encode(request: EncoderRequest) -> Vec<u8> {
     encoded
 }
 
-/// Attempts to decode the request.
+/// Attempts to decode the request. If a field cannot be parsed, the error
+/// describes the field type, the frame ID, and the offset of the field in the
+/// frame's content.
 pub fn decode(mut request: DecoderRequest) -> Result<Frame, Error> {
+    let data_len = request.data.len();
     let mut encoding = None;//request.encoding;
     let mut fields = vec![];
     let field_types = match frame::frame_format(request.id) {
@@ -51,6 +56,7 @@ pub fn decode(mut request: DecoderRequest) -> Result<Frame, Error> {
         None => 0 as *const _,
     };
     for ftype in field_types.iter() {
+        let offset = data_len - request.data.len();
         let out: Option<&mut Vec<u8>> = None;
         match Field::parse(&mut request.data, *ftype, encoding, request.data.len(), ftype as *const _ == last, out) {
             Ok(field) => {
@@ -62,7 +68,12 @@ pub fn decode(mut request: DecoderRequest) -> Result<Frame, Error> {
                 }
                 fields.push(field)
             },
-            Err(what) => {println!("{}", what); return Err(::std::convert::From::from(what))},
+            Err(what) => {
+                let what = io::Error::new(what.kind(), format!("invalid {} field in {} at byte {}: {}",
+                    ftype.name(), String::from_utf8_lossy(request.id.name()), offset, what));
+                debug!("{}", what);
+                return Err(::std::convert::From::from(what))
+            },
         }
     }
     let mut frame = Frame::new(request.id);
@@ -431,5 +442,17 @@ mod tests {
             }).is_err());
         }
     }
+
+    #[test]
+    fn test_error_context() {
+        let err = parsers::decode(DecoderRequest { id: V3(*b"APIC"), data: &[7, b'i', 0] }).unwrap_err();
+        let message = err.io_error().to_string();
+        assert!(message.contains("textencoding field in APIC at byte 0"), message);
+        assert!(message.contains("0x07"), message);
+
+        let err = parsers::decode(DecoderRequest { id: V4(*b"COMM"), data: b"\0engdescription" }).unwrap_err();
+        let message = err.io_error().to_string();
+        assert!(message.contains("encoded string field in COMM at byte 4"), message);
+    }
 }
 // }}}
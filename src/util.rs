@@ -2,7 +2,7 @@
 extern crate std;
 
 use id3v2::frame::Encoding;
-use std::mem::transmute;
+use std::io::{self, Read};
 use std::string;
 
 macro_rules! static_arr(($ty: ty, $vals: expr) => {{ const _F: &'static [$ty] = & $vals; _F }});
@@ -29,7 +29,7 @@ macro_rules! read_all_vec {
 macro_rules! read_all {
     ($reader:expr, $buf:expr) => {
         {
-            let len = try!($reader.read($buf));
+            let len = try!(::util::read_fully($reader, $buf));
             if len < $buf.len() {
                 return Err(::std::io::Error::new(::std::io::ErrorKind::InvalidInput, "unexpected end of stream").into())
             } else {}
@@ -39,7 +39,7 @@ macro_rules! read_all {
 macro_rules! read_at_least {
     ($reader:expr, $buf:expr, $min_len:expr) => {
         {
-            let len = try!($reader.read($buf));
+            let len = try!(::util::read_fully($reader, $buf));
             if len < $min_len {
                 return Err(::std::io::Error::new(::std::io::ErrorKind::InvalidInput, "unexpected end of stream").into())
             } else {len}
@@ -47,6 +47,23 @@ macro_rules! read_at_least {
     };
 }
 
+/// Reads from the reader until the buffer is full or the end of the stream is
+/// reached, returning the number of bytes read. Unlike a single call to
+/// `Read::read`, this is not cut short by readers which return fewer bytes
+/// than requested, such as pipes, sockets, and decompressors.
+pub fn read_fully<R: Read + ?Sized>(reader: &mut R, buf: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {},
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
+}
+
 /// Undoes the changes done to a byte buffer by the unsynchronization scheme.
 pub fn resynchronize(buffer: &mut Vec<u8>) {
     let mut discard_next_null_byte = false;
@@ -60,53 +77,122 @@ pub fn resynchronize(buffer: &mut Vec<u8>) {
     }
 }
 
-/// Applies the unsynchronization scheme to a byte buffer.
-pub fn unsynchronize(buffer: &mut Vec<u8>) {
-    let mut repeat_next_null_byte = false;
-    let mut i = 0;
-    while i < buffer.len() {
-        if buffer[i] == 0x00 && repeat_next_null_byte {
-            buffer.insert(i, 0);
-            i += 1;
+/// A reader which undoes the unsynchronization scheme on the bytes read from
+/// an underlying reader, dropping each zero byte which follows a 0xFF byte.
+pub struct ResyncReader<R> {
+    inner: R,
+    last_was_ff: bool,
+}
+
+impl<R: Read> ResyncReader<R> {
+    /// Creates a reader resynchronizing the bytes read from `inner`.
+    pub fn new(inner: R) -> ResyncReader<R> {
+        ResyncReader { inner: inner, last_was_ff: false }
+    }
+}
+
+impl<R: Read> Read for ResyncReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        // keep reading until the buffer is full, so that callers expecting a
+        // single read to fill it are not confused by dropped bytes
+        let mut filled = 0;
+        while filled < buf.len() {
+            let n = try!(self.inner.read(&mut buf[filled..]));
+            if n == 0 {
+                break
+            }
+            for i in filled..filled + n {
+                let byte = buf[i];
+                if self.last_was_ff && byte == 0x00 {
+                    self.last_was_ff = false;
+                    continue
+                }
+                self.last_was_ff = byte == 0xFF;
+                buf[filled] = byte;
+                filled += 1;
+            }
         }
-        repeat_next_null_byte = buffer[i] == 0xFF;
-        i += 1;
+        Ok(filled)
     }
 }
 
-//TODO: finish making this linear-time instead of quadratic
-/*/// Applies the unsynchronization scheme to a byte buffer.
-pub fn unsynchronize2(buffer: &mut Vec<u8>) {
-    let mut repeat_next_null_byte = false;
-    let unsync_count = buffer.iter().sum(|b| {
-        let inc = (buffer[i] == 0x00 && repeat_next_null_byte) as u32;
-        repeat_next_null_byte = buffer[i] == 0xFF;
-        inc
-    });
-    
-    let mut i = 0;
-    while i < buffer.len() {
-        if buffer[i] == 0x00 && repeat_next_null_byte {
-            buffer.insert(i, 0);
-            i += 1;
+/// Applies the unsynchronization scheme to a byte buffer, inserting a zero
+/// byte after each 0xFF byte which is followed by a zero byte or by a byte
+/// which would form a false MPEG sync (0b111xxxxx).
+pub fn unsynchronize(buffer: &mut Vec<u8>) {
+    let mut unsynced = Vec::with_capacity(buffer.len());
+    let mut last_was_ff = false;
+    for &byte in buffer.iter() {
+        if last_was_ff && (byte == 0x00 || byte & 0xE0 == 0xE0) {
+            unsynced.push(0x00);
         }
-        repeat_next_null_byte = buffer[i] == 0xFF;
-        i += 1;
+        unsynced.push(byte);
+        last_was_ff = byte == 0xFF;
+    }
+    *buffer = unsynced;
+}
+
+/// Returns the ID3v2.2 image format corresponding to a MIME type, such as
+/// `JPG` for `image/jpeg`.
+pub fn mime_type_format(mime_type: &str) -> [u8; 3] {
+    let subtype = mime_type.splitn(2, '/').last().unwrap_or("");
+    let subtype = if subtype == "jpeg" { "JPG".to_owned() } else { subtype.to_uppercase() };
+    let mut format = [b' '; 3];
+    for (byte, c) in format.iter_mut().zip(subtype.bytes()) {
+        *byte = c;
+    }
+    format
+}
+
+/// Returns the ISO-639-2 language code field for a language string,
+/// truncated or padded with spaces to 3 bytes.
+pub fn language_code(lang: &str) -> [u8; 3] {
+    let mut code = [b' '; 3];
+    for (byte, c) in code.iter_mut().zip(lang.bytes()) {
+        *byte = c;
+    }
+    code
+}
+
+/// Returns the MIME type corresponding to an ID3v2.2 image format, such as
+/// `image/jpeg` for `JPG`.
+pub fn format_mime_type(format: &[u8; 3]) -> String {
+    match format {
+        b"JPG" => "image/jpeg".to_owned(),
+        _ => format!("image/{}", String::from_utf8_lossy(format).trim_right().to_lowercase()),
     }
-}*/
+}
+
+/// Removes byte order marks (U+FEFF) anywhere in a decoded string, as left behind
+/// by taggers which concatenate UTF-16 strings, along with any trailing nul characters.
+pub fn clean_decoded(s: string::String) -> string::String {
+    let mut out: string::String = s.chars().filter(|&c| c != '\u{FEFF}').collect();
+    while out.ends_with('\0') {
+        out.pop();
+    }
+    out
+}
 
 /// Returns the converted to the given encoding. Characters which could not be
 /// represented in the target encoding are replaced with U+FFFD or '?'.
 pub fn encode_string(s: &str, encoding: Encoding) -> Vec<u8> {
     match encoding {
-        //TODO(sp3d): properly encode Latin1
-        Encoding::Latin1 => s.to_owned().into_bytes(),
+        Encoding::Latin1 => string_to_latin1(s),
         Encoding::UTF8 => s.as_bytes().to_vec(),
         Encoding::UTF16 => string_to_utf16(s),
         Encoding::UTF16BE => string_to_utf16be(s) 
     }
 }
 
+/// Returns whether every character of the string can be represented in the
+/// given encoding, so that `encode_string` will not replace any.
+pub fn is_encodable(s: &str, encoding: Encoding) -> bool {
+    match encoding {
+        Encoding::Latin1 => s.chars().all(|c| (c as u32) <= 0xFF),
+        Encoding::UTF8 | Encoding::UTF16 | Encoding::UTF16BE => true,
+    }
+}
+
 /// Returns the synchsafe variant of a `u32` value.
 #[inline]
 pub fn synchsafe(n: u32) -> u32 {
@@ -116,12 +202,32 @@ pub fn synchsafe(n: u32) -> u32 {
     x
 }
 
+/// Returns the synchsafe variant of a `u32` value, or `None` if the value
+/// needs more than the 28 bits a synchsafe `u32` can hold.
+#[inline]
+pub fn synchsafe_checked(n: u32) -> Option<u32> {
+    if n < 0x10000000 { Some(synchsafe(n)) } else { None }
+}
+
 /// Returns the unsynchsafe variant of a `u32` value.
 #[inline]
 pub fn unsynchsafe(n: u32) -> u32 {
     (n & 0xFF | (n & 0xFF00) >> 1 | (n & 0xFF0000) >> 2 | (n & 0xFF000000) >> 3)
 }
 
+/// Computes the CRC-32 of the data as ISO 3309 and zlib do, which is how
+/// ID3v2 extended headers checksum a tag's frames.
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB88320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
 /// Returns an array representation of a `u32` value.
 #[inline]
 pub fn u32_to_bytes(n: u32) -> [u8; 4] {
@@ -138,31 +244,66 @@ pub fn u32_to_bytes(n: u32) -> [u8; 4] {
 #[inline]
 pub fn string_from_encoding(encoding: Encoding, data: &[u8]) -> Option<string::String> {
     match encoding {
-        Encoding::Latin1 | Encoding::UTF8 => string_from_utf8(data),
-        Encoding::UTF16 => string_from_utf16(data),
+        Encoding::Latin1 => string_from_latin1(data),
+        Encoding::UTF8 => string_from_utf8(data),
+        Encoding::UTF16 => string_from_utf16_lenient(data),
         Encoding::UTF16BE => string_from_utf16be(data) 
     }
 }
 
+/// Returns a string created from the vector using ISO-8859-1 encoding, in
+/// which each byte is the code point of a character, removing any trailing
+/// nul bytes. Every byte sequence is valid ISO-8859-1, so this never returns
+/// `None`.
+pub fn string_from_latin1(data: &[u8]) -> Option<string::String> {
+    Some(trim_trailing_nuls(data).iter().map(|&c| c as char).collect())
+}
+
+/// Returns the data without any nul bytes at its end. Nul bytes elsewhere are
+/// kept, as ID3v2.4 uses them to separate the values of text frames.
+fn trim_trailing_nuls(data: &[u8]) -> &[u8] {
+    let len = data.iter().rposition(|&c| c != 0).map_or(0, |i| i + 1);
+    &data[..len]
+}
+
 /// Returns a string created from the vector using UTF-8 encoding, removing any
 /// trailing nul bytes.
 /// Returns `None` if the vector is not a valid UTF-8 string.
 pub fn string_from_utf8(data: &[u8]) -> Option<string::String> {
-    let data: Vec<u8> = data.iter().take_while(|&c| *c != 0).map(|c| *c).collect();
-    string::String::from_utf8(data).ok()
+    string::String::from_utf8(trim_trailing_nuls(data).to_vec()).ok()
 }
 
 /// Returns a string created from the vector using UTF-16 (with byte order mark) encoding.
-/// Returns `None` if the vector is not a valid UTF-16 string.
+/// Returns `None` if the vector does not start with a UTF-16 byte order mark or
+/// is not a valid UTF-16 string.
 pub fn string_from_utf16(data: &[u8]) -> Option<string::String> {
     if data.len() < 2 || data.len() % 2 != 0 { 
         return None;
     }
 
-    if data[0] == 0xFF && data[1] == 0xFE { // little endian
-        string_from_utf16le(&data[2..])
-    } else { // big endian
-        string_from_utf16be(&data[2..])
+    match (data[0], data[1]) {
+        (0xFF, 0xFE) => string_from_utf16le(&data[2..]),
+        (0xFE, 0xFF) => string_from_utf16be(&data[2..]),
+        _ => None
+    }
+}
+
+/// Returns a string created from the vector using UTF-16 encoding, which
+/// should start with a byte order mark but may not, as some taggers omit it.
+/// Without a byte order mark, the text is taken to be big endian if more of
+/// its zero bytes are at even offsets than odd ones, as is the case for
+/// mostly ASCII text, and little endian otherwise.
+/// Returns `None` if the vector is not a valid UTF-16 string.
+pub fn string_from_utf16_lenient(data: &[u8]) -> Option<string::String> {
+    if let Some((Encoding::UTF16, _)) = Encoding::from_bom(data) {
+        return string_from_utf16(data);
+    }
+
+    let zeros_at = |start: usize| data.iter().skip(start).step_by(2).filter(|&&b| b == 0).count();
+    if zeros_at(0) > zeros_at(1) {
+        string_from_utf16be(data)
+    } else {
+        string_from_utf16le(data)
     }
 }
 
@@ -173,18 +314,8 @@ pub fn string_from_utf16le(data: &[u8]) -> Option<string::String> {
         return None;
     }
 
-    if cfg!(target_endian = "little") {
-        let buf = unsafe { transmute::<_, &[u16]>(data) };
-        string::String::from_utf16(&buf[..data.len() / 2]).ok()
-    } else {
-        let mut buf: Vec<u16> = Vec::with_capacity(data.len() / 2);
-
-        for i in 0..(data.len() / 2) {
-            buf.push(data[2*i] as u16 | ((data[2*i + 1] as u16) << 8));
-        }
-
-        string::String::from_utf16(&*buf).ok()
-    }
+    let buf: Vec<u16> = data.chunks_exact(2).map(|c| u16::from_le_bytes([c[0], c[1]])).collect();
+    string::String::from_utf16(&*buf).ok()
 }
 
 /// Returns a string created from the vector using UTF-16BE encoding.
@@ -193,18 +324,14 @@ pub fn string_from_utf16be(data: &[u8]) -> Option<string::String> {
     if data.len() % 2 != 0 { 
         return None;
     }
-    if cfg!(target_endian = "big") {
-        let buf = unsafe { transmute::<_, &[u16]>(data) };
-        string::String::from_utf16(&buf[..data.len() / 2]).ok()
-    } else {
-        let mut buf: Vec<u16> = Vec::with_capacity(data.len() / 2);
-
-        for i in 0..(data.len()/2) {
-            buf.push((data[i*2] as u16) << 8 | data[i*2 + 1] as u16);
-        }
+    let buf: Vec<u16> = data.chunks_exact(2).map(|c| u16::from_be_bytes([c[0], c[1]])).collect();
+    string::String::from_utf16(&*buf).ok()
+}
 
-        string::String::from_utf16(&*buf).ok()
-    }
+/// Returns an ISO-8859-1 vector representation of the string. Characters
+/// beyond U+00FF, which ISO-8859-1 cannot represent, are replaced with '?'.
+pub fn string_to_latin1(text: &str) -> Vec<u8> {
+    text.chars().map(|c| if (c as u32) <= 0xFF { c as u8 } else { b'?' }).collect()
 }
 
 /// Returns a UTF-16 (with native byte order) vector representation of the string.
@@ -274,6 +401,15 @@ mod tests {
         assert_eq!(176994, util::unsynchsafe(681570));
     }
 
+    #[test]
+    fn test_synchsafe_checked() {
+        assert_eq!(util::synchsafe_checked(176994), Some(681570));
+        assert_eq!(util::synchsafe_checked(0x0FFFFFFF), Some(0x7F7F7F7F));
+        assert_eq!(util::unsynchsafe(0x7F7F7F7F), 0x0FFFFFFF);
+        assert_eq!(util::synchsafe_checked(0x10000000), None);
+        assert_eq!(util::synchsafe_checked(0xFFFFFFFF), None);
+    }
+
     #[test]
     fn test_strings() {
         let text: &str = "śốмễ śŧŗỉňĝ";
@@ -302,6 +438,79 @@ mod tests {
         assert_eq!(&*util::string_from_utf16(b"\xFF\xFE\x5B\x01\xD1\x1E\x3C\x04\xC5\x1E\x20\x00\x5B\x01\x67\x01\x57\x01\xC9\x1E\x48\x01\x1D\x01").unwrap(), text);
     }
 
+    #[test]
+    fn test_utf16_without_bom() {
+        let be = b"\0a\0b\0c";
+        let le = b"a\0b\0c\0";
+        assert_eq!(util::string_from_utf16(be), None);
+        assert_eq!(util::string_from_utf16(le), None);
+        assert_eq!(&*util::string_from_utf16_lenient(be).unwrap(), "abc");
+        assert_eq!(&*util::string_from_utf16_lenient(le).unwrap(), "abc");
+        assert_eq!(&*util::string_from_encoding(Encoding::UTF16, be).unwrap(), "abc");
+        assert_eq!(&*util::string_from_encoding(Encoding::UTF16, le).unwrap(), "abc");
+
+        assert_eq!(Encoding::from_bom(be), None);
+        assert_eq!(Encoding::from_bom(b"\xFF\xFEa\0"), Some((Encoding::UTF16, 2)));
+        assert_eq!(Encoding::from_bom(b"\xEF\xBB\xBFa"), Some((Encoding::UTF8, 3)));
+        assert_eq!(&*util::string_from_utf16_lenient(b"\xFE\xFF\0a").unwrap(), "a");
+        assert_eq!(util::string_from_utf16_lenient(b"a\0b"), None);
+    }
+
+    #[test]
+    fn test_latin1() {
+        assert_eq!(&*util::string_to_latin1("café"), b"caf\xE9");
+        assert_eq!(&*util::encode_string("café", Encoding::Latin1), b"caf\xE9");
+        assert_eq!(&*util::string_from_latin1(b"caf\xE9\0").unwrap(), "café");
+        assert_eq!(&*util::string_from_latin1(b"one\0two\0\0").unwrap(), "one\0two");
+        assert_eq!(&*util::string_from_encoding(Encoding::Latin1, b"caf\xE9").unwrap(), "café");
+        assert_eq!(&*util::string_to_latin1("śốмễ"), b"????");
+    }
+
+    #[test]
+    fn test_utf16_misaligned() {
+        for offset in 0..2 {
+            let mut buffer = vec![0u8; offset];
+            buffer.extend(util::string_to_utf16le("śốмễ text").into_iter());
+            assert_eq!(&*util::string_from_utf16le(&buffer[offset..]).unwrap(), "śốмễ text");
+
+            let mut buffer = vec![0u8; offset];
+            buffer.extend(util::string_to_utf16be("śốмễ text").into_iter());
+            assert_eq!(&*util::string_from_utf16be(&buffer[offset..]).unwrap(), "śốмễ text");
+            assert!(util::string_from_utf16be(&buffer[offset..buffer.len() - 1]).is_none());
+        }
+    }
+
+    #[test]
+    fn test_clean_decoded() {
+        assert_eq!(util::clean_decoded("\u{FEFF}first\u{FEFF}second\0\0".to_owned()), "firstsecond");
+        assert_eq!(util::clean_decoded("plain".to_owned()), "plain");
+
+        let mut data = vec![0xFF, 0xFE];
+        data.extend(util::string_to_utf16le("album").into_iter());
+        data.extend(&[0xFF, 0xFE]);
+        data.extend(util::string_to_utf16le(" two").into_iter());
+        let decoded = util::string_from_utf16(&*data).unwrap();
+        assert_eq!(util::clean_decoded(decoded), "album two");
+    }
+
+    #[test]
+    fn test_resync_reader() {
+        let raw = [0x01, 0xFF, 0x00, 0xE0, 0xFF, 0x00, 0x00, 0xFF, 0x00, 0xFF, 0x00];
+        let mut expected = raw.to_vec();
+        util::resynchronize(&mut expected);
+
+        let mut resynced = vec![];
+        util::ResyncReader::new(&raw[..]).read_to_end(&mut resynced).unwrap();
+        assert_eq!(resynced, expected);
+        assert_eq!(resynced, [0x01, 0xFF, 0xE0, 0xFF, 0x00, 0xFF, 0xFF]);
+    }
+
+    #[test]
+    fn test_crc32() {
+        assert_eq!(util::crc32(b""), 0);
+        assert_eq!(util::crc32(b"123456789"), 0xCBF43926);
+    }
+
     #[test]
     fn test_u32_to_bytes() {
         assert_eq!(util::u32_to_bytes(0x4B92DF71), [0x4B as u8, 0x92 as u8, 0xDF as u8, 0x71 as u8]);
@@ -0,0 +1,114 @@
+extern crate id3;
+
+use id3::id3v2;
+use id3::id3v2::Version::*;
+use id3::id3v2::frame::{Frame, Id, Encoding};
+use id3::id3v2::simple::Simple;
+
+static ID: Id = Id::V4(*b"TPOS");
+static DISC: u32 = 1;
+static TOTAL: u32 = 2;
+static INVALID: &'static str = "invalid";
+
+// UTF8 {{{
+#[test]
+fn utf8() {
+    let mut v2 = id3v2::Tag::with_version(V4);
+
+    v2.set_disc_enc(DISC, Encoding::UTF8);
+    v2.set_total_discs_enc(TOTAL, Encoding::UTF8);
+    assert_eq!(v2.text_frame_text(ID), Some(format!("{}/{}", DISC, TOTAL)));
+    assert_eq!(v2.disc_pair(), Some((DISC, Some(TOTAL))));
+
+    let frame = v2.get_frame_by_id(ID).unwrap();
+
+    let mut data: Vec<u8> = Vec::new();
+    data.push(Encoding::UTF8 as u8);
+    data.extend(format!("{}/{}", DISC, TOTAL).into_bytes().into_iter());
+    assert_eq!(frame.fields_to_bytes(), data);
+}
+
+#[test]
+fn utf8_only_disc() {
+    let mut v2 = id3v2::Tag::with_version(V4);
+
+    v2.set_disc_enc(DISC, Encoding::UTF8);
+    assert_eq!(v2.text_frame_text(ID), Some(format!("{}", DISC)));
+    assert_eq!(v2.disc_pair(), Some((DISC, None)));
+
+    let frame = v2.get_frame_by_id(ID).unwrap();
+
+    let mut data: Vec<u8> = Vec::new();
+    data.push(Encoding::UTF8 as u8);
+    data.extend(format!("{}", DISC).into_bytes().into_iter());
+    assert_eq!(frame.fields_to_bytes(), data);
+}
+
+#[test]
+fn utf8_invalid() {
+    let mut v2 = id3v2::Tag::with_version(V4);
+
+    let mut frame = Frame::new(ID);
+    let mut data = Vec::new();
+    data.push(Encoding::UTF8 as u8);
+    data.extend(format!("{}/{}", INVALID, TOTAL).into_bytes().into_iter());
+    frame.fields = frame.parse_fields(&*data).unwrap();
+    v2.add_frame(frame);
+    assert_eq!(v2.disc_pair(), None);
+
+    v2.remove_frames_by_id(ID);
+
+    let mut frame = Frame::new(ID);
+    let mut data = Vec::new();
+    data.push(Encoding::UTF8 as u8);
+    data.extend(format!("{}/{}", DISC, INVALID).into_bytes().into_iter());
+    frame.fields = frame.parse_fields(&*data).unwrap();
+    v2.add_frame(frame);
+    assert_eq!(v2.disc_pair(), None);
+}
+//}}}
+
+// UTF16 {{{
+#[test]
+fn utf16() {
+    let mut v2 = id3v2::Tag::with_version(V4);
+
+    v2.set_disc_enc(DISC, Encoding::UTF16);
+    v2.set_total_discs_enc(TOTAL, Encoding::UTF16);
+    assert_eq!(v2.text_frame_text(ID), Some(format!("{}/{}", DISC, TOTAL)));
+    let frame = v2.get_frame_by_id(ID).unwrap();
+
+    let mut data = Vec::new();
+    data.push(Encoding::UTF16 as u8);
+    data.extend(id3::util::string_to_utf16(&*format!("{}/{}", DISC, TOTAL)).into_iter());
+    assert_eq!(frame.fields_to_bytes(), data);
+}
+
+#[test]
+fn utf16_total_keeps_disc() {
+    let mut v2 = id3v2::Tag::with_version(V3);
+
+    v2.set_disc_enc(3, Encoding::UTF16);
+    v2.set_total_discs_enc(4, Encoding::UTF16);
+    v2.set_disc_enc(2, Encoding::UTF16);
+    assert_eq!(v2.text_frame_text(Id::V3(*b"TPOS")), Some("2/4".to_owned()));
+    assert_eq!(v2.disc_pair(), Some((2, Some(4))));
+}
+//}}}
+
+// UTF16BE {{{
+#[test]
+fn utf16be() {
+    let mut v2 = id3v2::Tag::with_version(V4);
+
+    v2.set_disc_enc(DISC, Encoding::UTF16BE);
+    v2.set_total_discs_enc(TOTAL, Encoding::UTF16BE);
+    assert_eq!(v2.text_frame_text(ID), Some(format!("{}/{}", DISC, TOTAL)));
+    let frame = v2.get_frame_by_id(ID).unwrap();
+
+    let mut data = Vec::new();
+    data.push(Encoding::UTF16BE as u8);
+    data.extend(id3::util::string_to_utf16be(&*format!("{}/{}", DISC, TOTAL)).into_iter());
+    assert_eq!(frame.fields_to_bytes(), data);
+}
+//}}}
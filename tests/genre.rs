@@ -0,0 +1,53 @@
+extern crate id3;
+
+use id3::id3v2;
+use id3::Genre;
+use id3::id3v2::Version::*;
+use id3::id3v2::frame::Encoding;
+
+fn genre_names(version: id3v2::Version, text: &str) -> Vec<String> {
+    let mut tag = id3v2::Tag::with_version(version);
+    let id = version.genre_id();
+    tag.add_text_frame_enc(id, text, Encoding::Latin1);
+    tag.genre_names()
+}
+
+#[test]
+fn references() {
+    assert_eq!(genre_names(V3, "(13)"), vec!["Pop"]);
+    assert_eq!(genre_names(V3, "(13)(RX)(CR)"), vec!["Pop", "Remix", "Cover"]);
+    assert_eq!(genre_names(V2, "(255)"), vec!["(255)"]);
+}
+
+#[test]
+fn refinements() {
+    assert_eq!(genre_names(V3, "(4)Eurodisco"), vec!["Disco", "Eurodisco"]);
+    assert_eq!(genre_names(V3, "(17)Rock"), vec!["Rock"]);
+    assert_eq!(genre_names(V3, "((and more)"), vec!["(and more)"]);
+    assert_eq!(genre_names(V3, "Shoegaze"), vec!["Shoegaze"]);
+}
+
+#[test]
+fn multiple_values() {
+    assert_eq!(genre_names(V4, "9\0Shoegaze\0RX"), vec!["Metal", "Shoegaze", "Remix"]);
+}
+
+#[test]
+fn missing() {
+    assert!(id3v2::Tag::new().genre_names().is_empty());
+}
+
+#[test]
+fn genre_codes() {
+    assert_eq!(Genre::from_code(9), Some(Genre::Metal));
+    assert_eq!(Genre::Metal.to_code(), 9);
+    assert_eq!(Genre::from_name("Metal"), Some(Genre::Metal));
+    assert_eq!(Genre::Metal.to_string(), "Metal");
+
+    for &(version, text) in &[(V2, "(9)"), (V3, "(9)"), (V4, "9")] {
+        let mut tag = id3v2::Tag::with_version(version);
+        tag.set_genre_code(Genre::Metal);
+        assert_eq!(tag.text_frame_text(version.genre_id()), Some(text.to_owned()));
+        assert_eq!(tag.genre_names(), vec!["Metal"]);
+    }
+}
@@ -0,0 +1,37 @@
+extern crate id3;
+
+use std::env;
+use std::fs::{self, File};
+use std::io::Write;
+
+use id3::{id3v1, id3v2};
+use id3::id3v2::Version::*;
+use id3::id3v2::frame::{Frame, Id, Encoding};
+use id3::FileTags;
+
+#[test]
+fn reads_both_tags_from_path() {
+    let path = env::temp_dir().join("id3-test-read-from-path.mp3");
+    {
+        let mut v2 = id3v2::Tag::with_version(V4);
+        v2.add_frame(Frame::new_text_frame(Id::V4(*b"TALB"), "album", Encoding::UTF8).unwrap());
+        let mut v1 = id3v1::Tag::new();
+        v1.artist = b"artist".to_vec();
+
+        let mut file = File::create(&path).unwrap();
+        v2.write_to(&mut file, false).unwrap();
+        file.write_all(&[0xFF, 0xFB, 0x90, 0x64]).unwrap();
+        file.write_all(&[0u8; 413]).unwrap();
+        v1.write(&mut file, false).unwrap();
+    }
+
+    let tags = FileTags::from_path(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(tags.path(), Some(&*path));
+    assert_eq!(tags.v2.as_ref().unwrap().text_frame_values(Id::V4(*b"TALB")), Some(vec!["album".to_owned()]));
+    assert_eq!(&tags.v1.as_ref().unwrap().artist[..6], b"artist");
+    assert_eq!(tags.artist(), Some("artist".to_owned()));
+
+    assert!(FileTags::from_tags(None, None).path().is_none());
+}
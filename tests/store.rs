@@ -0,0 +1,180 @@
+extern crate id3;
+
+use std::env;
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::PathBuf;
+
+use id3::{id3v1, id3v2};
+use id3::id3v2::Version::*;
+use id3::id3v2::frame::{Frame, Field, Id, Encoding};
+use id3::FileTags;
+
+/// Returns a few MPEG-1 Layer III frames of silence: 128 kbps, 44.1 kHz.
+fn audio() -> Vec<u8> {
+    let mut audio = vec![];
+    for _ in 0..4 {
+        audio.extend(&[0xFF, 0xFB, 0x90, 0x64]);
+        audio.extend(vec![0u8; 413]);
+    }
+    audio
+}
+
+/// Writes a small MP3 file with an ID3v2 tag and an ID3v1 tag.
+fn fixture(name: &str) -> PathBuf {
+    let path = env::temp_dir().join(name);
+    let mut v2 = id3v2::Tag::with_version(V4);
+    v2.add_frame(Frame::new_text_frame(Id::V4(*b"TIT2"), "old title", Encoding::UTF8).unwrap());
+    let mut v1 = id3v1::Tag::new();
+    v1.title = b"old title".to_vec();
+
+    let mut file = File::create(&path).unwrap();
+    v2.write_to(&mut file, false).unwrap();
+    file.write_all(&*audio()).unwrap();
+    v1.write(&mut file, false).unwrap();
+    path
+}
+
+/// Returns the UTF-8 text of a reread text frame, which holds a string list.
+fn text(tag: &id3v2::Tag, id: Id) -> Option<String> {
+    match &*tag.get_frame_by_id(id).unwrap().fields {
+        &[Field::TextEncoding(Encoding::UTF8), Field::StringList(ref values)] =>
+            Some(String::from_utf8(values.concat()).unwrap()),
+        _ => None,
+    }
+}
+
+fn read_bytes(path: &PathBuf) -> Vec<u8> {
+    let mut bytes = vec![];
+    File::open(path).unwrap().read_to_end(&mut bytes).unwrap();
+    bytes
+}
+
+#[test]
+fn replaces_v2_and_keeps_audio() {
+    let path = fixture("id3-test-store-replace.mp3");
+    let mut tags = FileTags::from_path(&path).unwrap();
+    {
+        let v2 = tags.v2.as_mut().unwrap();
+        v2.add_text_frame(Id::V4(*b"TIT2"), "new title");
+    }
+    tags.store_at_path(&path).unwrap();
+
+    let stored = FileTags::from_path(&path).unwrap();
+    let bytes = read_bytes(&path);
+    fs::remove_file(&path).unwrap();
+
+    let v2 = stored.v2.unwrap();
+    assert_eq!(text(&v2, Id::V4(*b"TIT2")), Some("new title".to_owned()));
+    assert_eq!(&stored.v1.unwrap().title[..9], b"old title");
+    let audio_start = v2.size(false) as usize;
+    assert_eq!(&bytes[audio_start..bytes.len() - 128], &*audio());
+}
+
+#[test]
+fn removes_v1() {
+    let path = fixture("id3-test-store-remove-v1.mp3");
+    let mut tags = FileTags::from_path(&path).unwrap();
+    tags.v1 = None;
+    tags.store_at_path(&path).unwrap();
+
+    let stored = FileTags::from_path(&path).unwrap();
+    let bytes = read_bytes(&path);
+    fs::remove_file(&path).unwrap();
+
+    assert!(stored.v1.is_none());
+    assert!(bytes.ends_with(&*audio()));
+}
+
+#[test]
+fn creates_missing_file() {
+    let path = env::temp_dir().join("id3-test-store-missing.mp3");
+    drop(fs::remove_file(&path));
+    let mut v2 = id3v2::Tag::with_version(V4);
+    v2.add_text_frame(Id::V4(*b"TALB"), "album");
+    FileTags::from_tags(None, Some(v2)).store_at_path(&path).unwrap();
+
+    let stored = FileTags::from_path(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    assert_eq!(text(&stored.v2.unwrap(), Id::V4(*b"TALB")), Some("album".to_owned()));
+}
+
+#[test]
+fn reuses_padding_in_place() {
+    let path = env::temp_dir().join("id3-test-store-in-place.mp3");
+    let mut v2 = id3v2::Tag::with_version(V4);
+    v2.add_text_frame(Id::V4(*b"TIT2"), "old title");
+    {
+        let mut file = File::create(&path).unwrap();
+        assert!(v2.write_to_fit(&mut file, false, 1024).unwrap());
+        file.write_all(&*audio()).unwrap();
+    }
+
+    let mut tags = FileTags::from_path(&path).unwrap();
+    tags.v2.as_mut().unwrap().add_text_frame(Id::V4(*b"TIT2"), "a somewhat longer new title");
+    tags.store_at_path(&path).unwrap();
+
+    let stored = FileTags::from_path(&path).unwrap();
+    let bytes = read_bytes(&path);
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(text(&stored.v2.unwrap(), Id::V4(*b"TIT2")), Some("a somewhat longer new title".to_owned()));
+    assert_eq!(bytes.len(), 1024 + audio().len());
+    assert_eq!(&bytes[1024..], &*audio());
+}
+
+#[test]
+fn rewrites_when_tag_outgrows_padding() {
+    let path = env::temp_dir().join("id3-test-store-outgrow.mp3");
+    let mut v2 = id3v2::Tag::with_version(V4);
+    v2.add_text_frame(Id::V4(*b"TIT2"), "old title");
+    v2.set_padding_len(0);
+    let size = v2.size(false);
+    {
+        let mut file = File::create(&path).unwrap();
+        v2.write_to(&mut file, false).unwrap();
+        file.write_all(&*audio()).unwrap();
+    }
+
+    let mut tags = FileTags::from_path(&path).unwrap();
+    tags.v2.as_mut().unwrap().add_text_frame(Id::V4(*b"TALB"), "album");
+    tags.store_at_path(&path).unwrap();
+
+    let bytes = read_bytes(&path);
+    fs::remove_file(&path).unwrap();
+
+    assert!(bytes.len() > size as usize + audio().len());
+    assert!(bytes.ends_with(&*audio()));
+}
+
+#[test]
+fn removing_an_early_comment_rewrites_the_whole_tag_in_place() {
+    use id3::id3v2::simple::Simple;
+
+    let path = env::temp_dir().join("id3-test-store-remove-comment.mp3");
+    {
+        let mut v2 = id3v2::Tag::with_version(V3);
+        v2.add_comment("first", "early comment");
+        v2.add_frame(Frame::new_text_frame(Id::V3(*b"TIT2"), "title", Encoding::Latin1).unwrap());
+        v2.add_comment("second", "late comment");
+        let mut file = File::create(&path).unwrap();
+        v2.write_to(&mut file, false).unwrap();
+        file.write_all(&*audio()).unwrap();
+    }
+    let original_len = read_bytes(&path).len();
+
+    let mut tags = FileTags::from_path(&path).unwrap();
+    tags.v2.as_mut().unwrap().remove_comment(Some("first"), None);
+    tags.store_at_path(&path).unwrap();
+
+    let stored = FileTags::from_path(&path).unwrap();
+    let bytes = read_bytes(&path);
+    fs::remove_file(&path).unwrap();
+
+    // the tag was padded to its old size rather than the file being rewritten
+    assert_eq!(bytes.len(), original_len);
+    let v2 = stored.v2.unwrap();
+    assert_eq!(v2.comments(), vec![("second".to_owned(), "late comment".to_owned())]);
+    assert_eq!(v2.text_frame_values(Id::V3(*b"TIT2")), Some(vec!["title".to_owned()]));
+    assert_eq!(&bytes[original_len - audio().len()..], &*audio());
+}
@@ -167,3 +167,49 @@ fn utf16be_invalid() {
     v2.add_frame(frame);
 }
 //}}}
+
+// set_track and set_disc {{{
+#[test]
+fn set_track_and_total() {
+    for &version in &[V2, V3, V4] {
+        let mut v2 = id3v2::Tag::with_version(version);
+        assert_eq!(v2.track_pair(), None);
+
+        v2.set_track(TRACK, Some(TOTAL));
+        assert_eq!(v2.text_frame_text(version.track_id()), Some(format!("{}/{}", TRACK, TOTAL)));
+        assert_eq!(v2.track_pair(), Some((TRACK, Some(TOTAL))));
+
+        v2.set_track(TRACK, None);
+        assert_eq!(v2.text_frame_text(version.track_id()), Some(format!("{}", TRACK)));
+        assert_eq!(v2.track_pair(), Some((TRACK, None)));
+
+        v2.set_total_tracks_enc(TOTAL, version.default_encoding());
+        assert_eq!(v2.track_pair(), Some((TRACK, Some(TOTAL))));
+        assert_eq!(v2.get_frames_by_id(version.track_id()).len(), 1);
+    }
+}
+
+#[test]
+fn set_disc_and_total() {
+    for &version in &[V2, V3, V4] {
+        let mut v2 = id3v2::Tag::with_version(version);
+        assert_eq!(v2.disc_pair(), None);
+
+        v2.set_disc(1, Some(2));
+        assert_eq!(v2.text_frame_text(version.disc_id()), Some("1/2".to_owned()));
+        assert_eq!(v2.disc_pair(), Some((1, Some(2))));
+
+        v2.set_disc(2, None);
+        assert_eq!(v2.text_frame_text(version.disc_id()), Some("2".to_owned()));
+        assert_eq!(v2.disc_pair(), Some((2, None)));
+        assert_eq!(v2.track_pair(), None);
+    }
+}
+
+#[test]
+fn total_without_track() {
+    let mut v2 = id3v2::Tag::with_version(V4);
+    v2.set_total_tracks_enc(TOTAL, Encoding::UTF8);
+    assert_eq!(v2.track_pair(), Some((1, Some(TOTAL))));
+}
+//}}}
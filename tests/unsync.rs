@@ -0,0 +1,41 @@
+extern crate id3;
+
+use id3::id3v2;
+use id3::id3v2::Version::*;
+use id3::id3v2::frame::{Frame, Field, Id};
+
+fn private_frame(id: Id) -> Frame {
+    let mut frame = Frame::new(id);
+    frame.fields = vec![Field::Latin1(b"owner".to_vec()), Field::BinaryData(vec![0xFF, 0xE0, 0x12, 0xFF, 0x00, 0xFF])];
+    frame
+}
+
+fn has_false_sync(bytes: &[u8]) -> bool {
+    bytes.windows(2).any(|pair| pair[0] == 0xFF && pair[1] >= 0xE0)
+}
+
+fn round_trip(version: id3v2::Version, id: Id) {
+    let mut tag = id3v2::Tag::with_version(version);
+    tag.add_frame(private_frame(id));
+
+    let mut buf = vec![];
+    let written = tag.write_to(&mut buf, true).unwrap();
+    assert_eq!(written as usize, buf.len());
+    assert_eq!(tag.size(true) as usize, buf.len());
+    assert!(!has_false_sync(&buf[10..]));
+
+    let reread = id3v2::read_tag(&mut &*buf).unwrap().unwrap();
+    assert!(reread.flags().get(id3v2::TagFlag::Unsynchronization));
+    assert_eq!(reread.frames.len(), 1);
+    assert_eq!(reread.frames[0].fields, private_frame(id).fields);
+}
+
+#[test]
+fn v3_round_trip() {
+    round_trip(V3, Id::V3(*b"PRIV"));
+}
+
+#[test]
+fn v4_round_trip() {
+    round_trip(V4, Id::V4(*b"PRIV"));
+}